@@ -3,9 +3,9 @@ extern crate bencher;
 use bencher::Bencher;
 
 extern crate rtriangulate;
-use rtriangulate::{triangulate, TriangulationPoint};
+use rtriangulate::{triangulate, triangulate_approx, TriangulationPoint};
 
-#[cfg_attr(rustfmt, rustfmt_skip)]
+#[rustfmt::skip]
 const POINTS: [TriangulationPoint<f32>; 100] = [
     TriangulationPoint {x:   1.0, y: 117.0}, TriangulationPoint {x:   3.0, y: 438.0},
     TriangulationPoint {x:   3.0, y: 524.0}, TriangulationPoint {x:  10.0, y: 253.0},
@@ -113,6 +113,11 @@ fn bench_one_hundred_points(bench: &mut Bencher) {
     bench.iter(|| triangulate(points));
 }
 
+fn bench_one_hundred_points_approx(bench: &mut Bencher) {
+    let points = &POINTS[..100];
+    bench.iter(|| triangulate_approx(points, 150.0));
+}
+
 benchmark_group!(
     benches,
     bench_three_points,
@@ -125,6 +130,7 @@ benchmark_group!(
     bench_seventy_points,
     bench_eighty_points,
     bench_ninety_points,
-    bench_one_hundred_points
+    bench_one_hundred_points,
+    bench_one_hundred_points_approx
 );
 benchmark_main!(benches);
@@ -0,0 +1,189 @@
+// Copyright 2017-2018 Samuel Loretan <tynril@gmail.com> -- See LICENSE file
+
+//! Approximate triangulation, trading the Delaunay property for speed.
+
+use super::{
+    bounding_supertriangle, circumcircle_center, in_computed_circumcircle, is_outside_shortcut,
+    is_sorted_for_triangulation, remove_shared_edges, ConcatPoints, Coordinate, Point, Result,
+    Triangle,
+};
+
+/// Generate an approximate Delaunay triangulation of given set of points, faster than
+/// `triangulate` does.
+///
+/// As the points are inserted in order, sweeping along the x axis, the triangles only get tested
+/// against the points less than `cell_size` away from the center of their circumcircle, along
+/// that axis: the triangles left behind by the sweep are not tested anymore. This limits every
+/// insertion to the triangles of a strip of the mesh around the new point, instead of all of them.
+/// The triangles whose circumcircle is smaller than `cell_size` are tested against all the points
+/// which can be in it, so they are still exactly Delaunay.
+///
+/// The larger triangles, mostly along the hull or around regions without points, can miss some
+/// points which are in their circumcircle: they aren't Delaunay anymore, but the output is still a
+/// valid triangulation, without overlapping triangles. When skipping a triangle would make the
+/// new triangles overlap it, the triangles left behind are tested again for that insertion.
+///
+/// The `cell_size` is the knob trading the accuracy for the speed: a few times the typical
+/// distance between neighbouring points keeps almost all the triangles Delaunay, while being much
+/// faster than `triangulate` on large sets of points. A `cell_size` as large as the extent of the
+/// points gives exactly the output of `triangulate`, though not in the same order.
+///
+/// As with `triangulate`, the points must be sorted.
+///
+/// Example:
+///
+/// ```rust
+/// use rtriangulate::{triangulate_approx, TriangulationPoint, Triangle};
+///
+/// let points = [
+///     TriangulationPoint::new(10.0, 50.0),
+///     TriangulationPoint::new(25.0, 40.0),
+///     TriangulationPoint::new(30.0, 40.0)
+/// ];
+/// assert_eq!(triangulate_approx(&points, 5.0).unwrap(), [Triangle(1, 0, 2)]);
+/// ```
+pub fn triangulate_approx<T, P>(points: &[P], cell_size: T) -> Result<Vec<Triangle>>
+where
+    T: Coordinate,
+    P: Point<T>,
+{
+    let supertriangle = bounding_supertriangle(points)?;
+    debug_assert!(
+        is_sorted_for_triangulation(points),
+        "the points must be sorted with `sort_points` before being triangulated"
+    );
+    let all_points = ConcatPoints::new(points, &supertriangle);
+    let points_count = points.len();
+
+    // The triangles still tested, and those left behind by the sweep.
+    let mut active = vec![Triangle(points_count, points_count + 1, points_count + 2)];
+    let mut behind = Vec::new();
+    let mut cavity = Vec::new();
+    let mut edges = Vec::with_capacity(18);
+    let mut to_remove = Vec::with_capacity(18);
+    let contains = |i: usize, t: &Triangle| {
+        let (point, t0, t1, t2) = (
+            all_points.point(i),
+            all_points.point(t.0),
+            all_points.point(t.1),
+            all_points.point(t.2),
+        );
+        !is_outside_shortcut(&point, &t0, &t1, &t2)
+            && in_computed_circumcircle(&point, &t0, &t1, &t2, false)
+    };
+
+    for i in 0..points_count {
+        let point = all_points.point(i);
+
+        // Leave the triangles behind once the sweep is far enough past their circumcircle.
+        let mut k = 0;
+        while k < active.len() {
+            let t = &active[k];
+            let (t0, t1, t2) = (
+                all_points.point(t.0),
+                all_points.point(t.1),
+                all_points.point(t.2),
+            );
+            let (circ_x, circ_y) = circumcircle_center(&t0, &t1, &t2);
+            let past = point.x() - circ_x;
+            let radius_sq =
+                (t1.x() - circ_x) * (t1.x() - circ_x) + (t1.y() - circ_y) * (t1.y() - circ_y);
+            if past > T::zero() && (past > cell_size || past * past > radius_sq) {
+                behind.push(active.swap_remove(k));
+            } else {
+                k += 1;
+            }
+        }
+
+        // Find the cavity, making sure that its new triangles are all turned clockwise, with the
+        // whole mesh if they aren't.
+        for retry in 0..2 {
+            if retry == 1 {
+                let mut k = 0;
+                while k < behind.len() {
+                    if contains(i, &behind[k]) {
+                        active.push(behind.swap_remove(k));
+                    } else {
+                        k += 1;
+                    }
+                }
+            }
+
+            cavity.clear();
+            edges.clear();
+            for (k, t) in active.iter().enumerate() {
+                if contains(i, t) {
+                    cavity.push(k);
+                    edges.extend_from_slice(&t.edges());
+                }
+            }
+            remove_shared_edges(&mut edges, &mut to_remove);
+
+            let overlaps = edges.iter().any(|e| {
+                let (a, b) = (all_points.point(e.0), all_points.point(e.1));
+                (b.x() - a.x()) * (point.y() - a.y()) - (b.y() - a.y()) * (point.x() - a.x())
+                    > T::zero()
+            });
+            if !overlaps {
+                break;
+            }
+        }
+
+        // The cavity is in increasing order, so removing it from the end keeps its indices valid.
+        for &k in cavity.iter().rev() {
+            active.swap_remove(k);
+        }
+        active.extend(edges.iter().map(|e| Triangle(e.0, e.1, i)));
+    }
+
+    // Remove triangles with supertriangle vertices
+    active.extend(behind);
+    active.retain(|t| t.0 < points_count && t.1 < points_count && t.2 < points_count);
+    Ok(active)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{
+        is_edge_manifold, sort_points, triangulate, Triangle, TriangulateError, TriangulationPoint,
+    };
+    use super::triangulate_approx;
+
+    fn canonical(triangles: Vec<Triangle>) -> Vec<Triangle> {
+        let mut triangles: Vec<_> = triangles.iter().map(Triangle::canonical).collect();
+        triangles.sort();
+        triangles
+    }
+
+    #[test]
+    fn test_approx() {
+        let mut points: Vec<_> = (0..400)
+            .map(|i| TriangulationPoint::new((i * 37 % 401) as f64, (i * 59 % 397) as f64))
+            .collect();
+        points.sort_by(sort_points);
+
+        // A large enough cell gives the exact triangulation.
+        assert_eq!(
+            canonical(triangulate_approx(&points, 1000.0).unwrap()),
+            canonical(triangulate(&points).unwrap())
+        );
+
+        // A small one gives a valid triangulation, with clockwise triangles which don't overlap.
+        let triangles = triangulate_approx(&points, 20.0).unwrap();
+        assert!(is_edge_manifold(&triangles));
+        let area = |t: &Triangle| {
+            let (a, b, c) = (&points[t.0], &points[t.1], &points[t.2]);
+            (b.x - a.x) * (c.y - a.y) - (b.y - a.y) * (c.x - a.x)
+        };
+        assert!(triangles.iter().all(|t| area(t) <= 0.0));
+        let exact_area: f64 = triangulate(&points).unwrap().iter().map(|t| -area(t)).sum();
+        let approx_area: f64 = triangles.iter().map(|t| -area(t)).sum();
+        assert!(approx_area <= exact_area * 1.01);
+        assert!(approx_area >= exact_area * 0.9);
+
+        match triangulate_approx(&points[..2], 20.0) {
+            Err(TriangulateError::NotEnoughPoints) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+}
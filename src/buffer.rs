@@ -0,0 +1,351 @@
+// Copyright 2017-2018 Samuel Loretan <tynril@gmail.com> -- See LICENSE file
+
+//! The buffers a triangulation is built into, regardless of their allocator or storage.
+
+#[cfg(feature = "allocator-api")]
+use std::alloc::Allocator;
+use std::ops::{Deref, DerefMut};
+
+/// The operations the triangulation needs on its buffers.
+///
+/// This only exists so that the triangulation can be written once for the vectors of any
+/// allocator, when the `allocator-api` feature is enabled, for the plain vectors otherwise, for
+/// the fixed slices of `triangulate_fixed`, and for the stack scratch buffers of the
+/// `stack-scratch` feature.
+pub(crate) trait Buffer<E>: DerefMut<Target = [E]> {
+    fn push(&mut self, element: E);
+
+    fn clear(&mut self);
+
+    fn retain<F>(&mut self, f: F)
+    where
+        F: FnMut(&E) -> bool;
+
+    fn extend<I>(&mut self, elements: I)
+    where
+        I: IntoIterator<Item = E>;
+
+    fn extend_from_slice(&mut self, elements: &[E])
+    where
+        E: Clone;
+
+    fn resize(&mut self, len: usize, value: E)
+    where
+        E: Clone;
+}
+
+macro_rules! impl_buffer {
+    () => {
+        #[inline(always)]
+        fn push(&mut self, element: E) {
+            Vec::push(self, element)
+        }
+
+        #[inline(always)]
+        fn clear(&mut self) {
+            Vec::clear(self)
+        }
+
+        #[inline(always)]
+        fn retain<F>(&mut self, f: F)
+        where
+            F: FnMut(&E) -> bool,
+        {
+            Vec::retain(self, f)
+        }
+
+        #[inline(always)]
+        fn extend<I>(&mut self, elements: I)
+        where
+            I: IntoIterator<Item = E>,
+        {
+            Extend::extend(self, elements)
+        }
+
+        #[inline(always)]
+        fn extend_from_slice(&mut self, elements: &[E])
+        where
+            E: Clone,
+        {
+            Vec::extend_from_slice(self, elements)
+        }
+
+        #[inline(always)]
+        fn resize(&mut self, len: usize, value: E)
+        where
+            E: Clone,
+        {
+            Vec::resize(self, len, value)
+        }
+    };
+}
+
+#[cfg(not(feature = "allocator-api"))]
+impl<E> Buffer<E> for Vec<E> {
+    impl_buffer!();
+}
+
+#[cfg(feature = "allocator-api")]
+impl<E, A> Buffer<E> for Vec<E, A>
+where
+    A: Allocator,
+{
+    impl_buffer!();
+}
+
+/// A buffer over a fixed slice, which can't grow past the length of that slice.
+///
+/// Pushing into a full buffer drops the element and marks the buffer as overflowed, so that the
+/// triangulation can be run to completion without allocating, and its result discarded after.
+pub(crate) struct FixedBuffer<'a, E: 'a> {
+    storage: &'a mut [E],
+    len: usize,
+    overflowed: bool,
+}
+
+impl<'a, E: 'a> FixedBuffer<'a, E> {
+    pub(crate) fn new(storage: &'a mut [E]) -> Self {
+        FixedBuffer {
+            storage,
+            len: 0,
+            overflowed: false,
+        }
+    }
+
+    /// Returns true if an element was ever pushed into this buffer while it was full.
+    pub(crate) fn overflowed(&self) -> bool {
+        self.overflowed
+    }
+
+    /// Returns the number of elements this buffer can hold.
+    #[cfg(any(test, feature = "stack-scratch"))]
+    pub(crate) fn capacity(&self) -> usize {
+        self.storage.len()
+    }
+}
+
+impl<'a, E: 'a> Deref for FixedBuffer<'a, E> {
+    type Target = [E];
+
+    fn deref(&self) -> &[E] {
+        &self.storage[..self.len]
+    }
+}
+
+impl<'a, E: 'a> DerefMut for FixedBuffer<'a, E> {
+    fn deref_mut(&mut self) -> &mut [E] {
+        &mut self.storage[..self.len]
+    }
+}
+
+impl<'a, E: 'a> Buffer<E> for FixedBuffer<'a, E> {
+    fn push(&mut self, element: E) {
+        if self.len < self.storage.len() {
+            self.storage[self.len] = element;
+            self.len += 1;
+        } else {
+            self.overflowed = true;
+        }
+    }
+
+    fn clear(&mut self) {
+        self.len = 0;
+    }
+
+    fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&E) -> bool,
+    {
+        // Compact the kept elements to the front, in order.
+        let mut kept = 0;
+        for i in 0..self.len {
+            if f(&self.storage[i]) {
+                self.storage.swap(kept, i);
+                kept += 1;
+            }
+        }
+        self.len = kept;
+    }
+
+    fn extend<I>(&mut self, elements: I)
+    where
+        I: IntoIterator<Item = E>,
+    {
+        for element in elements {
+            self.push(element);
+        }
+    }
+
+    fn extend_from_slice(&mut self, elements: &[E])
+    where
+        E: Clone,
+    {
+        self.extend(elements.iter().cloned());
+    }
+
+    fn resize(&mut self, len: usize, value: E)
+    where
+        E: Clone,
+    {
+        if len <= self.len {
+            self.len = len;
+        } else {
+            while self.len < len && !self.overflowed {
+                self.push(value.clone());
+            }
+        }
+    }
+}
+
+/// A buffer over a fixed slice, usually on the stack, which moves its elements to a vector once
+/// it gets full.
+///
+/// The scratch buffers of the triangulation hold the edges of a single cavity, which only has a
+/// handful of triangles for most insertions: this keeps those on the stack, without failing on
+/// the rare larger cavities.
+#[cfg(any(test, feature = "stack-scratch"))]
+pub(crate) struct SpillBuffer<'a, E: 'a> {
+    fixed: FixedBuffer<'a, E>,
+    spilled: Option<Vec<E>>,
+}
+
+#[cfg(any(test, feature = "stack-scratch"))]
+impl<'a, E: 'a + Clone> SpillBuffer<'a, E> {
+    pub(crate) fn new(storage: &'a mut [E]) -> Self {
+        SpillBuffer {
+            fixed: FixedBuffer::new(storage),
+            spilled: None,
+        }
+    }
+
+    /// Returns true if the elements were moved to the heap.
+    #[cfg(test)]
+    fn spilled(&self) -> bool {
+        self.spilled.is_some()
+    }
+
+    /// Returns the vector to add `additional` elements to, moving the elements to it if they
+    /// don't fit in the slice anymore, or `None` if they still do.
+    fn spill(&mut self, additional: usize) -> Option<&mut Vec<E>> {
+        if self.spilled.is_none() && self.fixed.len() + additional > self.fixed.capacity() {
+            let mut vector = Vec::with_capacity(2 * (self.fixed.len() + additional));
+            vector.extend_from_slice(&self.fixed);
+            self.fixed.clear();
+            self.spilled = Some(vector);
+        }
+        self.spilled.as_mut()
+    }
+}
+
+#[cfg(any(test, feature = "stack-scratch"))]
+impl<'a, E: 'a> Deref for SpillBuffer<'a, E> {
+    type Target = [E];
+
+    fn deref(&self) -> &[E] {
+        match self.spilled {
+            Some(ref vector) => vector,
+            None => &self.fixed,
+        }
+    }
+}
+
+#[cfg(any(test, feature = "stack-scratch"))]
+impl<'a, E: 'a> DerefMut for SpillBuffer<'a, E> {
+    fn deref_mut(&mut self) -> &mut [E] {
+        match self.spilled {
+            Some(ref mut vector) => vector,
+            None => &mut self.fixed,
+        }
+    }
+}
+
+#[cfg(any(test, feature = "stack-scratch"))]
+impl<'a, E: 'a + Clone> Buffer<E> for SpillBuffer<'a, E> {
+    fn push(&mut self, element: E) {
+        match self.spill(1) {
+            Some(vector) => vector.push(element),
+            None => self.fixed.push(element),
+        }
+    }
+
+    fn clear(&mut self) {
+        // Once spilled, keep using the vector, as its allocation was already paid for.
+        match self.spilled {
+            Some(ref mut vector) => vector.clear(),
+            None => self.fixed.clear(),
+        }
+    }
+
+    fn retain<F>(&mut self, f: F)
+    where
+        F: FnMut(&E) -> bool,
+    {
+        match self.spilled {
+            Some(ref mut vector) => vector.retain(f),
+            None => self.fixed.retain(f),
+        }
+    }
+
+    fn extend<I>(&mut self, elements: I)
+    where
+        I: IntoIterator<Item = E>,
+    {
+        for element in elements {
+            self.push(element);
+        }
+    }
+
+    fn extend_from_slice(&mut self, elements: &[E])
+    where
+        E: Clone,
+    {
+        match self.spill(elements.len()) {
+            Some(vector) => vector.extend_from_slice(elements),
+            None => self.fixed.extend_from_slice(elements),
+        }
+    }
+
+    fn resize(&mut self, len: usize, value: E)
+    where
+        E: Clone,
+    {
+        let additional = len.saturating_sub(self.len());
+        match self.spill(additional) {
+            Some(vector) => vector.resize(len, value),
+            None => self.fixed.resize(len, value),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Buffer, SpillBuffer};
+
+    #[test]
+    fn test_spill_buffer() {
+        let mut storage = [0; 4];
+        let mut buffer = SpillBuffer::new(&mut storage);
+        buffer.extend_from_slice(&[1, 2, 3]);
+        buffer.push(4);
+        assert!(!buffer.spilled());
+        assert_eq!(&buffer[..], [1, 2, 3, 4]);
+
+        // Going past the slice moves everything to the heap, in order.
+        buffer.extend(5..7);
+        assert!(buffer.spilled());
+        assert_eq!(&buffer[..], [1, 2, 3, 4, 5, 6]);
+        buffer.retain(|&e| e % 2 == 0);
+        assert_eq!(&buffer[..], [2, 4, 6]);
+        buffer.resize(5, 0);
+        assert_eq!(&buffer[..], [2, 4, 6, 0, 0]);
+        buffer.clear();
+        assert!(buffer.is_empty());
+
+        // Resizing past the slice spills too.
+        let mut storage = [0; 2];
+        let mut buffer = SpillBuffer::new(&mut storage);
+        buffer.resize(3, 7);
+        assert!(buffer.spilled());
+        assert_eq!(&buffer[..], [7, 7, 7]);
+    }
+}
@@ -0,0 +1,119 @@
+// Copyright 2017-2018 Samuel Loretan <tynril@gmail.com> -- See LICENSE file
+
+//! The steps of the insertion of a point into a triangulation, as done by `triangulate`.
+
+use super::{
+    in_circumcircle, remove_shared_edges, Coordinate, Edge, Point, Triangle, TriangulationPoint,
+};
+
+/// Returns the indices of the triangles whose circumcircle contains a new point, in increasing
+/// order.
+///
+/// This is the cavity which the Bowyer-Watson algorithm removes from the mesh to insert the point,
+/// found with the same predicate as `triangulate`: points exactly on a circumcircle are inside of
+/// it. The `triangles` must index into `points`, and the new point doesn't need to be one of them.
+/// For a Delaunay triangulation, the cavity is a connected region around the point, whose boundary
+/// is given by `cavity_boundary`.
+///
+/// Example:
+///
+/// ```rust
+/// use rtriangulate::{find_cavity, TriangulationPoint, Triangle};
+///
+/// let points = [
+///     TriangulationPoint::new(0.0, 0.0),
+///     TriangulationPoint::new(0.0, 2.0),
+///     TriangulationPoint::new(2.0, 0.0),
+///     TriangulationPoint::new(2.0, 3.0)
+/// ];
+/// let triangles = [Triangle(0, 1, 2), Triangle(2, 1, 3)];
+/// assert_eq!(find_cavity(&points, &triangles, &TriangulationPoint::new(0.2, 0.2)), [0]);
+/// assert_eq!(find_cavity(&points, &triangles, &TriangulationPoint::new(1.0, 1.0)), [0, 1]);
+/// ```
+pub fn find_cavity<T, P, Q>(points: &[P], triangles: &[Triangle], new_point: &Q) -> Vec<usize>
+where
+    T: Coordinate,
+    P: Point<T>,
+    Q: Point<T>,
+{
+    let new_point = TriangulationPoint::new(new_point.x(), new_point.y());
+    let copy = |p: &P| TriangulationPoint::new(p.x(), p.y());
+    triangles
+        .iter()
+        .enumerate()
+        .filter(|&(_, t)| {
+            let (t0, t1, t2) = (copy(&points[t.0]), copy(&points[t.1]), copy(&points[t.2]));
+            in_circumcircle(&new_point, &t0, &t1, &t2, false)
+        })
+        .map(|(i, _)| i)
+        .collect()
+}
+
+/// Returns the boundary of a cavity, as the edges of its triangles which aren't shared by two of
+/// them.
+///
+/// The `cavity` holds indices into `triangles`, as returned by `find_cavity`. The edges keep the
+/// direction they have in their triangle, and the order in which they are found: inserting the
+/// point then means replacing the triangles of the cavity by `Triangle(edge.0, edge.1, point)`
+/// for every edge, which keeps the winding of the triangles.
+///
+/// Example:
+///
+/// ```rust
+/// use rtriangulate::{cavity_boundary, Edge, Triangle};
+///
+/// let triangles = [Triangle(0, 1, 2), Triangle(2, 1, 3)];
+/// assert_eq!(
+///     cavity_boundary(&triangles, &[0, 1]),
+///     [Edge(0, 1), Edge(2, 0), Edge(1, 3), Edge(3, 2)]
+/// );
+/// ```
+pub fn cavity_boundary(triangles: &[Triangle], cavity: &[usize]) -> Vec<Edge> {
+    let mut edges = Vec::with_capacity(cavity.len() * 3);
+    for &t in cavity {
+        edges.extend_from_slice(&triangles[t].edges());
+    }
+    remove_shared_edges(&mut edges, &mut Vec::new());
+    edges
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{is_delaunay, sort_points, triangulate, Triangle, TriangulationPoint};
+    use super::{cavity_boundary, find_cavity};
+
+    #[test]
+    fn test_cavity() {
+        let mut points: Vec<_> = (0..40)
+            .map(|i| TriangulationPoint::new((i * 37 % 101) as f64, (i * 59 % 97) as f64))
+            .collect();
+        for &(x, y) in &[
+            (-20.0, -20.0),
+            (-20.0, 120.0),
+            (120.0, -20.0),
+            (120.0, 120.0),
+        ] {
+            points.push(TriangulationPoint::new(x, y));
+        }
+        points.sort_by(sort_points);
+        let triangles = triangulate(&points).unwrap();
+
+        // Inserting a point by hand gives a Delaunay triangulation again.
+        let new_point = TriangulationPoint::new(51.5, 48.25);
+        let cavity = find_cavity(&points, &triangles, &new_point);
+        assert!(!cavity.is_empty());
+        let boundary = cavity_boundary(&triangles, &cavity);
+        assert_eq!(boundary.len(), cavity.len() + 2);
+
+        let mut inserted: Vec<Triangle> = triangles
+            .iter()
+            .enumerate()
+            .filter(|&(i, _)| !cavity.contains(&i))
+            .map(|(_, t)| *t)
+            .collect();
+        inserted.extend(boundary.iter().map(|e| Triangle(e.0, e.1, points.len())));
+        points.push(new_point);
+        assert_eq!(inserted.len(), triangles.len() + 2);
+        assert!(is_delaunay(&points, &inserted));
+    }
+}
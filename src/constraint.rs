@@ -0,0 +1,920 @@
+// Copyright 2017-2018 Samuel Loretan <tynril@gmail.com> -- See LICENSE file
+
+//! Constrained triangulations, whose edges follow given segments.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use super::incremental::bounds_supertriangle;
+use super::validate::{circle_tolerance, circumcircle};
+use super::warm::{rotate_to, third_vertex};
+use super::{
+    in_circumcircle, remove_shared_edges, Coordinate, Edge, Point, Result, Triangle,
+    TriangulateError, TriangulationPoint,
+};
+
+/// Inserts an edge into a triangulation, by flipping the edges crossing it until it appears.
+///
+/// This makes an edge between two vertices of the triangulation, for the mesh to follow a
+/// segment which must be in it, such as to annotate or cut along it. The edges crossing the
+/// segment are flipped until none is left, then the new edges are flipped again if they aren't
+/// Delaunay, leaving the inserted edge alone: the triangles far from the segment are untouched, and
+/// those around it are Delaunay again, except where the inserted edge prevents it. No point is
+/// added, so the number of triangles doesn't change, and neither does their winding.
+///
+/// The `triangles` must index into `points`, be consistently oriented and cover a convex region,
+/// as returned by `triangulate`. Nothing changes when the edge already is in the triangulation.
+/// This returns `TriangulateError::MissingVertex` when a vertex of the edge is out of range of the
+/// points or used by no triangle, and `TriangulateError::Degenerate` when the two vertices are
+/// the same, or when another vertex lies on the segment between them.
+///
+/// Example:
+///
+/// ```rust
+/// use rtriangulate::{insert_constraint_edge, triangulate, Edge, TriangulationPoint, Triangle};
+///
+/// let points = [
+///     TriangulationPoint::new(-3.0, 0.0),
+///     TriangulationPoint::new(0.0, -1.0),
+///     TriangulationPoint::new(0.0, 1.0),
+///     TriangulationPoint::new(3.0, 0.0)
+/// ];
+/// let mut triangles = triangulate(&points).unwrap();
+/// assert_eq!(triangles, [Triangle(1, 0, 2), Triangle(1, 2, 3)]);
+///
+/// // The long diagonal isn't Delaunay, but can still be forced into the mesh.
+/// insert_constraint_edge(&points, &mut triangles, Edge(0, 3)).unwrap();
+/// assert_eq!(triangles, [Triangle(2, 3, 0), Triangle(3, 1, 0)]);
+/// ```
+pub fn insert_constraint_edge<T, P>(
+    points: &[P],
+    triangles: &mut [Triangle],
+    edge: Edge,
+) -> Result<()>
+where
+    T: Coordinate,
+    P: Point<T>,
+{
+    let used =
+        |v: usize| v < points.len() && triangles.iter().any(|t| t.0 == v || t.1 == v || t.2 == v);
+    if !used(edge.0) || !used(edge.1) {
+        return Err(TriangulateError::MissingVertex);
+    }
+    if edge.0 == edge.1 {
+        return Err(TriangulateError::Degenerate);
+    }
+
+    // The segment can't be made of several edges.
+    let (a, b) = (edge.0, edge.1);
+    let used: Vec<usize> = triangles.iter().flat_map(|t| vec![t.0, t.1, t.2]).collect();
+    if vertex_on_segment(points, used.into_iter(), a, b).is_some() {
+        return Err(TriangulateError::Degenerate);
+    }
+
+    force_edge(
+        points,
+        triangles,
+        &mut edge_sides(triangles),
+        &edge,
+        &HashSet::new(),
+    )
+}
+
+/// A constrained Delaunay triangulation built incrementally, by inserting points and constraint
+/// edges one after the other.
+///
+/// This is the constrained counterpart of `Triangulation`: the triangles are Delaunay, except
+/// where the constraint edges prevent it, as the constraints are never flipped. Inserting a point
+/// only changes the triangles around it which it can see without crossing a constraint, and
+/// inserting a constraint flips the edges crossing it, as `insert_constraint_edge` does. When a
+/// new point lands exactly on a constraint, the constraint is split in two at the point, so that
+/// the mesh still follows it.
+///
+/// As with `Triangulation`, the bounds of the points must be known upfront, and the triangles are
+/// clockwise.
+///
+/// Example:
+///
+/// ```rust
+/// use rtriangulate::{ConstrainedTriangulation, Edge, TriangulationPoint};
+///
+/// let mut triangulation = ConstrainedTriangulation::new(
+///     TriangulationPoint::new(0.0, 0.0),
+///     TriangulationPoint::new(4.0, 4.0)
+/// ).unwrap();
+/// for &(x, y) in &[(0.0, 1.0), (2.0, 0.0), (2.0, 2.0), (4.0, 1.0)] {
+///     triangulation.insert_point(&TriangulationPoint::new(x, y));
+/// }
+///
+/// // The long diagonal isn't Delaunay, but stays as a constraint.
+/// triangulation.insert_constraint(Edge(0, 3)).unwrap();
+/// assert!(triangulation.triangles().iter().all(|t| t.edges().contains(&Edge(0, 3))));
+///
+/// // A point on it splits it.
+/// let middle = triangulation.insert_point(&TriangulationPoint::new(2.0, 1.0));
+/// assert_eq!(triangulation.constraints(), [Edge(0, middle), Edge(middle, 3)]);
+/// assert_eq!(triangulation.triangles().len(), 4);
+/// ```
+#[derive(Clone, Debug)]
+pub struct ConstrainedTriangulation<T> {
+    /// The vertices of the supertriangle, then the inserted points.
+    points: Vec<TriangulationPoint<T>>,
+    /// The triangles between all the vertices, including the supertriangle.
+    triangles: Vec<Triangle>,
+    /// The triangles on both sides of every edge, as indices into `triangles`.
+    sides: HashMap<Edge, Vec<usize>>,
+    /// The constraint edges, between indices of `points`.
+    constraints: HashSet<Edge>,
+}
+
+/// The number of vertices of the supertriangle, before the inserted points.
+const SUPER_VERTICES: usize = 3;
+
+/// Generate the constrained Delaunay triangulation of the given points, whose edges include the
+/// given constraint edges.
+///
+/// The points are inserted into a `ConstrainedTriangulation`, then the constraints, which are
+/// edges between indices into `points`. The triangles index into `points` too, and are clockwise.
+/// The points don't need to be sorted, and a point at the same position as a previous one is
+/// left out of the triangles, its previous occurrence being used instead.
+///
+/// The constraints are checked before anything is triangulated: this returns
+/// `TriangulateError::ConstraintOutOfRange` with the first edge which has a vertex out of range
+/// of the points, such as a stale index after filtering the points. This otherwise fails as
+/// `ConstrainedTriangulation::insert_constraint` does, with `TriangulateError::Degenerate` for an
+/// edge between two coincident points, and `TriangulateError::IntersectingConstraints` when two
+/// constraints cross. `TriangulateError::NotEnoughPoints` is returned for fewer than three points,
+/// and `TriangulateError::Degenerate` when they are all coincident.
+///
+/// Example:
+///
+/// ```rust
+/// use rtriangulate::{triangulate_constrained, Edge, TriangulateError, TriangulationPoint};
+///
+/// let points = [
+///     TriangulationPoint::new(-3.0, 0.0),
+///     TriangulationPoint::new(0.0, -1.0),
+///     TriangulationPoint::new(0.0, 1.0),
+///     TriangulationPoint::new(3.0, 0.0)
+/// ];
+/// let triangles = triangulate_constrained(&points, &[Edge(0, 3)]).unwrap();
+/// assert!(triangles.iter().all(|t| t.edges().contains(&Edge(0, 3))));
+///
+/// match triangulate_constrained(&points, &[Edge(0, 3), Edge(2, 4)]) {
+///     Err(TriangulateError::ConstraintOutOfRange { edge }) => assert_eq!(edge, Edge(2, 4)),
+///     other => panic!("unexpected result: {:?}", other),
+/// }
+/// ```
+pub fn triangulate_constrained<T, P>(points: &[P], constraints: &[Edge]) -> Result<Vec<Triangle>>
+where
+    T: Coordinate,
+    P: Point<T>,
+{
+    if let Some(edge) = constraints
+        .iter()
+        .find(|e| e.0 >= points.len() || e.1 >= points.len())
+    {
+        return Err(TriangulateError::ConstraintOutOfRange { edge: edge.clone() });
+    }
+    if points.len() < 3 {
+        return Err(TriangulateError::NotEnoughPoints);
+    }
+
+    let first = TriangulationPoint::new(points[0].x(), points[0].y());
+    let (min, max) = points.iter().fold((first, first), |(min, max), p| {
+        (
+            TriangulationPoint::new(min.x.min(p.x()), min.y.min(p.y())),
+            TriangulationPoint::new(max.x.max(p.x()), max.y.max(p.y())),
+        )
+    });
+    let mut triangulation = ConstrainedTriangulation::new(min, max)?;
+
+    // Map the indices of the points to the ones in the triangulation, and back.
+    let mut inserted = Vec::with_capacity(points.len());
+    let mut original = Vec::with_capacity(points.len());
+    for (i, p) in points.iter().enumerate() {
+        let index = triangulation.insert_point(p);
+        if index == original.len() {
+            original.push(i);
+        }
+        inserted.push(index);
+    }
+    for e in constraints {
+        triangulation.insert_constraint(Edge(inserted[e.0], inserted[e.1]))?;
+    }
+
+    Ok(triangulation
+        .triangles()
+        .into_iter()
+        .map(|t| Triangle(original[t.0], original[t.1], original[t.2]))
+        .collect())
+}
+
+impl<T> ConstrainedTriangulation<T>
+where
+    T: Coordinate,
+{
+    /// Makes an empty triangulation, for points within the given bounds.
+    ///
+    /// Returns `TriangulateError::Degenerate` if the bounds have no extent at all.
+    pub fn new(min: TriangulationPoint<T>, max: TriangulationPoint<T>) -> Result<Self> {
+        let triangles = vec![Triangle(0, 1, 2)];
+        Ok(ConstrainedTriangulation {
+            points: bounds_supertriangle(min, max)?.to_vec(),
+            sides: edge_sides(&triangles),
+            triangles,
+            constraints: HashSet::new(),
+        })
+    }
+
+    /// Inserts a point in the triangulation, and returns its index.
+    ///
+    /// The point gets the next index, which is the number of points inserted before it, unless it
+    /// is at the same position as a previous point: nothing changes then, and the index of that
+    /// point is returned. It must lie within the bounds given to `new`. A constraint going through
+    /// the point is split in two constraints, joining the point to either end.
+    pub fn insert_point<P>(&mut self, point: &P) -> usize
+    where
+        P: Point<T>,
+    {
+        let i = self.points.len();
+        self.points
+            .push(TriangulationPoint::new(point.x(), point.y()));
+
+        // The triangles containing the point: the one found by walking to it, and the one on the
+        // other side of its edge when the point lies on it.
+        let found = self.locate(i);
+        let mut seeds = vec![found];
+        for e in self.triangles[found].edges().iter() {
+            if orientation(&self.points, e.0, e.1, i) == 0 {
+                seeds.extend(self.sides[e].iter().filter(|&&n| n != found));
+            }
+        }
+        let (p, t) = (self.points[i], self.triangles[found]);
+        if let Some(&existing) = [t.0, t.1, t.2].iter().find(|&&v| self.points[v] == p) {
+            self.points.pop();
+            return existing - SUPER_VERTICES;
+        }
+        let points = &self.points;
+
+        // Split the constraint going through the point, which is an edge of those triangles.
+        let split: Vec<Edge> = seeds
+            .iter()
+            .flat_map(|&k| self.triangles[k].edges().to_vec())
+            .filter(|e| orientation(points, e.0, e.1, i) == 0 && self.constraints.contains(e))
+            .collect();
+        for e in split {
+            if self.constraints.remove(&e) {
+                self.constraints.insert(Edge(e.0, i));
+                self.constraints.insert(Edge(i, e.1));
+            }
+        }
+
+        // The cavity grows from the triangles containing the point, without crossing constraints.
+        let mut in_cavity: HashSet<usize> = seeds.iter().cloned().collect();
+        let mut cavity = seeds.clone();
+        let mut queue = seeds;
+        while let Some(k) = queue.pop() {
+            for e in self.triangles[k].edges().iter() {
+                if self.constraints.contains(e) {
+                    continue;
+                }
+                for &n in &self.sides[e] {
+                    let t = &self.triangles[n];
+                    if !in_cavity.contains(&n)
+                        && in_circumcircle(&p, &points[t.0], &points[t.1], &points[t.2], false)
+                    {
+                        in_cavity.insert(n);
+                        cavity.push(n);
+                        queue.push(n);
+                    }
+                }
+            }
+        }
+
+        // The new triangles take the places of the cavity, and the two more ones are appended.
+        let mut edges: Vec<Edge> = Vec::new();
+        for &k in &cavity {
+            edges.extend_from_slice(&self.triangles[k].edges());
+            unlink(&mut self.sides, k, &self.triangles[k]);
+        }
+        remove_shared_edges(&mut edges, &mut Vec::new());
+        debug_assert!(edges.len() >= cavity.len());
+        for (j, e) in edges.into_iter().enumerate() {
+            let t = Triangle(e.0, e.1, i);
+            let k = match cavity.get(j) {
+                Some(&k) => {
+                    self.triangles[k] = t;
+                    k
+                }
+                None => {
+                    self.triangles.push(t);
+                    self.triangles.len() - 1
+                }
+            };
+            link(&mut self.sides, k, &t);
+        }
+
+        i - SUPER_VERTICES
+    }
+
+    /// Inserts a constraint edge between two inserted points.
+    ///
+    /// The edges crossing the constraint are flipped until it appears, then the triangles around
+    /// it are made Delaunay again where the constraints allow it. When other points lie on the
+    /// segment between its two vertices, the constraint is split into the segments between them.
+    ///
+    /// This returns `TriangulateError::MissingVertex` when a vertex of the edge isn't an inserted
+    /// point, `TriangulateError::Degenerate` when both vertices are the same, and
+    /// `TriangulateError::IntersectingConstraints` when the edge crosses a previous constraint.
+    /// In the latter case, the parts of a split constraint before the crossing are kept.
+    pub fn insert_constraint(&mut self, edge: Edge) -> Result<()> {
+        let count = self.points.len() - SUPER_VERTICES;
+        if edge.0 >= count || edge.1 >= count {
+            return Err(TriangulateError::MissingVertex);
+        }
+        if edge.0 == edge.1 {
+            return Err(TriangulateError::Degenerate);
+        }
+
+        let (a, b) = (edge.0 + SUPER_VERTICES, edge.1 + SUPER_VERTICES);
+        let others = (SUPER_VERTICES..self.points.len()).filter(|&v| v != a && v != b);
+        if let Some(v) = vertex_on_segment(&self.points, others, a, b) {
+            let v = v - SUPER_VERTICES;
+            self.insert_constraint(Edge(edge.0, v))?;
+            return self.insert_constraint(Edge(v, edge.1));
+        }
+
+        let edge = Edge(a, b);
+        force_edge(
+            &self.points,
+            &mut self.triangles,
+            &mut self.sides,
+            &edge,
+            &self.constraints,
+        )?;
+        self.constraints.insert(edge);
+        Ok(())
+    }
+
+    /// Returns the index of a triangle containing the vertex `i`.
+    ///
+    /// This walks from the last triangle towards the vertex, across the edges it lies beyond,
+    /// starting from a different edge at every step. The walk can go around in circles in a
+    /// triangulation which isn't Delaunay, so after as many steps as there are triangles, this
+    /// falls back to checking every triangle.
+    fn locate(&self, i: usize) -> usize {
+        let contains = |t: &Triangle| {
+            t.edges()
+                .iter()
+                .all(|e| orientation(&self.points, e.0, e.1, i) <= 0)
+        };
+
+        let mut k = self.triangles.len() - 1;
+        for step in 0..self.triangles.len() {
+            let edges = self.triangles[k].edges();
+            let beyond = (0..3)
+                .map(|j| &edges[(j + step) % 3])
+                .find(|e| orientation(&self.points, e.0, e.1, i) > 0);
+            let e = match beyond {
+                Some(e) => e,
+                None => return k,
+            };
+            match self.sides[e].iter().find(|&&n| n != k) {
+                Some(&n) => k = n,
+                None => break,
+            }
+        }
+        (0..self.triangles.len())
+            .find(|&k| contains(&self.triangles[k]))
+            .unwrap_or(k)
+    }
+
+    /// The points inserted so far, in their insertion order.
+    pub fn points(&self) -> &[TriangulationPoint<T>] {
+        &self.points[SUPER_VERTICES..]
+    }
+
+    /// The constraint edges, sorted, between indices of the inserted points.
+    pub fn constraints(&self) -> Vec<Edge> {
+        let mut constraints: Vec<Edge> = self
+            .constraints
+            .iter()
+            .map(|e| Edge(e.0 - SUPER_VERTICES, e.1 - SUPER_VERTICES))
+            .collect();
+        constraints.sort();
+        constraints
+    }
+
+    /// The triangles between the points inserted so far.
+    pub fn triangles(&self) -> Vec<Triangle> {
+        self.triangles
+            .iter()
+            .filter(|t| t.0 >= SUPER_VERTICES && t.1 >= SUPER_VERTICES && t.2 >= SUPER_VERTICES)
+            .map(|t| {
+                Triangle(
+                    t.0 - SUPER_VERTICES,
+                    t.1 - SUPER_VERTICES,
+                    t.2 - SUPER_VERTICES,
+                )
+            })
+            .collect()
+    }
+}
+
+/// Returns the sign of the cross product of `b - a` and `c - a`.
+fn orientation<T, P>(points: &[P], a: usize, b: usize, c: usize) -> i32
+where
+    T: Coordinate,
+    P: Point<T>,
+{
+    let (a, b, c) = (&points[a], &points[b], &points[c]);
+    let cross = (b.x() - a.x()) * (c.y() - a.y()) - (b.y() - a.y()) * (c.x() - a.x());
+    if cross > T::zero() {
+        1
+    } else if cross < T::zero() {
+        -1
+    } else {
+        0
+    }
+}
+
+/// Returns the vertex closest to `a`, among `vertices`, lying strictly between `a` and `b`.
+fn vertex_on_segment<T, P, I>(points: &[P], vertices: I, a: usize, b: usize) -> Option<usize>
+where
+    T: Coordinate,
+    P: Point<T>,
+    I: Iterator<Item = usize>,
+{
+    let between = |p: T, a: T, b: T| (p >= a && p <= b) || (p <= a && p >= b);
+    let (pa, pb) = (&points[a], &points[b]);
+    let distance = |v: usize| {
+        let (dx, dy) = (points[v].x() - pa.x(), points[v].y() - pa.y());
+        dx * dx + dy * dy
+    };
+    vertices
+        .filter(|&v| {
+            let p = &points[v];
+            v != a
+                && v != b
+                && orientation(points, a, b, v) == 0
+                && between(p.x(), pa.x(), pb.x())
+                && between(p.y(), pa.y(), pb.y())
+                && (p.x() != pa.x() || p.y() != pa.y())
+                && (p.x() != pb.x() || p.y() != pb.y())
+        })
+        .fold(None, |closest: Option<usize>, v| match closest {
+            Some(c) if distance(c) <= distance(v) => Some(c),
+            _ => Some(v),
+        })
+}
+
+/// Flips the edges crossing the segment of `edge` until it is an edge of the triangulation, then
+/// restores the Delaunay property around it, without flipping `edge` or the `constraints`.
+///
+/// The `sides` of the edges, as made by `edge_sides`, are kept up to date with the flips. No vertex
+/// may lie on the segment. Returns `TriangulateError::IntersectingConstraints`, without changing
+/// anything, if the segment crosses one of the constraints, and `TriangulateError::Degenerate`
+/// if the crossing edges can't all be flipped, after putting back the triangles already flipped.
+fn force_edge<T, P>(
+    points: &[P],
+    triangles: &mut [Triangle],
+    sides: &mut HashMap<Edge, Vec<usize>>,
+    edge: &Edge,
+    constraints: &HashSet<Edge>,
+) -> Result<()>
+where
+    T: Coordinate,
+    P: Point<T>,
+{
+    let orientation = |a: usize, b: usize, c: usize| orientation(points, a, b, c);
+    let (a, b) = (edge.0, edge.1);
+    if sides.contains_key(edge) {
+        return Ok(());
+    }
+
+    let crosses = |e: &Edge| {
+        e.0 != a
+            && e.0 != b
+            && e.1 != a
+            && e.1 != b
+            && orientation(a, b, e.0) * orientation(a, b, e.1) < 0
+            && orientation(e.0, e.1, a) * orientation(e.0, e.1, b) < 0
+    };
+    let mut crossing: VecDeque<Edge> = sides.keys().filter(|e| crosses(e)).cloned().collect();
+    if crossing.iter().any(|e| constraints.contains(e)) {
+        return Err(TriangulateError::IntersectingConstraints);
+    }
+    let mut created = Vec::new();
+
+    // Flip the crossing edges whose two triangles make a convex quad, coming back later to the
+    // others, which become flippable as their neighbours get flipped. The triangles are saved
+    // before their first flip, to be put back if this never ends.
+    let mut original = HashMap::<usize, Triangle>::new();
+    let mut attempts = 0;
+    while let Some(e) = crossing.pop_front() {
+        attempts += 1;
+        if attempts > 8 * triangles.len() * triangles.len() + 8 {
+            for &k in original.keys() {
+                unlink(sides, k, &triangles[k]);
+            }
+            for (&k, t) in &original {
+                triangles[k] = *t;
+                link(sides, k, t);
+            }
+            return Err(TriangulateError::Degenerate);
+        }
+        if let Some(side) = sides.get(&e) {
+            for &k in side {
+                original.entry(k).or_insert(triangles[k]);
+            }
+        }
+        match flip(triangles, sides, &e, |c, d| {
+            orientation(c, d, e.0) * orientation(c, d, e.1) < 0
+        }) {
+            Some(flipped) => {
+                if crosses(&flipped) {
+                    crossing.push_back(flipped);
+                } else {
+                    created.push(flipped);
+                }
+            }
+            None => crossing.push_back(e),
+        }
+    }
+
+    // Restore the Delaunay property around the new edges, but for the constraints. Flipping an
+    // edge changes the triangles on either side of its neighbours, which are checked again.
+    let tolerance = circle_tolerance::<T>();
+    while let Some(e) = created.pop() {
+        if e == *edge || constraints.contains(&e) {
+            continue;
+        }
+        let flipped = flip(triangles, sides, &e, |c, d| {
+            let t = rotate_to(&Triangle(e.0, e.1, c), &e);
+            match circumcircle(&points[t.0], &points[t.1], &points[t.2]) {
+                Some((center_x, center_y, radius_sq)) => {
+                    let (dx, dy) = (points[d].x() - center_x, points[d].y() - center_y);
+                    radius_sq - (dx * dx + dy * dy) > radius_sq * tolerance
+                        && orientation(c, d, e.0) * orientation(c, d, e.1) < 0
+                }
+                None => false,
+            }
+        });
+        if let Some(flipped) = flipped {
+            for &t in &sides[&flipped] {
+                created.extend(
+                    triangles[t]
+                        .edges()
+                        .iter()
+                        .filter(|n| **n != flipped)
+                        .cloned(),
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns the triangles on both sides of every edge, as indices into `triangles`.
+fn edge_sides(triangles: &[Triangle]) -> HashMap<Edge, Vec<usize>> {
+    let mut sides = HashMap::with_capacity(triangles.len() * 2);
+    for (k, t) in triangles.iter().enumerate() {
+        link(&mut sides, k, t);
+    }
+    sides
+}
+
+/// Adds the triangle `t`, at index `k`, to the sides of its edges.
+fn link(sides: &mut HashMap<Edge, Vec<usize>>, k: usize, t: &Triangle) {
+    for e in t.edges().iter() {
+        sides.entry(e.clone()).or_default().push(k);
+    }
+}
+
+/// Removes the triangle `t`, at index `k`, from the sides of its edges.
+fn unlink(sides: &mut HashMap<Edge, Vec<usize>>, k: usize, t: &Triangle) {
+    for e in t.edges().iter() {
+        if let Some(side) = sides.get_mut(e) {
+            side.retain(|&n| n != k);
+            if side.is_empty() {
+                sides.remove(e);
+            }
+        }
+    }
+}
+
+/// Flips an edge shared by two triangles, replacing it by the edge between their third vertices,
+/// if `can_flip` accepts those two vertices. Returns the new edge.
+///
+/// The first triangle is `a, b, c` and the second one `b, a, d`, for the edge going from `a` to
+/// `b`: they are replaced by `a, d, c` and `d, b, c`, which keeps their winding.
+fn flip<F>(
+    triangles: &mut [Triangle],
+    sides: &mut HashMap<Edge, Vec<usize>>,
+    edge: &Edge,
+    can_flip: F,
+) -> Option<Edge>
+where
+    F: Fn(usize, usize) -> bool,
+{
+    let (t1, t2) = match sides.get(edge).map(|s| &s[..]) {
+        Some(&[t1, t2]) => (t1, t2),
+        _ => return None,
+    };
+    let (a, b, c) = rotate_to(&triangles[t1], edge);
+    let d = third_vertex(&triangles[t2], a, b);
+    if !can_flip(c, d) {
+        return None;
+    }
+
+    triangles[t1] = Triangle(a, d, c);
+    triangles[t2] = Triangle(d, b, c);
+    sides.remove(edge);
+    sides.insert(Edge(c, d), vec![t1, t2]);
+    for &(ref outer, from, to) in &[(Edge(a, d), t2, t1), (Edge(b, c), t1, t2)] {
+        if let Some(side) = sides.get_mut(outer) {
+            for s in side.iter_mut().filter(|s| **s == from) {
+                *s = to;
+            }
+        }
+    }
+    Some(Edge(c, d))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::xorshift::XorShift;
+    use super::super::{
+        is_delaunay, is_edge_manifold, sort_points, triangulate, Edge, Triangle, TriangulateError,
+        Triangulation, TriangulationPoint,
+    };
+    use super::{insert_constraint_edge, triangulate_constrained, ConstrainedTriangulation};
+
+    fn scattered(count: usize) -> Vec<TriangulationPoint<f64>> {
+        let mut rng = XorShift::new(0);
+        (0..count)
+            .map(|_| TriangulationPoint::new(rng.next_f64() * 100.0, rng.next_f64() * 100.0))
+            .collect()
+    }
+
+    #[test]
+    fn test_insert_constraint_edge() {
+        // A diagonal across a convex quad.
+        let quad = [
+            TriangulationPoint::new(-3.0, 0.0),
+            TriangulationPoint::new(0.0, -1.0),
+            TriangulationPoint::new(0.0, 1.0),
+            TriangulationPoint::new(3.0, 0.0),
+        ];
+        let mut triangles = triangulate(&quad).unwrap();
+        insert_constraint_edge(&quad, &mut triangles, Edge(3, 0)).unwrap();
+        assert_eq!(triangles.len(), 2);
+        assert!(triangles.iter().all(|t| t.edges().contains(&Edge(0, 3))));
+
+        // A long edge across many triangles.
+        let mut points = scattered(60);
+        points.sort_by(sort_points);
+        let before = triangulate(&points).unwrap();
+        let mut triangles = before.clone();
+        let edge = Edge(0, points.len() - 1);
+        insert_constraint_edge(&points, &mut triangles, edge.clone()).unwrap();
+        assert_eq!(triangles.len(), before.len());
+        assert!(is_edge_manifold(&triangles));
+        assert!(triangles.iter().any(|t| t.edges().contains(&edge)));
+        let cross = |t: &super::super::Triangle| {
+            let (a, b, c) = (&points[t.0], &points[t.1], &points[t.2]);
+            (b.x - a.x) * (c.y - a.y) - (b.y - a.y) * (c.x - a.x)
+        };
+        assert!(triangles.iter().all(|t| cross(t) < 0.0));
+
+        // Inserting it again changes nothing.
+        let inserted = triangles.clone();
+        insert_constraint_edge(&points, &mut triangles, edge).unwrap();
+        assert_eq!(triangles, inserted);
+
+        match insert_constraint_edge(&points, &mut triangles, Edge(0, 99)) {
+            Err(TriangulateError::MissingVertex) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        match insert_constraint_edge(&points, &mut triangles, Edge(3, 3)) {
+            Err(TriangulateError::Degenerate) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_insert_constraint_edge_restores() {
+        // The segment from 0 to 4 crosses the edge between 1 and 2, which can be flipped, then
+        // leaves the mesh through the edge between 1 and 3, which can't, as the region isn't
+        // convex. The flip is undone before giving up.
+        let points = [
+            TriangulationPoint::new(0.0, 0.0),
+            TriangulationPoint::new(1.0, 1.0),
+            TriangulationPoint::new(1.0, -1.0),
+            TriangulationPoint::new(2.0, 0.0),
+            TriangulationPoint::new(4.0, 0.5),
+            TriangulationPoint::new(5.0, 0.0),
+            TriangulationPoint::new(5.0, 1.0),
+        ];
+        let before = [Triangle(0, 1, 2), Triangle(2, 1, 3), Triangle(4, 6, 5)];
+        let mut triangles = before;
+        match insert_constraint_edge(&points, &mut triangles, Edge(0, 4)) {
+            Err(TriangulateError::Degenerate) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        assert_eq!(triangles, before);
+    }
+
+    #[test]
+    fn test_triangulate_constrained() {
+        // The constraints are in separate ranges of x, so that they don't cross, and one starts
+        // from a duplicated point.
+        let mut points = scattered(60);
+        let mut order: Vec<usize> = (0..points.len()).collect();
+        order.sort_by(|&a, &b| sort_points(&points[a], &points[b]));
+        points.push(points[order[0]]);
+        let constraints = [
+            Edge(60, order[8]),
+            Edge(order[20], order[30]),
+            Edge(order[55], order[40]),
+        ];
+        let triangles = triangulate_constrained(&points, &constraints).unwrap();
+        assert!(is_edge_manifold(&triangles));
+        assert!(triangles
+            .iter()
+            .all(|t| t.0 != 60 && t.1 != 60 && t.2 != 60));
+        for e in &[
+            Edge(order[0], order[8]),
+            constraints[1].clone(),
+            constraints[2].clone(),
+        ] {
+            assert!(triangles.iter().any(|t| t.edges().contains(e)));
+        }
+
+        // Without constraints, this is a Delaunay triangulation.
+        let unconstrained = triangulate_constrained(&points, &[]).unwrap();
+        assert_eq!(unconstrained.len(), triangles.len());
+        assert!(is_delaunay(&points, &unconstrained));
+
+        for edge in &[Edge(61, 0), Edge(3, 100)] {
+            match triangulate_constrained(&points, &[Edge(0, 1), edge.clone()]) {
+                Err(TriangulateError::ConstraintOutOfRange { edge: e }) => assert_eq!(e, *edge),
+                other => panic!("unexpected result: {:?}", other),
+            }
+        }
+    }
+
+    #[test]
+    fn test_constrained_triangulation() {
+        let points = scattered(60);
+        let mut triangulation = ConstrainedTriangulation::new(
+            TriangulationPoint::new(0.0, 0.0),
+            TriangulationPoint::new(100.0, 100.0),
+        )
+        .unwrap();
+        let mut unconstrained = Triangulation::new(
+            TriangulationPoint::new(0.0, 0.0),
+            TriangulationPoint::new(100.0, 100.0),
+        )
+        .unwrap();
+        for (i, p) in points.iter().enumerate() {
+            assert_eq!(triangulation.insert_point(p), i);
+            unconstrained.insert(p);
+        }
+        assert_eq!(triangulation.points(), &points[..]);
+        assert_eq!(triangulation.insert_point(&points[7]), 7);
+
+        // Without constraints, this is the same as the unconstrained triangulation.
+        let sorted = |mut triangles: Vec<Triangle>| {
+            let mut triangles: Vec<_> = triangles.drain(..).map(|t| t.canonical()).collect();
+            triangles.sort();
+            triangles
+        };
+        let triangles = triangulation.triangles();
+        assert_eq!(sorted(triangles.clone()), sorted(unconstrained.triangles()));
+        assert!(is_delaunay(&points, &triangles));
+
+        // The constraints stay in the mesh as points get inserted around them.
+        triangulation.insert_constraint(Edge(0, 1)).unwrap();
+        triangulation.insert_constraint(Edge(2, 5)).unwrap();
+        let more = scattered(90);
+        for p in &more[60..] {
+            triangulation.insert_point(p);
+        }
+        let triangles = triangulation.triangles();
+        assert!(is_edge_manifold(&triangles));
+        let has_edge = |e: &Edge| triangles.iter().any(|t| t.edges().contains(e));
+        for e in triangulation.constraints() {
+            assert!(has_edge(&e));
+        }
+        let all = triangulation.points();
+        let cross = |t: &Triangle| {
+            let (a, b, c) = (&all[t.0], &all[t.1], &all[t.2]);
+            (b.x - a.x) * (c.y - a.y) - (b.y - a.y) * (c.x - a.x)
+        };
+        assert!(triangles.iter().all(|t| cross(t) < 0.0));
+
+        // Every other edge is locally Delaunay.
+        let constraints = triangulation.constraints();
+        for (k, t) in triangles.iter().enumerate() {
+            for u in &triangles[k + 1..] {
+                let shared: Vec<_> = t
+                    .edges()
+                    .iter()
+                    .filter(|e| u.edges().contains(e))
+                    .cloned()
+                    .collect();
+                if shared.is_empty() || constraints.contains(&shared[0]) {
+                    continue;
+                }
+                let pair: Vec<_> = [t.0, t.1, t.2, u.0, u.1, u.2]
+                    .iter()
+                    .map(|&v| all[v])
+                    .collect();
+                assert!(is_delaunay(&pair, &[Triangle(0, 1, 2), Triangle(3, 4, 5)]));
+            }
+        }
+
+        // A crossing constraint is refused, and invalid ones too.
+        let (p0, p1) = (all[0], all[1]);
+        let left = triangulation.insert_point(&TriangulationPoint::new(
+            (p0.x + p1.x) / 2.0 - (p1.y - p0.y) * 0.01,
+            (p0.y + p1.y) / 2.0 + (p1.x - p0.x) * 0.01,
+        ));
+        let right = triangulation.insert_point(&TriangulationPoint::new(
+            (p0.x + p1.x) / 2.0 + (p1.y - p0.y) * 0.01,
+            (p0.y + p1.y) / 2.0 - (p1.x - p0.x) * 0.01,
+        ));
+        match triangulation.insert_constraint(Edge(left, right)) {
+            Err(TriangulateError::IntersectingConstraints) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        match triangulation.insert_constraint(Edge(0, 1000)) {
+            Err(TriangulateError::MissingVertex) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_constrained_triangulation_many_points() {
+        // Enough points for the walk to the inserted ones to go a long way, with duplicates.
+        let mut points = scattered(500);
+        points.extend(scattered(20));
+        let mut triangulation = ConstrainedTriangulation::new(
+            TriangulationPoint::new(0.0, 0.0),
+            TriangulationPoint::new(100.0, 100.0),
+        )
+        .unwrap();
+        let mut unconstrained = Triangulation::new(
+            TriangulationPoint::new(0.0, 0.0),
+            TriangulationPoint::new(100.0, 100.0),
+        )
+        .unwrap();
+        for (i, p) in points.iter().enumerate() {
+            assert_eq!(triangulation.insert_point(p), i % 500);
+            if i < 500 {
+                unconstrained.insert(p);
+            }
+        }
+        let sorted = |triangles: Vec<Triangle>| {
+            let mut triangles: Vec<_> = triangles.iter().map(|t| t.canonical()).collect();
+            triangles.sort();
+            triangles
+        };
+        assert_eq!(
+            sorted(triangulation.triangles()),
+            sorted(unconstrained.triangles())
+        );
+    }
+
+    #[test]
+    fn test_constrained_triangulation_splits() {
+        let mut triangulation = ConstrainedTriangulation::new(
+            TriangulationPoint::new(0.0, 0.0),
+            TriangulationPoint::new(8.0, 8.0),
+        )
+        .unwrap();
+        for &(x, y) in &[(0.0, 1.0), (8.0, 5.0), (3.0, 0.0), (5.0, 8.0), (4.0, 3.0)] {
+            triangulation.insert_point(&TriangulationPoint::new(x, y));
+        }
+
+        // A constraint through a point is made of two edges.
+        triangulation.insert_constraint(Edge(1, 0)).unwrap();
+        assert_eq!(triangulation.constraints(), [Edge(4, 0), Edge(1, 4)]);
+
+        // A point on a constraint splits it.
+        let middle = triangulation.insert_point(&TriangulationPoint::new(6.0, 4.0));
+        assert_eq!(
+            triangulation.constraints(),
+            [Edge(4, 0), Edge(1, middle), Edge(middle, 4)]
+        );
+        let triangles = triangulation.triangles();
+        for e in triangulation.constraints() {
+            assert!(triangles.iter().any(|t| t.edges().contains(&e)));
+        }
+        assert!(triangles
+            .iter()
+            .all(|t| !t.edges().contains(&Edge(1, 4)) && !t.edges().contains(&Edge(1, 0))));
+    }
+}
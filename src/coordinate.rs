@@ -0,0 +1,470 @@
+// Copyright 2017-2018 Samuel Loretan <tynril@gmail.com> -- See LICENSE file
+
+//! The abstraction over the numeric type of the point coordinates.
+
+use std::ops::{Add, Div, Mul, Sub};
+
+use num_traits::float::FloatCore;
+use num_traits::{NumCast, One, ToPrimitive, Zero};
+
+/// A numeric type which can be used for the coordinates of the points to triangulate.
+///
+/// This gathers the operations the triangulation algorithm actually needs, so that it can work
+/// with other types than the primitive floats, such as fixed-point or rational numbers. It is
+/// implemented for every `FloatCore` type, which includes `f32` and `f64`.
+///
+/// The `infinity` and `neg_infinity` sentinels only need to compare above (resp. below) any
+/// coordinate of the points, so types without infinities can return their largest (resp. smallest)
+/// values. The `epsilon` is the tolerance under which two coordinates are considered equal by the
+/// circumcircle computations, and can be zero for exact types. As with floats, a division by zero
+/// must not panic: the circumcircle of collinear points is computed, and then discarded.
+///
+/// The triangulation functions, along with the filters and the verification helpers, accept any
+/// `Coordinate`. The other helpers still require `FloatCore` or `Float`. Nothing assumes the
+/// precision of `f64`, so wider types work as well, and make for a more accurate triangulation.
+/// The few constants the triangulation needs are converted with `from_f64`: a type which can't
+/// represent them makes it fail with `TriangulateError::UnsupportedCoordinate`, without panicking.
+///
+/// Example:
+///
+/// ```rust
+/// use rtriangulate::Coordinate;
+///
+/// fn midpoint<T: Coordinate>(a: T, b: T) -> T {
+///     (a + b) * T::from_f64(0.5).unwrap()
+/// }
+/// assert_eq!(midpoint(1.0f32, 2.0), 1.5);
+/// ```
+pub trait Coordinate:
+    Copy
+    + PartialOrd
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Div<Output = Self>
+{
+    /// The additive identity, `0`.
+    fn zero() -> Self;
+
+    /// The multiplicative identity, `1`.
+    fn one() -> Self;
+
+    /// A value above any coordinate.
+    fn infinity() -> Self;
+
+    /// A value below any coordinate.
+    fn neg_infinity() -> Self;
+
+    /// The tolerance under which two coordinates are considered equal.
+    fn epsilon() -> Self;
+
+    /// Converts a `f64` constant to this type, or returns `None` if it can't be represented.
+    fn from_f64(value: f64) -> Option<Self>;
+
+    /// Converts this value to a `f64`, or returns `None` if it can't be represented.
+    fn to_f64(self) -> Option<f64>;
+
+    /// Returns true if this value is neither infinite nor NaN.
+    #[inline(always)]
+    fn is_finite(self) -> bool {
+        true
+    }
+
+    /// Returns the absolute value.
+    #[inline(always)]
+    fn abs(self) -> Self {
+        if self < Self::zero() {
+            Self::zero() - self
+        } else {
+            self
+        }
+    }
+
+    /// Returns the smallest of two values.
+    #[inline(always)]
+    fn min(self, other: Self) -> Self {
+        if other < self {
+            other
+        } else {
+            self
+        }
+    }
+
+    /// Returns the largest of two values.
+    #[inline(always)]
+    fn max(self, other: Self) -> Self {
+        if other > self {
+            other
+        } else {
+            self
+        }
+    }
+}
+
+impl<T> Coordinate for T
+where
+    T: FloatCore,
+{
+    #[inline(always)]
+    fn zero() -> Self {
+        <T as Zero>::zero()
+    }
+
+    #[inline(always)]
+    fn one() -> Self {
+        <T as One>::one()
+    }
+
+    #[inline(always)]
+    fn infinity() -> Self {
+        <T as FloatCore>::infinity()
+    }
+
+    #[inline(always)]
+    fn neg_infinity() -> Self {
+        <T as FloatCore>::neg_infinity()
+    }
+
+    #[inline(always)]
+    fn epsilon() -> Self {
+        <T as FloatCore>::epsilon()
+    }
+
+    #[inline(always)]
+    fn from_f64(value: f64) -> Option<Self> {
+        <T as NumCast>::from(value)
+    }
+
+    #[inline(always)]
+    fn to_f64(self) -> Option<f64> {
+        <T as ToPrimitive>::to_f64(&self)
+    }
+
+    #[inline(always)]
+    fn is_finite(self) -> bool {
+        <T as FloatCore>::is_finite(self)
+    }
+
+    #[inline(always)]
+    fn abs(self) -> Self {
+        <T as FloatCore>::abs(self)
+    }
+
+    #[inline(always)]
+    fn min(self, other: Self) -> Self {
+        <T as FloatCore>::min(self, other)
+    }
+
+    #[inline(always)]
+    fn max(self, other: Self) -> Self {
+        <T as FloatCore>::max(self, other)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ops::{Add, Div, Mul, Sub};
+
+    use super::super::{
+        sort_triangles_morton, triangulate, triangulate_with, Point, TriangulateError,
+        TriangulateOptions, TriangulationPoint,
+    };
+    use super::Coordinate;
+
+    /// Implements the arithmetic operators of a wrapper type, by forwarding them to the wrapped
+    /// value.
+    macro_rules! forward_ops {
+        ($t:ident) => {
+            impl Add for $t {
+                type Output = $t;
+                fn add(self, other: $t) -> $t {
+                    $t(self.0 + other.0)
+                }
+            }
+
+            impl Sub for $t {
+                type Output = $t;
+                fn sub(self, other: $t) -> $t {
+                    $t(self.0 - other.0)
+                }
+            }
+
+            impl Mul for $t {
+                type Output = $t;
+                fn mul(self, other: $t) -> $t {
+                    $t(self.0 * other.0)
+                }
+            }
+
+            impl Div for $t {
+                type Output = $t;
+                fn div(self, other: $t) -> $t {
+                    $t(self.0 / other.0)
+                }
+            }
+        };
+    }
+
+    /// A fixed-point number, with 16 fractional bits.
+    #[derive(Clone, Copy, PartialEq, PartialOrd, Debug)]
+    struct Fixed(i64);
+
+    impl Add for Fixed {
+        type Output = Fixed;
+        fn add(self, other: Fixed) -> Fixed {
+            Fixed(self.0 + other.0)
+        }
+    }
+
+    impl Sub for Fixed {
+        type Output = Fixed;
+        fn sub(self, other: Fixed) -> Fixed {
+            Fixed(self.0 - other.0)
+        }
+    }
+
+    impl Mul for Fixed {
+        type Output = Fixed;
+        fn mul(self, other: Fixed) -> Fixed {
+            Fixed(((i128::from(self.0) * i128::from(other.0)) >> 16) as i64)
+        }
+    }
+
+    impl Div for Fixed {
+        type Output = Fixed;
+        fn div(self, other: Fixed) -> Fixed {
+            if other.0 == 0 {
+                return if self.0 < 0 {
+                    Fixed(i64::MIN)
+                } else {
+                    Fixed(i64::MAX)
+                };
+            }
+            Fixed(((i128::from(self.0) << 16) / i128::from(other.0)) as i64)
+        }
+    }
+
+    impl Coordinate for Fixed {
+        fn zero() -> Self {
+            Fixed(0)
+        }
+        fn one() -> Self {
+            Fixed(1 << 16)
+        }
+        fn infinity() -> Self {
+            Fixed(i64::MAX)
+        }
+        fn neg_infinity() -> Self {
+            Fixed(i64::MIN)
+        }
+        fn epsilon() -> Self {
+            Fixed(1)
+        }
+        fn from_f64(value: f64) -> Option<Self> {
+            Some(Fixed((value * 65536.0) as i64))
+        }
+        fn to_f64(self) -> Option<f64> {
+            Some(self.0 as f64 / 65536.0)
+        }
+    }
+
+    #[test]
+    fn test_fixed_point() {
+        let coords = [(10, 10), (15, 25), (25, 15), (30, 25), (40, 15)];
+        let floats: Vec<TriangulationPoint<f64>> = coords
+            .iter()
+            .map(|&(x, y)| TriangulationPoint::new(x as f64, y as f64))
+            .collect();
+        let fixed: Vec<TriangulationPoint<Fixed>> = floats
+            .iter()
+            .map(|p| {
+                TriangulationPoint::new(
+                    Fixed::from_f64(p.x).unwrap(),
+                    Fixed::from_f64(p.y).unwrap(),
+                )
+            })
+            .collect();
+
+        assert_eq!(fixed[1].x().to_f64(), Some(15.0));
+        assert_eq!(triangulate(&fixed).unwrap(), triangulate(&floats).unwrap());
+    }
+
+    /// An integer, which can't represent the constants the triangulation needs.
+    #[derive(Clone, Copy, PartialEq, PartialOrd, Debug)]
+    struct Integer(i64);
+
+    forward_ops!(Integer);
+
+    impl Coordinate for Integer {
+        fn zero() -> Self {
+            Integer(0)
+        }
+        fn one() -> Self {
+            Integer(1)
+        }
+        fn infinity() -> Self {
+            Integer(i64::MAX)
+        }
+        fn neg_infinity() -> Self {
+            Integer(i64::MIN)
+        }
+        fn epsilon() -> Self {
+            Integer(0)
+        }
+        fn from_f64(value: f64) -> Option<Self> {
+            if value.fract() == 0.0 {
+                Some(Integer(value as i64))
+            } else {
+                None
+            }
+        }
+        fn to_f64(self) -> Option<f64> {
+            Some(self.0 as f64)
+        }
+    }
+
+    #[test]
+    fn test_unsupported_coordinate() {
+        let points = [
+            TriangulationPoint::new(Integer(10), Integer(10)),
+            TriangulationPoint::new(Integer(15), Integer(25)),
+            TriangulationPoint::new(Integer(25), Integer(15)),
+        ];
+        match triangulate(&points) {
+            Err(TriangulateError::UnsupportedCoordinate) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    /// A float which can't be converted back to `f64`.
+    #[derive(Clone, Copy, PartialEq, PartialOrd, Debug)]
+    struct Opaque(f64);
+
+    forward_ops!(Opaque);
+
+    impl Coordinate for Opaque {
+        fn zero() -> Self {
+            Opaque(0.0)
+        }
+        fn one() -> Self {
+            Opaque(1.0)
+        }
+        fn infinity() -> Self {
+            Opaque(f64::INFINITY)
+        }
+        fn neg_infinity() -> Self {
+            Opaque(f64::NEG_INFINITY)
+        }
+        fn epsilon() -> Self {
+            Opaque(f64::EPSILON)
+        }
+        fn from_f64(value: f64) -> Option<Self> {
+            Some(Opaque(value))
+        }
+        fn to_f64(self) -> Option<f64> {
+            None
+        }
+        fn is_finite(self) -> bool {
+            self.0.is_finite()
+        }
+    }
+
+    #[test]
+    fn test_unconvertible_coordinate() {
+        let points: Vec<TriangulationPoint<Opaque>> = [(10.0, 10.0), (15.0, 25.0), (25.0, 15.0)]
+            .iter()
+            .map(|&(x, y)| TriangulationPoint::new(Opaque(x), Opaque(y)))
+            .collect();
+        assert_eq!(triangulate(&points).unwrap().len(), 1);
+
+        let widened = TriangulateOptions {
+            widen_accumulate: true,
+            ..Default::default()
+        };
+        match triangulate_with(&points, &widened) {
+            Err(TriangulateError::UnsupportedCoordinate) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        let slivers = TriangulateOptions {
+            drop_slivers_below_angle: Some(Opaque(0.1)),
+            ..Default::default()
+        };
+        match triangulate_with(&points, &slivers) {
+            Err(TriangulateError::UnsupportedCoordinate) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        let mut triangles = triangulate(&points).unwrap();
+        match sort_triangles_morton(&points, &mut triangles) {
+            Err(TriangulateError::UnsupportedCoordinate) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    /// A quadruple-precision float.
+    #[cfg(feature = "f128")]
+    #[derive(Clone, Copy, PartialEq, PartialOrd, Debug)]
+    struct Quad(f128);
+
+    #[cfg(feature = "f128")]
+    forward_ops!(Quad);
+
+    #[cfg(feature = "f128")]
+    impl Coordinate for Quad {
+        fn zero() -> Self {
+            Quad(0.0)
+        }
+        fn one() -> Self {
+            Quad(1.0)
+        }
+        fn infinity() -> Self {
+            Quad(f128::INFINITY)
+        }
+        fn neg_infinity() -> Self {
+            Quad(f128::NEG_INFINITY)
+        }
+        fn epsilon() -> Self {
+            Quad(f128::EPSILON)
+        }
+        fn from_f64(value: f64) -> Option<Self> {
+            Some(Quad(value as f128))
+        }
+        fn to_f64(self) -> Option<f64> {
+            Some(self.0 as f64)
+        }
+        fn is_finite(self) -> bool {
+            self.0.is_finite()
+        }
+    }
+
+    #[cfg(feature = "f128")]
+    #[test]
+    fn test_quad_precision() {
+        let coords = [
+            (10, 10),
+            (15, 25),
+            (25, 15),
+            (30, 25),
+            (40, 15),
+            (45, 40),
+            (55, 5),
+            (60, 30),
+        ];
+        let floats: Vec<TriangulationPoint<f64>> = coords
+            .iter()
+            .map(|&(x, y)| TriangulationPoint::new(x as f64, y as f64))
+            .collect();
+
+        // Far from the origin, the points are too close together for a `f64` to tell them apart.
+        let offset = 1e17f64 as f128;
+        let quads: Vec<TriangulationPoint<Quad>> = coords
+            .iter()
+            .map(|&(x, y)| {
+                TriangulationPoint::new(Quad(offset + x as f128), Quad(offset + y as f128))
+            })
+            .collect();
+        assert_eq!(1e17 + 10.0, 1e17 + 15.0);
+        assert_eq!(triangulate(&quads).unwrap(), triangulate(&floats).unwrap());
+    }
+}
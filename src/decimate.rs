@@ -0,0 +1,317 @@
+// Copyright 2017-2018 Samuel Loretan <tynril@gmail.com> -- See LICENSE file
+
+//! Simplification of a triangulation by collapsing its edges.
+
+use std::collections::HashSet;
+
+use num_traits::Float;
+
+use super::topology::{boundary_edges, non_manifold_edges, vertex_triangles};
+use super::{Edge, Point, Triangle, TriangulationPoint};
+
+/// Simplifies a triangulation down to about `target_count` triangles, by collapsing its edges.
+///
+/// The edges are collapsed greedily, the shortest ones first: collapsing an edge merges one of its
+/// points into the other, which removes the two triangles sharing it. A collapse is skipped when it
+/// would flip a triangle over or make it degenerate, change the connectivity of the mesh beyond
+/// removing those two triangles, or move the boundary: the points of the boundary, and of the
+/// edges shared by more than two triangles, are never merged into others.
+///
+/// As every collapse removes two triangles, the output can have one triangle less than the
+/// target. It can also have more of them, when no collapse is possible anymore, such as when only
+/// the points of the boundary are left. The output is made of the remaining points, in their
+/// original order, and of the remaining triangles, re-indexed into them. The remaining points
+/// don't move, so that the mesh keeps its winding and its boundary, but the triangles are
+/// **not** guaranteed to be Delaunay anymore.
+///
+/// This is meant to get a low-poly version of a dense triangulation, such as for display.
+///
+/// Example:
+///
+/// ```rust
+/// use rtriangulate::{decimate, triangulate, TriangulationPoint};
+///
+/// let points = [
+///     TriangulationPoint::new(0.0, 0.0),
+///     TriangulationPoint::new(0.0, 10.0),
+///     TriangulationPoint::new(4.0, 5.0),
+///     TriangulationPoint::new(5.0, 5.0),
+///     TriangulationPoint::new(10.0, 0.0),
+///     TriangulationPoint::new(10.0, 10.0)
+/// ];
+/// let triangles = triangulate(&points).unwrap();
+/// assert_eq!(triangles.len(), 6);
+///
+/// // The shortest edge, between the two inner points, gets collapsed.
+/// let (points, triangles) = decimate(&points, &triangles, 4);
+/// assert_eq!(points.len(), 5);
+/// assert_eq!(triangles.len(), 4);
+/// ```
+pub fn decimate<T, P>(
+    points: &[P],
+    triangles: &[Triangle],
+    target_count: usize,
+) -> (Vec<TriangulationPoint<T>>, Vec<Triangle>)
+where
+    T: Float,
+    P: Point<T>,
+{
+    let mut fixed = vec![false; points.len()];
+    let mut fixed_edges: Vec<Edge> = boundary_edges(triangles).into_iter().collect();
+    fixed_edges.extend(non_manifold_edges(triangles).into_iter().map(|(e, _)| e));
+    for e in fixed_edges {
+        fixed[e.0] = true;
+        fixed[e.1] = true;
+    }
+
+    let mut mesh = Collapser {
+        points,
+        incident: vertex_triangles(triangles, points.len()),
+        triangles: triangles.iter().cloned().map(Some).collect(),
+    };
+    let mut count = triangles.len();
+
+    // The positions of the points don't change, and neither do the lengths of the edges: the edges
+    // are sorted once per pass, and those created by the collapses are only tried in the next one.
+    while count > target_count {
+        let mut edges: Vec<(T, Edge)> = mesh
+            .edges()
+            .into_iter()
+            .filter(|e| !fixed[e.0] || !fixed[e.1])
+            .map(|e| {
+                let (a, b) = (&points[e.0], &points[e.1]);
+                let (dx, dy) = (a.x() - b.x(), a.y() - b.y());
+                (dx * dx + dy * dy, e)
+            })
+            .collect();
+        // The lengths are NaN for points with NaN or infinite coordinates: those edges go last.
+        edges.sort_by(|a, b| {
+            a.0.partial_cmp(&b.0)
+                .unwrap_or_else(|| a.0.is_nan().cmp(&b.0.is_nan()))
+                .then_with(|| a.1.cmp(&b.1))
+        });
+
+        let mut collapsed = false;
+        for (_, e) in edges {
+            if count <= target_count {
+                break;
+            }
+            if (!fixed[e.1] && mesh.collapse(e.1, e.0)) || (!fixed[e.0] && mesh.collapse(e.0, e.1))
+            {
+                count -= 2;
+                collapsed = true;
+            }
+        }
+        if !collapsed {
+            break;
+        }
+    }
+
+    // Keep the points still in use, in their original order.
+    let mut remap = vec![None; points.len()];
+    let mut decimated_points = Vec::new();
+    for (i, p) in points.iter().enumerate() {
+        if !mesh.incident[i].is_empty() {
+            remap[i] = Some(decimated_points.len());
+            decimated_points.push(TriangulationPoint::new(p.x(), p.y()));
+        }
+    }
+    let index = |i: usize| remap[i].unwrap();
+    let decimated_triangles = mesh
+        .triangles
+        .iter()
+        .filter_map(|t| t.as_ref())
+        .map(|t| Triangle(index(t.0), index(t.1), index(t.2)))
+        .collect();
+
+    (decimated_points, decimated_triangles)
+}
+
+/// A triangulation whose edges get collapsed, with the triangles incident to every point.
+struct Collapser<'a, P: 'a> {
+    points: &'a [P],
+    /// The triangles, `None` once removed.
+    triangles: Vec<Option<Triangle>>,
+    /// The remaining triangles around every point.
+    incident: Vec<Vec<usize>>,
+}
+
+impl<'a, P> Collapser<'a, P> {
+    /// Returns the edges of the remaining triangles, once each.
+    fn edges(&self) -> Vec<Edge> {
+        let mut edges = HashSet::new();
+        for t in self.triangles.iter().filter_map(|t| t.as_ref()) {
+            edges.extend(t.edges().iter().cloned());
+        }
+        edges.into_iter().collect()
+    }
+
+    /// Returns the points sharing a triangle with a point.
+    fn neighbours(&self, vertex: usize) -> HashSet<usize> {
+        self.incident[vertex]
+            .iter()
+            .filter_map(|&t| self.triangles[t].as_ref())
+            .flat_map(|t| vec![t.0, t.1, t.2])
+            .filter(|&v| v != vertex)
+            .collect()
+    }
+
+    /// Merges the point `from` into the point `into`, if they share an interior edge and that
+    /// neither flips a triangle over nor changes the connectivity of the others.
+    fn collapse<T>(&mut self, from: usize, into: usize) -> bool
+    where
+        T: Float,
+        P: Point<T>,
+    {
+        let (shared, others): (Vec<usize>, Vec<usize>) =
+            self.incident[from].iter().cloned().partition(|&t| {
+                let t = self.triangles[t].as_ref().unwrap();
+                t.0 == into || t.1 == into || t.2 == into
+            });
+        if shared.len() != 2 {
+            return false;
+        }
+
+        // The only points next to both of them must be those across the edge, or the collapse
+        // would fold triangles onto each other.
+        let across: HashSet<usize> = shared
+            .iter()
+            .flat_map(|&t| {
+                let t = self.triangles[t].as_ref().unwrap();
+                vec![t.0, t.1, t.2]
+            })
+            .filter(|&v| v != from && v != into)
+            .collect();
+        let into_neighbours = self.neighbours(into);
+        let common: HashSet<usize> = self
+            .neighbours(from)
+            .into_iter()
+            .filter(|v| into_neighbours.contains(v))
+            .collect();
+        if across.len() != 2 || common != across {
+            return false;
+        }
+
+        let points = self.points;
+        let cross = |t: &Triangle| {
+            let (a, b, c) = (&points[t.0], &points[t.1], &points[t.2]);
+            (b.x() - a.x()) * (c.y() - a.y()) - (b.y() - a.y()) * (c.x() - a.x())
+        };
+        let moved = |t: &Triangle| {
+            let swap = |v: usize| if v == from { into } else { v };
+            Triangle(swap(t.0), swap(t.1), swap(t.2))
+        };
+        let keeps_orientation = others.iter().all(|&t| {
+            let t = self.triangles[t].as_ref().unwrap();
+            cross(t) * cross(&moved(t)) > T::zero()
+        });
+        if !keeps_orientation {
+            return false;
+        }
+
+        for t in shared {
+            let triangle = self.triangles[t].take().unwrap();
+            for &v in &[triangle.0, triangle.1, triangle.2] {
+                self.incident[v].retain(|&i| i != t);
+            }
+        }
+        for t in others {
+            let triangle = moved(self.triangles[t].as_ref().unwrap());
+            self.triangles[t] = Some(triangle);
+            self.incident[into].push(t);
+        }
+        self.incident[from].clear();
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{
+        boundary_edges, is_edge_manifold, triangulate, Edge, TriangulateExt, TriangulationPoint,
+    };
+    use super::decimate;
+
+    #[test]
+    fn test_decimate() {
+        let points: Vec<_> = (0..100)
+            .map(|i| {
+                let (x, y) = ((i / 10) as f64, (i % 10) as f64);
+                let jitter = ((i * 37) % 11) as f64 * 0.02;
+                if x == 0.0 || x == 9.0 || y == 0.0 || y == 9.0 {
+                    TriangulationPoint::new(x * 10.0, y * 10.0)
+                } else {
+                    TriangulationPoint::new(x * 10.0 + jitter, y * 10.0 - jitter)
+                }
+            })
+            .collect();
+        let triangles = points.triangulate_unsorted().unwrap();
+
+        let (decimated_points, decimated) = decimate(&points, &triangles, 60);
+        assert!(decimated.len() <= 60 && decimated.len() >= 59);
+        assert!(decimated_points.len() < points.len());
+        assert!(is_edge_manifold(&decimated));
+
+        // Every triangle keeps the clockwise winding of the input, without degenerating.
+        for t in &decimated {
+            let (a, b, c) = (
+                &decimated_points[t.0],
+                &decimated_points[t.1],
+                &decimated_points[t.2],
+            );
+            assert!((b.x - a.x) * (c.y - a.y) - (b.y - a.y) * (c.x - a.x) < 0.0);
+        }
+
+        // The boundary is made of the same segments.
+        let segments = |points: &[TriangulationPoint<f64>], edges: Vec<_>| {
+            let mut segments: Vec<_> = edges
+                .into_iter()
+                .map(|e: Edge| {
+                    let (a, b) = (&points[e.0], &points[e.1]);
+                    format!("{:?} {:?} {:?} {:?}", a.x, a.y, b.x, b.y)
+                })
+                .collect();
+            segments.sort();
+            segments
+        };
+        assert_eq!(
+            segments(&points, boundary_edges(&triangles).into_iter().collect()),
+            segments(
+                &decimated_points,
+                boundary_edges(&decimated).into_iter().collect()
+            )
+        );
+
+        // Only the points of the boundary are left when decimating as much as possible.
+        let (decimated_points, decimated) = decimate(&points, &triangles, 0);
+        assert_eq!(decimated_points.len(), 36);
+        assert_eq!(decimated.len(), 34);
+
+        // Nothing is collapsed when there are few enough triangles already.
+        let sorted = [
+            TriangulationPoint::new(0.0, 0.0),
+            TriangulationPoint::new(1.0, 2.0),
+            TriangulationPoint::new(2.0, 0.0),
+        ];
+        let triangles = triangulate(&sorted).unwrap();
+        assert_eq!(
+            decimate(&sorted, &triangles, 0),
+            (sorted.to_vec(), triangles)
+        );
+    }
+
+    #[test]
+    fn test_decimate_non_finite() {
+        let mut points: Vec<_> = (0..25)
+            .map(|i| TriangulationPoint::new((i / 5) as f64, (i % 5) as f64 * 1.1))
+            .collect();
+        let triangles = triangulate(&points).unwrap();
+
+        // The edges of the non-finite points have a NaN length, which doesn't panic.
+        points[6] = TriangulationPoint::new(f64::NAN, 1.1);
+        points[18] = TriangulationPoint::new(3.0, f64::INFINITY);
+        let (_, decimated) = decimate(&points, &triangles, 10);
+        assert!(decimated.len() < triangles.len());
+        assert!(is_edge_manifold(&decimated));
+    }
+}
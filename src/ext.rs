@@ -0,0 +1,90 @@
+// Copyright 2017-2018 Samuel Loretan <tynril@gmail.com> -- See LICENSE file
+
+//! Methods to triangulate slices of points directly.
+
+use super::{
+    convex_hull_only, triangulate, triangulate_subset, Coordinate, Point, Result, Triangle,
+};
+
+/// Triangulation methods on slices of points.
+///
+/// This makes it possible to call `points.triangulate()` instead of `triangulate(&points)`, which
+/// reads better in method chains. Every method is the same as the function it's named after.
+///
+/// Example:
+///
+/// ```rust
+/// use rtriangulate::{Triangle, TriangulateExt, TriangulationPoint};
+///
+/// let points = vec![
+///     TriangulationPoint::new(10.0, 50.0),
+///     TriangulationPoint::new(25.0, 40.0),
+///     TriangulationPoint::new(30.0, 40.0)
+/// ];
+/// assert_eq!(points.triangulate().unwrap(), [Triangle(1, 0, 2)]);
+/// assert_eq!(points.convex_hull().unwrap(), [0, 2, 1]);
+/// ```
+pub trait TriangulateExt<T> {
+    /// Triangulates the points, which must be sorted, as `triangulate` does.
+    fn triangulate(&self) -> Result<Vec<Triangle>>;
+
+    /// Triangulates the points, which don't need to be sorted.
+    ///
+    /// The points are sorted into a copy before being triangulated, and the triangles index into
+    /// the slice itself, as with `triangulate_subset` selecting all the points.
+    fn triangulate_unsorted(&self) -> Result<Vec<Triangle>>;
+
+    /// Computes the convex hull of the points, which must be sorted, as `convex_hull_only` does.
+    fn convex_hull(&self) -> Result<Vec<usize>>;
+}
+
+impl<T, P> TriangulateExt<T> for [P]
+where
+    T: Coordinate,
+    P: Point<T>,
+{
+    fn triangulate(&self) -> Result<Vec<Triangle>> {
+        triangulate(self)
+    }
+
+    fn triangulate_unsorted(&self) -> Result<Vec<Triangle>> {
+        let all: Vec<usize> = (0..self.len()).collect();
+        triangulate_subset(self, &all)
+    }
+
+    fn convex_hull(&self) -> Result<Vec<usize>> {
+        convex_hull_only(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{sort_points, triangulate, Triangle, TriangulationPoint};
+    use super::TriangulateExt;
+
+    #[test]
+    fn test_triangulate_ext() {
+        let points = [
+            TriangulationPoint::new(30.0, 25.0),
+            TriangulationPoint::new(10.0, 10.0),
+            TriangulationPoint::new(40.0, 15.0),
+            TriangulationPoint::new(15.0, 25.0),
+            TriangulationPoint::new(25.0, 15.0),
+        ];
+        let mut sorted = points.to_vec();
+        sorted.sort_by(sort_points);
+        assert_eq!(sorted.triangulate().unwrap(), triangulate(&sorted).unwrap());
+
+        // The unsorted triangles index into the original points.
+        let triangles = points.triangulate_unsorted().unwrap();
+        let expected: Vec<_> = triangulate(&sorted)
+            .unwrap()
+            .into_iter()
+            .map(|t| {
+                let index = |i: usize| points.iter().position(|p| *p == sorted[i]).unwrap();
+                Triangle(index(t.0), index(t.1), index(t.2))
+            })
+            .collect();
+        assert_eq!(triangles, expected);
+    }
+}
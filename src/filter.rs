@@ -0,0 +1,488 @@
+// Copyright 2017-2018 Samuel Loretan <tynril@gmail.com> -- See LICENSE file
+
+//! Post-processing filters over the output of a triangulation.
+
+use super::buffer::Buffer;
+use super::topology::triangle_adjacency;
+use super::validate::{circle_tolerance, circumcircle};
+use super::{Coordinate, Point, Result, Triangle, TriangulateError, TriangulationPoint};
+
+/// Returns the triangles whose edges are all no longer than `max_len`.
+///
+/// Removing the triangles bridging far-apart points turns the convex triangulation into a concave,
+/// locally-dense mesh that follows the shape of the point set. This is similar in spirit to an
+/// alpha shape, which removes triangles based on the radius of their circumcircle instead: both
+/// discard the large triangles spanning empty regions, but filtering on the edge length also
+/// removes long and thin triangles whose circumcircle is small relative to their longest edge,
+/// and keeps the threshold expressed in the same unit as the distance between points.
+///
+/// The `triangles` must be indices into `points`, as returned by `triangulate`.
+///
+/// Example:
+///
+/// ```rust
+/// use rtriangulate::{filter_long_edges, triangulate, TriangulationPoint, Triangle};
+///
+/// let points = [
+///     TriangulationPoint::new(0.0, 0.0),
+///     TriangulationPoint::new(1.0, 2.0),
+///     TriangulationPoint::new(2.0, 0.0),
+///     TriangulationPoint::new(10.0, 1.0)
+/// ];
+/// let triangles = triangulate(&points).unwrap();
+/// assert_eq!(filter_long_edges(&points, &triangles, 3.0), [Triangle(0, 1, 2)]);
+/// ```
+pub fn filter_long_edges<T, P>(points: &[P], triangles: &[Triangle], max_len: T) -> Vec<Triangle>
+where
+    T: Coordinate,
+    P: Point<T>,
+{
+    // Compare squared lengths, which saves computing square roots.
+    let max_len_sq = max_len * max_len;
+    triangles
+        .iter()
+        .filter(|t| {
+            t.edges().iter().all(|e| {
+                let (a, b) = (&points[e.0], &points[e.1]);
+                let (dx, dy) = (a.x() - b.x(), a.y() - b.y());
+                dx * dx + dy * dy <= max_len_sq
+            })
+        })
+        .cloned()
+        .collect()
+}
+
+/// Removes the triangles whose smallest angle has a squared sine below `min_sin_sq`.
+///
+/// The smallest angle of a triangle is at most 60 degrees, and its sine is the smallest of the
+/// three, even when another angle is obtuse.
+pub(crate) fn drop_slivers<T, P, B>(points: &[P], triangles: &mut B, min_sin_sq: T)
+where
+    B: Buffer<Triangle>,
+    T: Coordinate,
+    P: Point<T>,
+{
+    triangles.retain(|t| {
+        let (a, b, c) = (&points[t.0], &points[t.1], &points[t.2]);
+        let sin_sq = |o: &P, u: &P, v: &P| {
+            let (ux, uy) = (u.x() - o.x(), u.y() - o.y());
+            let (vx, vy) = (v.x() - o.x(), v.y() - o.y());
+            let lengths_sq = (ux * ux + uy * uy) * (vx * vx + vy * vy);
+            if lengths_sq > T::zero() {
+                let cross = ux * vy - uy * vx;
+                cross * cross / lengths_sq
+            } else {
+                T::zero()
+            }
+        };
+        let smallest_sin_sq = sin_sq(a, b, c).min(sin_sq(b, c, a)).min(sin_sq(c, a, b));
+        smallest_sin_sq >= min_sin_sq
+    });
+}
+
+/// Flips the edges between cocircular triangles, so that every edge of cocircular points is the
+/// diagonal of its quad with the lowest-indexed vertex.
+///
+/// The flips are done in passes over the triangles, flipping every triangle at most once a pass,
+/// until there is nothing to flip anymore. Every flip replaces an edge by one with a lower
+/// smallest index, so that always ends. The triangles keep their position, and stay clockwise.
+pub(crate) fn break_cocircular_ties<T, P, B>(points: &[P], triangles: &mut B)
+where
+    B: Buffer<Triangle>,
+    T: Coordinate,
+    P: Point<T>,
+{
+    let tolerance = circle_tolerance::<T>();
+    let orientation = |a: usize, b: usize, p: usize| {
+        let (a, b, p) = (&points[a], &points[b], &points[p]);
+        (b.x() - a.x()) * (p.y() - a.y()) - (b.y() - a.y()) * (p.x() - a.x())
+    };
+    let cocircular = |a: usize, b: usize, c: usize, d: usize| match circumcircle(
+        &points[a], &points[b], &points[c],
+    ) {
+        Some((center_x, center_y, radius_sq)) => {
+            let (dx, dy) = (points[d].x() - center_x, points[d].y() - center_y);
+            (radius_sq - (dx * dx + dy * dy)).abs() <= radius_sq * tolerance
+        }
+        None => false,
+    };
+
+    loop {
+        let adjacency = triangle_adjacency(triangles);
+        let mut flipped = vec![false; triangles.len()];
+        for t in 0..triangles.len() {
+            for (k, neighbour) in adjacency[t].iter().enumerate() {
+                let u = match *neighbour {
+                    Some(u) if !flipped[t] && !flipped[u] => u,
+                    _ => continue,
+                };
+                let triangle = [triangles[t].0, triangles[t].1, triangles[t].2];
+                let (a, b, c) = (triangle[k], triangle[(k + 1) % 3], triangle[(k + 2) % 3]);
+                let d = [triangles[u].0, triangles[u].1, triangles[u].2]
+                    .iter()
+                    .cloned()
+                    .find(|&v| v != a && v != b)
+                    .unwrap();
+
+                // Replace the triangles `a, b, c` and `b, a, d` by `c, a, d` and `d, b, c`.
+                if c.min(d) < a.min(b)
+                    && cocircular(a, b, c, d)
+                    && orientation(a, d, c) < T::zero()
+                    && orientation(d, b, c) < T::zero()
+                {
+                    triangles[t] = Triangle(c, a, d);
+                    triangles[u] = Triangle(d, b, c);
+                    flipped[t] = true;
+                    flipped[u] = true;
+                }
+            }
+        }
+        if !flipped.contains(&true) {
+            break;
+        }
+    }
+}
+
+/// Removes the points which aren't referenced by any triangle, and remaps the triangles indices
+/// accordingly.
+///
+/// After filtering triangles out of a triangulation, some points can end up not being used by
+/// any triangle anymore. This returns a copy of the points without those orphans (keeping the
+/// relative order of the remaining points), along with the triangles re-indexed into this new
+/// list of points.
+///
+/// Example:
+///
+/// ```rust
+/// use rtriangulate::{compact, Triangle, TriangulationPoint};
+///
+/// let points = [
+///     TriangulationPoint::new(0.0, 0.0),
+///     TriangulationPoint::new(1.0, 2.0),
+///     TriangulationPoint::new(2.0, 0.0),
+///     TriangulationPoint::new(3.0, 2.0)
+/// ];
+/// let (points, triangles) = compact(&points, &[Triangle(1, 3, 2)]);
+/// assert_eq!(points, [
+///     TriangulationPoint::new(1.0, 2.0),
+///     TriangulationPoint::new(2.0, 0.0),
+///     TriangulationPoint::new(3.0, 2.0)
+/// ]);
+/// assert_eq!(triangles, [Triangle(0, 2, 1)]);
+/// ```
+pub fn compact<T, P>(
+    points: &[P],
+    triangles: &[Triangle],
+) -> (Vec<TriangulationPoint<T>>, Vec<Triangle>)
+where
+    T: Coordinate,
+    P: Point<T>,
+{
+    // Flag the points in use, then give them new indices in their original order.
+    let mut remap = vec![None; points.len()];
+    for t in triangles {
+        remap[t.0] = Some(0);
+        remap[t.1] = Some(0);
+        remap[t.2] = Some(0);
+    }
+
+    let mut compacted_points = Vec::with_capacity(points.len());
+    for (p, index) in points.iter().zip(remap.iter_mut()) {
+        if index.is_some() {
+            *index = Some(compacted_points.len());
+            compacted_points.push(TriangulationPoint::new(p.x(), p.y()));
+        }
+    }
+
+    let index = |i: usize| remap[i].unwrap();
+    let compacted_triangles = triangles
+        .iter()
+        .map(|t| Triangle(index(t.0), index(t.1), index(t.2)))
+        .collect();
+
+    (compacted_points, compacted_triangles)
+}
+
+/// The order in which the vertices of a triangle are listed, as seen with the `y` axis going up.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Winding {
+    /// Clockwise order, as output by `triangulate`.
+    Clockwise,
+    /// Counter-clockwise order, which is the default front face in OpenGL.
+    CounterClockwise,
+}
+
+/// Reorders the vertices of the triangles not having the requested winding, in place.
+///
+/// The winding of a triangle is given by the sign of its area, and is reversed by swapping its
+/// last two vertices. This normalizes triangles coming from any source, and not only from
+/// `triangulate`, which only outputs clockwise triangles. Degenerate triangles, whose area is
+/// zero, have no winding and are left as is.
+///
+/// Example:
+///
+/// ```rust
+/// use rtriangulate::{ensure_winding, triangulate, Triangle, TriangulationPoint, Winding};
+///
+/// let points = [
+///     TriangulationPoint::new(0.0, 0.0),
+///     TriangulationPoint::new(1.0, 2.0),
+///     TriangulationPoint::new(2.0, 0.0)
+/// ];
+/// let mut triangles = triangulate(&points).unwrap();
+/// ensure_winding(&points, &mut triangles, Winding::CounterClockwise);
+/// assert_eq!(triangles, [Triangle(0, 2, 1)]);
+/// ```
+pub fn ensure_winding<T, P>(points: &[P], triangles: &mut [Triangle], winding: Winding)
+where
+    T: Coordinate,
+    P: Point<T>,
+{
+    for t in triangles {
+        let (a, b, c) = (&points[t.0], &points[t.1], &points[t.2]);
+        let cross = (b.x() - a.x()) * (c.y() - a.y()) - (b.y() - a.y()) * (c.x() - a.x());
+        let reversed = match winding {
+            Winding::Clockwise => cross > T::zero(),
+            Winding::CounterClockwise => cross < T::zero(),
+        };
+        if reversed {
+            *t = Triangle(t.0, t.2, t.1);
+        }
+    }
+}
+
+/// Reorders the triangles along a Z-order curve, by the Morton code of their centroid.
+///
+/// The centroids are quantized to 16 bits per axis, over the bounding box of all the `points`:
+/// the box is split into a grid of 65536 by 65536 cells, and the Morton code of a triangle is the
+/// 32-bit interleaving of the column and row of the cell its centroid falls in. Triangles in the
+/// same cell keep their relative order. The triangles themselves are not changed.
+///
+/// Neighbouring triangles end up close to each other in the output, which improves the hit rate
+/// of the vertex caches of GPUs when rendering large meshes.
+///
+/// The coordinates are quantized in `f64`: if one of the `points` can't be converted to `f64`,
+/// `TriangulateError::UnsupportedCoordinate` is returned and the triangles are left untouched.
+///
+/// Example:
+///
+/// ```rust
+/// use rtriangulate::{sort_triangles_morton, Triangle, TriangulationPoint};
+///
+/// let points = [
+///     TriangulationPoint::new(0.0, 0.0),
+///     TriangulationPoint::new(0.0, 2.0),
+///     TriangulationPoint::new(2.0, 0.0),
+///     TriangulationPoint::new(2.0, 2.0)
+/// ];
+/// let mut triangles = [Triangle(2, 1, 3), Triangle(0, 1, 2)];
+/// sort_triangles_morton(&points, &mut triangles).unwrap();
+/// assert_eq!(triangles, [Triangle(0, 1, 2), Triangle(2, 1, 3)]);
+/// ```
+pub fn sort_triangles_morton<T, P>(points: &[P], triangles: &mut [Triangle]) -> Result<()>
+where
+    T: Coordinate,
+    P: Point<T>,
+{
+    let coordinates = points
+        .iter()
+        .map(|p| Some((p.x().to_f64()?, p.y().to_f64()?)))
+        .collect::<Option<Vec<_>>>()
+        .ok_or(TriangulateError::UnsupportedCoordinate)?;
+    let (min, max) = coordinates.iter().fold(
+        (
+            (f64::INFINITY, f64::INFINITY),
+            (f64::NEG_INFINITY, f64::NEG_INFINITY),
+        ),
+        |(min, max), &(x, y)| ((min.0.min(x), min.1.min(y)), (max.0.max(x), max.1.max(y))),
+    );
+    let quantize = |value: f64, min: f64, max: f64| {
+        if max > min {
+            ((value - min) / (max - min) * 65535.0).round() as u32
+        } else {
+            0
+        }
+    };
+
+    triangles.sort_by_key(|t| {
+        let corners = [coordinates[t.0], coordinates[t.1], coordinates[t.2]];
+        let x = (corners[0].0 + corners[1].0 + corners[2].0) / 3.0;
+        let y = (corners[0].1 + corners[1].1 + corners[2].1) / 3.0;
+        spread_bits(quantize(x, min.0, max.0)) | (spread_bits(quantize(y, min.1, max.1)) << 1)
+    });
+    Ok(())
+}
+
+/// Spreads the 16 low bits of a value over the even bits of the result.
+fn spread_bits(value: u32) -> u32 {
+    let mut value = value & 0xffff;
+    value = (value | (value << 8)) & 0x00ff_00ff;
+    value = (value | (value << 4)) & 0x0f0f_0f0f;
+    value = (value | (value << 2)) & 0x3333_3333;
+    (value | (value << 1)) & 0x5555_5555
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{triangulate, Triangle, TriangulationPoint};
+    use super::{compact, ensure_winding, filter_long_edges, sort_triangles_morton, Winding};
+
+    #[test]
+    fn test_filter_long_edges_clusters() {
+        // Two clusters of points, separated by a gap.
+        let points = [
+            TriangulationPoint::new(0.0, 0.0),
+            TriangulationPoint::new(1.0, 2.0),
+            TriangulationPoint::new(2.0, 0.0),
+            TriangulationPoint::new(2.5, 1.5),
+            TriangulationPoint::new(10.0, 0.0),
+            TriangulationPoint::new(11.0, 2.0),
+            TriangulationPoint::new(12.0, 0.0),
+        ];
+        let tris = triangulate(&points).unwrap();
+        assert!(tris.len() > 3);
+
+        let filtered = filter_long_edges(&points, &tris, 3.0);
+        assert_eq!(filtered.len(), 3);
+        for t in &filtered {
+            let left_cluster = t.0 < 4 && t.1 < 4 && t.2 < 4;
+            let right_cluster = t.0 >= 4 && t.1 >= 4 && t.2 >= 4;
+            assert!(left_cluster || right_cluster);
+        }
+        assert!(filtered.contains(&Triangle(4, 5, 6)) || filtered.contains(&Triangle(5, 6, 4)));
+    }
+
+    #[test]
+    fn test_filter_long_edges_keeps_all() {
+        let points = [
+            TriangulationPoint::new(0.0, 0.0),
+            TriangulationPoint::new(1.0, 2.0),
+            TriangulationPoint::new(2.0, 0.0),
+        ];
+        let tris = triangulate(&points).unwrap();
+
+        assert_eq!(filter_long_edges(&points, &tris, 2.0_f64.hypot(1.0)), tris);
+        assert!(filter_long_edges(&points, &tris, 2.0).is_empty());
+    }
+
+    #[test]
+    fn test_compact() {
+        let points = [
+            TriangulationPoint::new(0.0, 0.0),
+            TriangulationPoint::new(1.0, 2.0),
+            TriangulationPoint::new(2.0, 0.0),
+            TriangulationPoint::new(2.5, 1.5),
+            TriangulationPoint::new(10.0, 0.0),
+            TriangulationPoint::new(11.0, 2.0),
+            TriangulationPoint::new(12.0, 0.0),
+        ];
+        let tris = triangulate(&points).unwrap();
+        let filtered = filter_long_edges(&points, &tris, 3.0);
+        let right_cluster: Vec<Triangle> = filtered.into_iter().filter(|t| t.0 >= 4).collect();
+
+        let (compacted_points, compacted_tris) = compact(&points, &right_cluster);
+        assert_eq!(compacted_points, &points[4..]);
+        assert_eq!(compacted_tris.len(), 1);
+        let t = compacted_tris[0];
+        assert_eq!(
+            (points[t.0 + 4], points[t.1 + 4], points[t.2 + 4]),
+            (
+                points[right_cluster[0].0],
+                points[right_cluster[0].1],
+                points[right_cluster[0].2]
+            )
+        );
+    }
+
+    #[test]
+    fn test_compact_nothing_to_remove() {
+        let points = [
+            TriangulationPoint::new(10.0, 10.0),
+            TriangulationPoint::new(15.0, 25.0),
+            TriangulationPoint::new(25.0, 15.0),
+        ];
+        let tris = triangulate(&points).unwrap();
+
+        assert_eq!(compact(&points, &tris), (points.to_vec(), tris));
+    }
+
+    #[test]
+    fn test_ensure_winding() {
+        let points = [
+            TriangulationPoint::new(0.0, 0.0),
+            TriangulationPoint::new(0.0, 2.0),
+            TriangulationPoint::new(2.0, 0.0),
+            TriangulationPoint::new(2.0, 2.0),
+            TriangulationPoint::new(4.0, 0.0),
+        ];
+        let mixed = [
+            Triangle(0, 1, 2),
+            Triangle(1, 3, 2),
+            Triangle(2, 4, 3),
+            Triangle(0, 2, 4),
+        ];
+
+        let mut triangles = mixed;
+        ensure_winding(&points, &mut triangles, Winding::Clockwise);
+        assert_eq!(
+            triangles,
+            [
+                Triangle(0, 1, 2),
+                Triangle(1, 3, 2),
+                Triangle(2, 3, 4),
+                Triangle(0, 2, 4),
+            ]
+        );
+
+        ensure_winding(&points, &mut triangles, Winding::CounterClockwise);
+        assert_eq!(
+            triangles,
+            [
+                Triangle(0, 2, 1),
+                Triangle(1, 2, 3),
+                Triangle(2, 4, 3),
+                Triangle(0, 2, 4),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_sort_triangles_morton() {
+        let points: Vec<_> = (0..100)
+            .map(|i| TriangulationPoint::new((i / 10) as f64, (i % 10) as f64))
+            .collect();
+        let tris = triangulate(&points).unwrap();
+        let mut sorted = tris.clone();
+        sort_triangles_morton(&points, &mut sorted).unwrap();
+
+        // The same triangles, reordered.
+        let mut expected = tris.clone();
+        expected.sort();
+        let mut actual = sorted.clone();
+        actual.sort();
+        assert_eq!(actual, expected);
+
+        // Consecutive triangles are closer to each other than in the insertion order.
+        let centroid = |t: &Triangle| {
+            let (a, b, c) = (&points[t.0], &points[t.1], &points[t.2]);
+            ((a.x + b.x + c.x) / 3.0, (a.y + b.y + c.y) / 3.0)
+        };
+        let jumps = |triangles: &[Triangle]| -> f64 {
+            triangles
+                .windows(2)
+                .map(|w| {
+                    let (a, b) = (centroid(&w[0]), centroid(&w[1]));
+                    (a.0 - b.0).hypot(a.1 - b.1)
+                })
+                .sum()
+        };
+        assert!(jumps(&sorted) < jumps(&tris));
+
+        // The first quadrant of the grid comes first.
+        let quarter = sorted.len() / 4;
+        assert!(sorted[..quarter].iter().all(|t| {
+            let (x, y) = centroid(t);
+            x < 5.0 && y < 5.0
+        }));
+    }
+}
@@ -0,0 +1,1052 @@
+// Copyright 2017-2018 Samuel Loretan <tynril@gmail.com> -- See LICENSE file
+
+//! Geometric measures over the triangles of a triangulation.
+
+use std::collections::HashMap;
+use std::ops::{Add, Div, Mul, Sub};
+
+use num_traits::Float;
+
+use super::topology::boundary_edges;
+use super::{Edge, Point, Triangle, TriangulationPoint};
+
+/// Returns the center of the circumcircle of a triangle.
+///
+/// The `triangle` is made of indices into `points`. Returns `None` if the triangle is degenerate
+/// (its three points are collinear), as its circumcircle is then undefined.
+///
+/// Example:
+///
+/// ```rust
+/// use rtriangulate::{circumcenter, Triangle, TriangulationPoint};
+///
+/// let points = [
+///     TriangulationPoint::new(0.0, 0.0),
+///     TriangulationPoint::new(0.0, 2.0),
+///     TriangulationPoint::new(2.0, 0.0)
+/// ];
+/// assert_eq!(
+///     circumcenter(&points, &Triangle(0, 1, 2)),
+///     Some(TriangulationPoint::new(1.0, 1.0))
+/// );
+/// ```
+pub fn circumcenter<T, P>(points: &[P], triangle: &Triangle) -> Option<TriangulationPoint<T>>
+where
+    T: Float,
+    P: Point<T>,
+{
+    let (a, b, c) = (
+        &points[triangle.0],
+        &points[triangle.1],
+        &points[triangle.2],
+    );
+    let (x, y) = circumcenter_offset((a.x(), a.y()), (b.x(), b.y()), (c.x(), c.y()), T::zero())?;
+    if !x.is_finite() || !y.is_finite() {
+        return None;
+    }
+
+    Some(TriangulationPoint::new(a.x() + x, a.y() + y))
+}
+
+/// Computes the circumcenter of the triangle `a`, `b`, `c` relative to `a`, or `None` if the
+/// triangle is degenerate.
+///
+/// This only needs the arithmetic operators, so that both `circumcenter` and the `Coordinate`
+/// verification helpers use it. The offset is infinite or NaN for a nearly degenerate triangle,
+/// which the callers check with their own coordinate type.
+pub(crate) fn circumcenter_offset<T>(a: (T, T), b: (T, T), c: (T, T), zero: T) -> Option<(T, T)>
+where
+    T: Copy + PartialEq + Add<Output = T> + Sub<Output = T> + Mul<Output = T> + Div<Output = T>,
+{
+    // Work relative to the first point, which keeps the magnitudes small.
+    let (bx, by) = (b.0 - a.0, b.1 - a.1);
+    let (cx, cy) = (c.0 - a.0, c.1 - a.1);
+    let d = bx * cy - by * cx;
+    let d = d + d;
+    if d == zero {
+        return None;
+    }
+
+    let b_sq = bx * bx + by * by;
+    let c_sq = cx * cx + cy * cy;
+    Some(((cy * b_sq - by * c_sq) / d, (bx * c_sq - cx * b_sq) / d))
+}
+
+/// Returns the radius of the circumcircle of a triangle.
+///
+/// The `triangle` is made of indices into `points`. Returns `None` if the triangle is degenerate
+/// (its three points are collinear), as its circumcircle is then undefined.
+///
+/// Example:
+///
+/// ```rust
+/// use rtriangulate::{circumradius, Triangle, TriangulationPoint};
+///
+/// let points = [
+///     TriangulationPoint::new(0.0, 0.0),
+///     TriangulationPoint::new(0.0, 2.0),
+///     TriangulationPoint::new(2.0, 0.0)
+/// ];
+/// assert_eq!(circumradius(&points, &Triangle(0, 1, 2)), Some(2.0f64.sqrt()));
+/// ```
+pub fn circumradius<T, P>(points: &[P], triangle: &Triangle) -> Option<T>
+where
+    T: Float,
+    P: Point<T>,
+{
+    circumcenter(points, triangle).map(|center| {
+        let a = &points[triangle.0];
+        (a.x() - center.x).hypot(a.y() - center.y)
+    })
+}
+
+/// Returns the point halfway between the two vertices of an edge.
+///
+/// Example:
+///
+/// ```rust
+/// use rtriangulate::{edge_midpoint, Edge, TriangulationPoint};
+///
+/// let points = [TriangulationPoint::new(0.0, 1.0), TriangulationPoint::new(2.0, 4.0)];
+/// assert_eq!(edge_midpoint(&points, &Edge(0, 1)), TriangulationPoint::new(1.0, 2.5));
+/// ```
+pub fn edge_midpoint<T, P>(points: &[P], edge: &Edge) -> TriangulationPoint<T>
+where
+    T: Float,
+    P: Point<T>,
+{
+    let (a, b) = (&points[edge.0], &points[edge.1]);
+    let half = T::from(0.5).unwrap();
+    TriangulationPoint::new((a.x() + b.x()) * half, (a.y() + b.y()) * half)
+}
+
+/// Returns the direction of the perpendicular bisector of an edge, as a unit vector.
+///
+/// The bisector goes through `edge_midpoint`, and its direction is the one of the edge, from
+/// `edge.0` to `edge.1`, turned a quarter counter-clockwise. Unlike a slope, which is infinite for
+/// the bisector of a horizontal edge, this is defined for every direction: the bisector of a
+/// vertical edge is horizontal, and the one of a horizontal edge vertical. Returns `None` if the
+/// two vertices are coincident, as the edge then has no direction.
+///
+/// Example:
+///
+/// ```rust
+/// use rtriangulate::{bisector_direction, Edge, TriangulationPoint};
+///
+/// let points = [
+///     TriangulationPoint::new(0.0, 0.0),
+///     TriangulationPoint::new(0.0, 2.0),
+///     TriangulationPoint::new(3.0, 4.0)
+/// ];
+/// assert_eq!(bisector_direction(&points, &Edge(0, 1)), Some(TriangulationPoint::new(-1.0, 0.0)));
+/// assert_eq!(bisector_direction(&points, &Edge(0, 2)), Some(TriangulationPoint::new(-0.8, 0.6)));
+/// assert_eq!(bisector_direction(&points, &Edge(1, 1)), None);
+/// ```
+pub fn bisector_direction<T, P>(points: &[P], edge: &Edge) -> Option<TriangulationPoint<T>>
+where
+    T: Float,
+    P: Point<T>,
+{
+    let (a, b) = (&points[edge.0], &points[edge.1]);
+    let (dx, dy) = (b.x() - a.x(), b.y() - a.y());
+    let length = dx.hypot(dy);
+    if length == T::zero() || !length.is_finite() {
+        return None;
+    }
+    Some(TriangulationPoint::new(-dy / length, dx / length))
+}
+
+/// Returns the circumcircle of every triangle, as its center and radius, in the same order as
+/// `triangles`.
+///
+/// This is the same as calling `circumcenter` and `circumradius` for every triangle, with the
+/// center only computed once. Degenerate triangles get `None`, so that the circumcircles stay
+/// aligned with their triangles. These are the vertices of the Voronoi diagram of the points.
+///
+/// Example:
+///
+/// ```rust
+/// use rtriangulate::{circumcircles, Triangle, TriangulationPoint};
+///
+/// let points = [
+///     TriangulationPoint::new(0.0, 0.0),
+///     TriangulationPoint::new(0.0, 2.0),
+///     TriangulationPoint::new(2.0, 0.0),
+///     TriangulationPoint::new(4.0, 0.0)
+/// ];
+/// assert_eq!(
+///     circumcircles(&points, &[Triangle(0, 1, 2), Triangle(0, 2, 3)]),
+///     [Some((TriangulationPoint::new(1.0, 1.0), 2.0f64.sqrt())), None]
+/// );
+/// ```
+pub fn circumcircles<T, P>(
+    points: &[P],
+    triangles: &[Triangle],
+) -> Vec<Option<(TriangulationPoint<T>, T)>>
+where
+    T: Float,
+    P: Point<T>,
+{
+    triangles
+        .iter()
+        .map(|t| {
+            circumcenter(points, t).map(|center| {
+                let a = &points[t.0];
+                (center, (a.x() - center.x).hypot(a.y() - center.y))
+            })
+        })
+        .collect()
+}
+
+/// Returns the radius-edge ratio of every triangle, in the same order as `triangles`.
+///
+/// The radius-edge ratio of a triangle is the radius of its circumcircle divided by the length of
+/// its shortest edge. It is the quality measure that Delaunay refinement algorithms (such as
+/// Ruppert's) aim to bound: the lowest possible value is `1 / sqrt(3)`, for an equilateral
+/// triangle, and high values flag thin triangles worth refining. Degenerate triangles have an
+/// infinite ratio.
+///
+/// Example:
+///
+/// ```rust
+/// use rtriangulate::{triangle_qualities, Triangle, TriangulationPoint};
+///
+/// let points = [
+///     TriangulationPoint::new(0.0, 0.0),
+///     TriangulationPoint::new(0.0, 2.0),
+///     TriangulationPoint::new(2.0, 0.0)
+/// ];
+/// let qualities = triangle_qualities(&points, &[Triangle(0, 1, 2)]);
+/// assert!((qualities[0] - 2.0f64.sqrt() / 2.0).abs() < 1e-12);
+/// ```
+pub fn triangle_qualities<T, P>(points: &[P], triangles: &[Triangle]) -> Vec<T>
+where
+    T: Float,
+    P: Point<T>,
+{
+    triangles
+        .iter()
+        .map(|t| {
+            let shortest_edge = triangle_edge_lengths(points, t)
+                .iter()
+                .cloned()
+                .fold(T::infinity(), T::min);
+            match circumradius(points, t) {
+                Some(radius) if shortest_edge > T::zero() => radius / shortest_edge,
+                _ => T::infinity(),
+            }
+        })
+        .collect()
+}
+
+/// Returns the aspect ratio of every triangle, in the same order as `triangles`.
+///
+/// The aspect ratio of a triangle is the radius of its circumcircle divided by the radius of its
+/// incircle, the latter being its area divided by half its perimeter. Its lowest value is `2`, for
+/// an equilateral triangle, and it grows without bound as triangles get thinner, whether they
+/// have a small angle or a large one. Degenerate triangles, which have no area, have an infinite
+/// ratio. Unlike `triangle_qualities`, this also flags the flat triangles with no short edge.
+///
+/// Example:
+///
+/// ```rust
+/// use rtriangulate::{aspect_ratios, Triangle, TriangulationPoint};
+///
+/// let points = [
+///     TriangulationPoint::new(0.0, 0.0),
+///     TriangulationPoint::new(0.0, 2.0),
+///     TriangulationPoint::new(2.0, 0.0)
+/// ];
+/// let ratios = aspect_ratios(&points, &[Triangle(0, 1, 2)]);
+/// assert!((ratios[0] - (1.0 + 2.0f64.sqrt())).abs() < 1e-12);
+/// ```
+pub fn aspect_ratios<T, P>(points: &[P], triangles: &[Triangle]) -> Vec<T>
+where
+    T: Float,
+    P: Point<T>,
+{
+    let two = T::one() + T::one();
+    triangles
+        .iter()
+        .map(|t| {
+            let (a, b, c) = (&points[t.0], &points[t.1], &points[t.2]);
+            let [ab, bc, ca] = triangle_edge_lengths(points, t);
+            let area =
+                ((b.x() - a.x()) * (c.y() - a.y()) - (b.y() - a.y()) * (c.x() - a.x())).abs() / two;
+            if area <= T::zero() {
+                return T::infinity();
+            }
+
+            // The circumradius is `ab * bc * ca / (4 * area)`, and the inradius
+            // `area / semiperimeter`.
+            let semiperimeter = (ab + bc + ca) / two;
+            let circumradius = ab * bc * ca / (two * two * area);
+            circumradius * semiperimeter / area
+        })
+        .collect()
+}
+
+/// Returns the lengths of the three edges of every triangle, in the same order as `triangles`.
+///
+/// The lengths of a triangle are in the order of `Triangle::edges`: from its first vertex to its
+/// second one, from its second vertex to its third one, then from its third vertex back to its
+/// first one. This is what `triangle_qualities` and `aspect_ratios` are computed from, for the
+/// analyses needing the lengths themselves.
+///
+/// Example:
+///
+/// ```rust
+/// use rtriangulate::{edge_lengths, Triangle, TriangulationPoint};
+///
+/// let points = [
+///     TriangulationPoint::new(0.0, 0.0),
+///     TriangulationPoint::new(0.0, 3.0),
+///     TriangulationPoint::new(4.0, 0.0)
+/// ];
+/// assert_eq!(edge_lengths(&points, &[Triangle(0, 1, 2)]), [[3.0, 5.0, 4.0]]);
+/// ```
+pub fn edge_lengths<T, P>(points: &[P], triangles: &[Triangle]) -> Vec<[T; 3]>
+where
+    T: Float,
+    P: Point<T>,
+{
+    triangles
+        .iter()
+        .map(|t| triangle_edge_lengths(points, t))
+        .collect()
+}
+
+/// Returns the lengths of the edges of a triangle, in the order of `Triangle::edges`.
+fn triangle_edge_lengths<T, P>(points: &[P], triangle: &Triangle) -> [T; 3]
+where
+    T: Float,
+    P: Point<T>,
+{
+    let length = |e: &Edge| {
+        let (a, b) = (&points[e.0], &points[e.1]);
+        (a.x() - b.x()).hypot(a.y() - b.y())
+    };
+    let edges = triangle.edges();
+    [length(&edges[0]), length(&edges[1]), length(&edges[2])]
+}
+
+/// Returns the part of a triangle within a convex polygon, as a polygon.
+///
+/// The clip polygon must be convex, and its vertices consistently wound, in either direction. The
+/// triangle is clipped against every edge of the clip polygon in turn (the Sutherland-Hodgman
+/// algorithm): the output keeps the winding of the triangle, `a`, `b`, `c`, and is empty when the
+/// triangle is outside of the clip polygon, or when they only touch. The output can have up to
+/// three more vertices than the clip polygon, and some of them can be coincident where the triangle
+/// and the clip polygon share a vertex.
+///
+/// This is meant for masking a mesh to a convex viewport, clipping its triangles one by one.
+///
+/// Example:
+///
+/// ```rust
+/// use rtriangulate::{clip_triangle_convex, TriangulationPoint};
+///
+/// let square = [
+///     TriangulationPoint::new(0.0, 0.0),
+///     TriangulationPoint::new(2.0, 0.0),
+///     TriangulationPoint::new(2.0, 2.0),
+///     TriangulationPoint::new(0.0, 2.0)
+/// ];
+/// let a = TriangulationPoint::new(1.0, 1.0);
+/// let b = TriangulationPoint::new(3.0, 1.0);
+/// let c = TriangulationPoint::new(1.0, 3.0);
+/// assert_eq!(clip_triangle_convex(&a, &b, &c, &square), [
+///     TriangulationPoint::new(1.0, 1.0),
+///     TriangulationPoint::new(2.0, 1.0),
+///     TriangulationPoint::new(2.0, 2.0),
+///     TriangulationPoint::new(1.0, 2.0)
+/// ]);
+/// ```
+pub fn clip_triangle_convex<T, P>(
+    a: &P,
+    b: &P,
+    c: &P,
+    clip_poly: &[TriangulationPoint<T>],
+) -> Vec<TriangulationPoint<T>>
+where
+    T: Float,
+    P: Point<T>,
+{
+    let triangle = [a, b, c]
+        .iter()
+        .map(|p| TriangulationPoint::new(p.x(), p.y()))
+        .collect();
+    clip_polygon(triangle, clip_poly)
+}
+
+/// Clips a polygon against a convex polygon, with the Sutherland-Hodgman algorithm. Returns an
+/// empty polygon if less than three vertices are left.
+pub(crate) fn clip_polygon<T: Float>(
+    mut polygon: Vec<TriangulationPoint<T>>,
+    clip_poly: &[TriangulationPoint<T>],
+) -> Vec<TriangulationPoint<T>> {
+    let cross =
+        |p: &TriangulationPoint<T>, q: &TriangulationPoint<T>, v: &TriangulationPoint<T>| {
+            (q.x - p.x) * (v.y - p.y) - (q.y - p.y) * (v.x - p.x)
+        };
+
+    // The inside of the clip polygon is on the left of its edges if it is counter-clockwise.
+    let doubled_area = (0..clip_poly.len()).fold(T::zero(), |area, k| {
+        let (p, q) = (&clip_poly[k], &clip_poly[(k + 1) % clip_poly.len()]);
+        area + p.x * q.y - q.x * p.y
+    });
+    let side = if doubled_area < T::zero() {
+        -T::one()
+    } else {
+        T::one()
+    };
+
+    for k in 0..clip_poly.len() {
+        if polygon.len() < 3 {
+            break;
+        }
+        let (p, q) = (&clip_poly[k], &clip_poly[(k + 1) % clip_poly.len()]);
+        let mut clipped = Vec::with_capacity(polygon.len() + 1);
+        for i in 0..polygon.len() {
+            let (u, v) = (&polygon[i], &polygon[(i + 1) % polygon.len()]);
+            let (cu, cv) = (cross(p, q, u) * side, cross(p, q, v) * side);
+            if cu >= T::zero() {
+                clipped.push(*u);
+            }
+            if (cu < T::zero()) != (cv < T::zero()) && cu != T::zero() && cv != T::zero() {
+                let t = cu / (cu - cv);
+                clipped.push(TriangulationPoint::new(
+                    u.x + (v.x - u.x) * t,
+                    u.y + (v.y - u.y) * t,
+                ));
+            }
+        }
+        polygon = clipped;
+    }
+
+    if polygon.len() < 3 {
+        polygon.clear();
+    }
+    polygon
+}
+
+/// Returns the boundary edges of the triangulation, each with its unit outward normal.
+///
+/// Every edge is oriented so that the triangulation lies on its left, which means that the hull
+/// of the output of `triangulate` is traversed counter-clockwise: each edge starts where the
+/// previous one ended. The first edge starts from the lowest vertex index of the boundary. If the
+/// boundary is made of several loops (e.g. once `filter_long_edges` made holes), the loops follow
+/// each other, in the same way.
+///
+/// The interior side of an edge is given by the single triangle it belongs to. Degenerate edges,
+/// whose two vertices are coincident, get a zero normal.
+///
+/// Example:
+///
+/// ```rust
+/// use rtriangulate::{hull_with_normals, Edge, Triangle, TriangulationPoint};
+///
+/// let points = [
+///     TriangulationPoint::new(0.0, 0.0),
+///     TriangulationPoint::new(0.0, 2.0),
+///     TriangulationPoint::new(2.0, 0.0)
+/// ];
+/// let hull = hull_with_normals(&points, &[Triangle(0, 1, 2)]);
+/// assert_eq!(hull[0], (Edge(0, 2), TriangulationPoint::new(0.0, -1.0)));
+/// assert_eq!(hull[2], (Edge(1, 0), TriangulationPoint::new(-1.0, 0.0)));
+/// ```
+pub fn hull_with_normals<T, P>(
+    points: &[P],
+    triangles: &[Triangle],
+) -> Vec<(Edge, TriangulationPoint<T>)>
+where
+    T: Float,
+    P: Point<T>,
+{
+    // Counts the triangles of every edge, and remembers the vertex facing it.
+    let mut edges = HashMap::<Edge, (usize, usize)>::with_capacity(triangles.len() * 2);
+    for t in triangles {
+        for (edge, &opposite) in t.edges().iter().zip(&[t.2, t.0, t.1]) {
+            edges.entry(edge.clone()).or_insert((0, opposite)).0 += 1;
+        }
+    }
+
+    // Orients every boundary edge so that its triangle lies on its left.
+    let cross = |a: usize, b: usize, c: usize| {
+        let (a, b, c) = (&points[a], &points[b], &points[c]);
+        (b.x() - a.x()) * (c.y() - a.y()) - (b.y() - a.y()) * (c.x() - a.x())
+    };
+    let mut next = HashMap::<usize, Vec<usize>>::new();
+    for (edge, &(count, opposite)) in &edges {
+        if count == 1 {
+            let (a, b) = if cross(edge.0, edge.1, opposite) < T::zero() {
+                (edge.1, edge.0)
+            } else {
+                (edge.0, edge.1)
+            };
+            next.entry(a).or_default().push(b);
+        }
+    }
+    for targets in next.values_mut() {
+        targets.sort_unstable_by(|a, b| b.cmp(a));
+    }
+
+    // Chains the edges, one loop after the other.
+    let mut starts: Vec<usize> = next.keys().cloned().collect();
+    starts.sort_unstable();
+    let mut hull = Vec::new();
+    for start in starts {
+        let mut current = start;
+        while let Some(to) = next.get_mut(&current).and_then(|targets| targets.pop()) {
+            let (a, b) = (&points[current], &points[to]);
+            let (dx, dy) = (b.x() - a.x(), b.y() - a.y());
+            let length = dx.hypot(dy);
+            let normal = if length > T::zero() {
+                TriangulationPoint::new(dy / length, -dx / length)
+            } else {
+                TriangulationPoint::new(T::zero(), T::zero())
+            };
+            hull.push((Edge(current, to), normal));
+            current = to;
+        }
+    }
+    hull
+}
+
+/// Returns the signed distance from a point to the boundary of the triangulation.
+///
+/// The distance is the one to the closest boundary edge, which for the output of `triangulate` is
+/// an edge of the convex hull. It is negative when `query` is inside a triangle, and positive when
+/// it is outside of all of them, and zero on the boundary itself. This also works once
+/// triangles have been removed (e.g. by `filter_long_edges`): the distance is then to the closest
+/// edge of the remaining mesh, holes included. Without any triangle, the distance is infinite.
+///
+/// Example:
+///
+/// ```rust
+/// use rtriangulate::{distance_to_hull, Triangle, TriangulationPoint};
+///
+/// let points = [
+///     TriangulationPoint::new(0.0, 0.0),
+///     TriangulationPoint::new(0.0, 4.0),
+///     TriangulationPoint::new(4.0, 0.0)
+/// ];
+/// let triangles = [Triangle(0, 1, 2)];
+/// assert_eq!(distance_to_hull(&points, &triangles, &TriangulationPoint::new(1.0, 1.0)), -1.0);
+/// assert_eq!(distance_to_hull(&points, &triangles, &TriangulationPoint::new(-3.0, 2.0)), 3.0);
+/// assert_eq!(distance_to_hull(&points, &triangles, &TriangulationPoint::new(7.0, -4.0)), 5.0);
+/// ```
+pub fn distance_to_hull<T, P, Q>(points: &[P], triangles: &[Triangle], query: &Q) -> T
+where
+    T: Float,
+    P: Point<T>,
+    Q: Point<T>,
+{
+    let (qx, qy) = (query.x(), query.y());
+    let distance = boundary_edges(triangles)
+        .iter()
+        .map(|e| segment_distance(&points[e.0], &points[e.1], qx, qy))
+        .fold(T::infinity(), T::min);
+
+    let inside = triangles
+        .iter()
+        .any(|t| contains(&points[t.0], &points[t.1], &points[t.2], qx, qy));
+    if inside {
+        -distance
+    } else {
+        distance
+    }
+}
+
+/// Returns the indices of the triangles which intersect a disk.
+///
+/// A triangle intersects the disk if any part of it, boundary included, is at most `radius` away
+/// from `center`: the test is exact, so triangles whose bounding box touches the disk but whose
+/// surface doesn't aren't returned. The indices are in increasing order. Every triangle is tested,
+/// which takes a time linear in their number.
+///
+/// Example:
+///
+/// ```rust
+/// use rtriangulate::{triangles_near, Triangle, TriangulationPoint};
+///
+/// let points = [
+///     TriangulationPoint::new(0.0, 0.0),
+///     TriangulationPoint::new(0.0, 4.0),
+///     TriangulationPoint::new(4.0, 0.0),
+///     TriangulationPoint::new(4.0, 4.0)
+/// ];
+/// let triangles = [Triangle(0, 1, 2), Triangle(2, 1, 3)];
+/// let near = |x, y, radius| {
+///     triangles_near(&points, &triangles, &TriangulationPoint::new(x, y), radius)
+/// };
+/// assert_eq!(near(1.0, 1.0, 0.5), [0]);
+/// assert_eq!(near(1.0, 1.0, 1.5), [0, 1]);
+/// assert_eq!(near(-1.0, 2.0, 1.0), [0]);
+/// assert!(near(5.0, -1.0, 1.0).is_empty());
+/// ```
+pub fn triangles_near<T, P, Q>(
+    points: &[P],
+    triangles: &[Triangle],
+    center: &Q,
+    radius: T,
+) -> Vec<usize>
+where
+    T: Float,
+    P: Point<T>,
+    Q: Point<T>,
+{
+    let (qx, qy) = (center.x(), center.y());
+    triangles
+        .iter()
+        .enumerate()
+        .filter(|&(_, t)| {
+            let (a, b, c) = (&points[t.0], &points[t.1], &points[t.2]);
+            contains(a, b, c, qx, qy)
+                || segment_distance(a, b, qx, qy) <= radius
+                || segment_distance(b, c, qx, qy) <= radius
+                || segment_distance(c, a, qx, qy) <= radius
+        })
+        .map(|(i, _)| i)
+        .collect()
+}
+
+/// Returns the smallest circle enclosing all the points, as its center and radius.
+///
+/// This is Welzl's algorithm, in its iterative form: the circle is exact, up to rounding errors,
+/// which are tolerated by considering points barely outside of a circle as enclosed by it. The
+/// points are visited in their input order instead of a random one, which keeps the result
+/// deterministic. This takes a linear time in most cases, but an adversarial order can make it
+/// cubic in the worst case: shuffling the points first avoids it. Returns `None` if there are no
+/// points, a zero radius for a single point, and the circle whose diameter joins them for two.
+///
+/// Example:
+///
+/// ```rust
+/// use rtriangulate::{bounding_circle, TriangulationPoint};
+///
+/// let points = [
+///     TriangulationPoint::new(0.0, 0.0),
+///     TriangulationPoint::new(1.0, 1.0),
+///     TriangulationPoint::new(4.0, 0.0),
+///     TriangulationPoint::new(2.0, -1.0)
+/// ];
+/// assert_eq!(bounding_circle(&points), Some((TriangulationPoint::new(2.0, 0.0), 2.0)));
+/// assert_eq!(bounding_circle::<f64, TriangulationPoint<f64>>(&[]), None);
+/// ```
+pub fn bounding_circle<T, P>(points: &[P]) -> Option<(TriangulationPoint<T>, T)>
+where
+    T: Float,
+    P: Point<T>,
+{
+    let point = |p: &P| TriangulationPoint::new(p.x(), p.y());
+    let half = T::from(0.5).unwrap();
+    let tolerance = T::one() + T::epsilon() * T::from(64).unwrap();
+    let encloses = |circle: &(TriangulationPoint<T>, T), p: &P| {
+        (p.x() - circle.0.x).hypot(p.y() - circle.0.y) <= circle.1 * tolerance
+    };
+    let diametral = |a: &P, b: &P| {
+        let center = TriangulationPoint::new((a.x() + b.x()) * half, (a.y() + b.y()) * half);
+        (center, (a.x() - center.x).hypot(a.y() - center.y))
+    };
+
+    let mut circle = (point(points.first()?), T::zero());
+    for i in 1..points.len() {
+        if encloses(&circle, &points[i]) {
+            continue;
+        }
+
+        // The point is on the boundary of the circle enclosing the previous ones and itself.
+        circle = (point(&points[i]), T::zero());
+        for j in 0..i {
+            if encloses(&circle, &points[j]) {
+                continue;
+            }
+
+            // Both points are on the boundary of that circle.
+            circle = diametral(&points[i], &points[j]);
+            for k in 0..j {
+                if encloses(&circle, &points[k]) {
+                    continue;
+                }
+
+                let (a, b, c) = (&points[i], &points[j], &points[k]);
+                circle = match circumcenter(&[point(a), point(b), point(c)], &Triangle(0, 1, 2)) {
+                    Some(center) => (center, (a.x() - center.x).hypot(a.y() - center.y)),
+                    // Collinear points are enclosed by the circle of the two farthest apart.
+                    None => {
+                        let candidates = [diametral(a, b), diametral(b, c), diametral(a, c)];
+                        candidates
+                            .iter()
+                            .fold(candidates[0], |largest, &candidate| {
+                                if candidate.1 > largest.1 {
+                                    candidate
+                                } else {
+                                    largest
+                                }
+                            })
+                    }
+                };
+            }
+        }
+    }
+    Some(circle)
+}
+
+/// Returns the distance from `(qx, qy)` to the closest point of the segment from `a` to `b`.
+fn segment_distance<T: Float, P: Point<T>>(a: &P, b: &P, qx: T, qy: T) -> T {
+    let (dx, dy) = (b.x() - a.x(), b.y() - a.y());
+    let length_sq = dx * dx + dy * dy;
+    let along = if length_sq > T::zero() {
+        ((qx - a.x()) * dx + (qy - a.y()) * dy) / length_sq
+    } else {
+        T::zero()
+    };
+    let along = along.max(T::zero()).min(T::one());
+    (qx - (a.x() + along * dx)).hypot(qy - (a.y() + along * dy))
+}
+
+/// Returns true if `(qx, qy)` is inside of the triangle `(a, b, c)`, or on its boundary.
+fn contains<T: Float, P: Point<T>>(a: &P, b: &P, c: &P, qx: T, qy: T) -> bool {
+    let side = |a: &P, b: &P| (b.x() - a.x()) * (qy - a.y()) - (b.y() - a.y()) * (qx - a.x());
+    let sides = [side(a, b), side(b, c), side(c, a)];
+    sides.iter().all(|&s| s >= T::zero()) || sides.iter().all(|&s| s <= T::zero())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{
+        boundary_edges, triangles_containing, triangulate, Edge, Triangle, TriangulationPoint,
+    };
+    use super::{
+        aspect_ratios, bisector_direction, bounding_circle, circumcenter, circumcircles,
+        circumradius, clip_triangle_convex, distance_to_hull, edge_lengths, edge_midpoint,
+        hull_with_normals, triangle_qualities, triangles_near,
+    };
+
+    #[test]
+    fn test_circumcircle() {
+        let points = [
+            TriangulationPoint::new(3.0, 1.0),
+            TriangulationPoint::new(5.0, 3.0),
+            TriangulationPoint::new(7.0, 1.0),
+            TriangulationPoint::new(8.0, 1.0),
+        ];
+
+        assert_eq!(
+            circumcenter(&points, &Triangle(0, 1, 2)),
+            Some(TriangulationPoint::new(5.0, 1.0))
+        );
+        assert_eq!(circumradius(&points, &Triangle(0, 1, 2)), Some(2.0));
+        assert_eq!(circumcenter(&points, &Triangle(0, 2, 3)), None);
+        assert_eq!(circumradius(&points, &Triangle(0, 2, 3)), None);
+
+        let triangles = [Triangle(0, 1, 2), Triangle(0, 2, 3), Triangle(1, 3, 2)];
+        let circles = circumcircles(&points, &triangles);
+        assert_eq!(circles.len(), triangles.len());
+        for (circle, t) in circles.iter().zip(&triangles) {
+            assert_eq!(circle.map(|c| c.0), circumcenter(&points, t));
+            assert_eq!(circle.map(|c| c.1), circumradius(&points, t));
+        }
+        assert_eq!(circles[1], None);
+    }
+
+    #[test]
+    fn test_bisector() {
+        let points = [
+            TriangulationPoint::new(3.0f64, 1.0),
+            TriangulationPoint::new(5.0, 3.0),
+            TriangulationPoint::new(7.0, 1.0),
+            TriangulationPoint::new(3.0, -2.0),
+        ];
+
+        // The circumcenter lies on the bisectors of all the edges of its triangle.
+        let center = circumcenter(&points, &Triangle(0, 1, 2)).unwrap();
+        for e in Triangle(0, 1, 2).edges().iter() {
+            let middle = edge_midpoint(&points, e);
+            let direction = bisector_direction(&points, e).unwrap();
+            let cross = direction.x * (center.y - middle.y) - direction.y * (center.x - middle.x);
+            assert!(cross.abs() < 1e-12);
+        }
+
+        assert_eq!(
+            edge_midpoint(&points, &Edge(0, 3)),
+            TriangulationPoint::new(3.0, -0.5)
+        );
+        assert_eq!(
+            bisector_direction(&points, &Edge(3, 0)),
+            Some(TriangulationPoint::new(-1.0, 0.0))
+        );
+        assert_eq!(
+            bisector_direction(&points, &Edge(0, 2)),
+            Some(TriangulationPoint::new(-0.0, 1.0))
+        );
+    }
+
+    #[test]
+    fn test_triangle_qualities() {
+        let points = [
+            TriangulationPoint::new(0.0, 0.0),
+            TriangulationPoint::new(0.5, 3.0f64.sqrt() / 2.0),
+            TriangulationPoint::new(1.0, 0.0),
+            TriangulationPoint::new(10.0, 0.1),
+            TriangulationPoint::new(20.0, 0.0),
+        ];
+        let tris = [Triangle(0, 1, 2), Triangle(2, 3, 4), Triangle(0, 2, 4)];
+        let qualities = triangle_qualities(&points, &tris);
+
+        assert_eq!(qualities.len(), 3);
+        assert!((qualities[0] - 1.0 / 3.0f64.sqrt()).abs() < 1e-12);
+        assert!(qualities[1] > 10.0);
+        assert!(qualities[2].is_infinite());
+    }
+
+    #[test]
+    fn test_triangle_qualities_triangulation() {
+        let points = [
+            TriangulationPoint::new(10.0, 10.0),
+            TriangulationPoint::new(15.0, 25.0),
+            TriangulationPoint::new(25.0, 15.0),
+            TriangulationPoint::new(30.0, 25.0),
+            TriangulationPoint::new(40.0, 15.0),
+        ];
+        let tris = triangulate(&points).unwrap();
+        let qualities = triangle_qualities(&points, &tris);
+
+        assert_eq!(qualities.len(), tris.len());
+        assert!(qualities.iter().all(|&q| q >= 1.0 / 3.0f64.sqrt() - 1e-12));
+    }
+
+    #[test]
+    fn test_aspect_ratios() {
+        let points = [
+            TriangulationPoint::new(0.0, 0.0),
+            TriangulationPoint::new(0.5, 3.0f64.sqrt() / 2.0),
+            TriangulationPoint::new(1.0, 0.0),
+            TriangulationPoint::new(10.0, 0.1),
+            TriangulationPoint::new(20.0, 0.0),
+        ];
+        let tris = [Triangle(0, 1, 2), Triangle(2, 3, 4), Triangle(0, 2, 4)];
+        let ratios = aspect_ratios(&points, &tris);
+
+        assert_eq!(ratios.len(), 3);
+        assert!((ratios[0] - 2.0).abs() < 1e-12);
+        assert!(ratios[1] > 100.0);
+        assert!(ratios[2].is_infinite());
+
+        // The ratio doesn't depend on the scale or the winding of the triangle.
+        let scaled: Vec<_> = points
+            .iter()
+            .map(|p| TriangulationPoint::new(p.x * 3.0, p.y * 3.0))
+            .collect();
+        let reversed = aspect_ratios(&scaled, &[Triangle(4, 3, 2)]);
+        assert!((reversed[0] - ratios[1]).abs() < 1e-9 * ratios[1]);
+    }
+
+    #[test]
+    fn test_edge_lengths() {
+        let points = [
+            TriangulationPoint::new(0.0, 0.0),
+            TriangulationPoint::new(0.0, 1.0),
+            TriangulationPoint::new(1.0, 0.0),
+            TriangulationPoint::new(1.0, 1.0),
+        ];
+        let tris = triangulate(&points).unwrap();
+        let lengths = edge_lengths(&points, &tris);
+        assert_eq!(lengths.len(), tris.len());
+        for (length, t) in lengths.iter().zip(&tris) {
+            for (&l, e) in length.iter().zip(&t.edges()) {
+                let diagonal = *e == Edge(0, 3) || *e == Edge(1, 2);
+                let expected = if diagonal { 2.0f64.sqrt() } else { 1.0 };
+                assert!((l - expected).abs() < 1e-12);
+            }
+        }
+        assert!(edge_lengths(&points, &[]).is_empty());
+    }
+
+    #[test]
+    fn test_clip_triangle_convex() {
+        let area = |polygon: &[TriangulationPoint<f64>]| {
+            (0..polygon.len()).fold(0.0, |area, k| {
+                let (a, b) = (&polygon[k], &polygon[(k + 1) % polygon.len()]);
+                area + (a.x * b.y - b.x * a.y) / 2.0
+            })
+        };
+        let hexagon: Vec<_> = (0..6)
+            .map(|k| {
+                let angle = k as f64 * std::f64::consts::PI / 3.0;
+                TriangulationPoint::new(angle.cos() * 2.0, angle.sin() * 2.0)
+            })
+            .collect();
+        let (a, b, c) = (
+            TriangulationPoint::new(0.0, -3.0),
+            TriangulationPoint::new(3.0, 3.0),
+            TriangulationPoint::new(-3.0, 3.0),
+        );
+
+        // The clipped polygon keeps the winding of the triangle, whatever that of the clip region.
+        let clipped = clip_triangle_convex(&a, &b, &c, &hexagon);
+        assert!(clipped.len() > 3 && area(&clipped) > 0.0 && area(&clipped) < area(&hexagon));
+        let reversed: Vec<_> = hexagon.iter().rev().cloned().collect();
+        assert_eq!(
+            clip_triangle_convex(&a, &b, &c, &reversed).len(),
+            clipped.len()
+        );
+        let flipped = clip_triangle_convex(&a, &c, &b, &hexagon);
+        assert!((area(&flipped) + area(&clipped)).abs() < 1e-9);
+
+        // A triangle within the clip region is unchanged, and one outside of it vanishes.
+        let inside = [
+            TriangulationPoint::new(0.0, 0.0),
+            TriangulationPoint::new(1.0, 0.0),
+            TriangulationPoint::new(0.0, 1.0),
+        ];
+        assert_eq!(
+            clip_triangle_convex(&inside[0], &inside[1], &inside[2], &hexagon),
+            inside
+        );
+        let far = TriangulationPoint::new(10.0, 10.0);
+        let outside = [
+            far,
+            TriangulationPoint::new(11.0, 10.0),
+            TriangulationPoint::new(10.0, 11.0),
+        ];
+        assert_eq!(
+            clip_triangle_convex(&outside[0], &outside[1], &outside[2], &hexagon),
+            []
+        );
+
+        // The hexagon is entirely within a large enough triangle.
+        let (a, b, c) = (
+            TriangulationPoint::new(-10.0, -10.0),
+            TriangulationPoint::new(10.0, -10.0),
+            TriangulationPoint::new(0.0, 10.0),
+        );
+        let clipped = clip_triangle_convex(&a, &b, &c, &hexagon);
+        assert!((area(&clipped) - area(&hexagon)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_distance_to_hull() {
+        let points = [
+            TriangulationPoint::new(10.0, 10.0),
+            TriangulationPoint::new(15.0, 25.0),
+            TriangulationPoint::new(25.0, 15.0),
+            TriangulationPoint::new(30.0, 25.0),
+            TriangulationPoint::new(40.0, 15.0),
+        ];
+        let tris = triangulate(&points).unwrap();
+        let distance = |x, y| distance_to_hull(&points, &tris, &TriangulationPoint::new(x, y));
+
+        // Around the top edge, from (15, 25) to (30, 25).
+        assert_eq!(distance(20.0, 23.0), -2.0);
+        assert_eq!(distance(20.0, 25.0), 0.0);
+        assert_eq!(distance(20.0, 28.0), 3.0);
+
+        // The closest edge is the bottom one, from (10, 10) to (40, 15).
+        assert!((distance(25.0, 15.0) + 75.0 / 925.0f64.sqrt()).abs() < 1e-12);
+
+        // The closest point of the boundary is a vertex.
+        assert_eq!(distance(44.0, 12.0), 5.0);
+        assert!(distance_to_hull(&points, &[], &points[0]).is_infinite());
+    }
+
+    #[test]
+    fn test_bounding_circle() {
+        let points: Vec<_> = (0..50)
+            .map(|i| {
+                let angle = i as f64 * 2.4;
+                let radius = (i % 7) as f64;
+                TriangulationPoint::new(3.0 + radius * angle.cos(), -1.0 + radius * angle.sin())
+            })
+            .collect();
+        let (center, radius) = bounding_circle(&points).unwrap();
+        assert!(points
+            .iter()
+            .all(|p| (p.x - center.x).hypot(p.y - center.y) <= radius + 1e-9));
+        assert!(radius <= 6.0 + 1e-9);
+
+        // At least two points lie on the circle, or it could be shrunk.
+        let on_circle = points
+            .iter()
+            .filter(|p| ((p.x - center.x).hypot(p.y - center.y) - radius).abs() < 1e-9)
+            .count();
+        assert!(on_circle >= 2);
+
+        let single = [TriangulationPoint::new(1.0, 2.0)];
+        assert_eq!(bounding_circle(&single), Some((single[0], 0.0)));
+        let collinear = [
+            TriangulationPoint::new(1.0, 1.0),
+            TriangulationPoint::new(5.0, 1.0),
+            TriangulationPoint::new(3.0, 1.0),
+            TriangulationPoint::new(-1.0, 1.0),
+        ];
+        assert_eq!(
+            bounding_circle(&collinear),
+            Some((TriangulationPoint::new(2.0, 1.0), 3.0))
+        );
+    }
+
+    #[test]
+    fn test_triangles_near() {
+        let points = [
+            TriangulationPoint::new(10.0, 10.0),
+            TriangulationPoint::new(15.0, 25.0),
+            TriangulationPoint::new(25.0, 15.0),
+            TriangulationPoint::new(30.0, 25.0),
+            TriangulationPoint::new(40.0, 15.0),
+        ];
+        let tris = triangulate(&points).unwrap();
+
+        // A disk of no radius on a vertex touches the triangles using it.
+        for (i, p) in points.iter().enumerate() {
+            assert_eq!(
+                triangles_near(&points, &tris, p, 0.0),
+                triangles_containing(&tris, i)
+            );
+        }
+
+        // The bounding box of the triangle (2, 3, 4) contains (38, 24), but the triangle doesn't.
+        let query = TriangulationPoint::new(38.0, 24.0);
+        assert!(triangles_near(&points, &tris, &query, 1.0).is_empty());
+        assert_eq!(triangles_near(&points, &tris, &query, 100.0), [0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn test_hull_normals() {
+        let points = [
+            TriangulationPoint::new(3.0f64, 1.0),
+            TriangulationPoint::new(5.0, 3.0),
+            TriangulationPoint::new(7.0, 1.0),
+        ];
+        let centroid = TriangulationPoint::new(5.0f64, 5.0 / 3.0);
+        for tris in &[[Triangle(0, 1, 2)], [Triangle(0, 2, 1)]] {
+            let hull = hull_with_normals(&points, tris);
+            assert_eq!(hull.len(), 3);
+            for (i, &(ref edge, normal)) in hull.iter().enumerate() {
+                assert_eq!(edge.1, hull[(i + 1) % 3].0 .0);
+                let (a, b) = (points[edge.0], points[edge.1]);
+                let (mx, my) = ((a.x + b.x) / 2.0, (a.y + b.y) / 2.0);
+                assert!((normal.x.hypot(normal.y) - 1.0).abs() < 1e-12);
+                assert!(normal.x * (mx - centroid.x) + normal.y * (my - centroid.y) > 0.0);
+                assert!((normal.x * (b.x - a.x) + normal.y * (b.y - a.y)).abs() < 1e-12);
+            }
+        }
+    }
+
+    #[test]
+    fn test_hull_normals_triangulation() {
+        let points = [
+            TriangulationPoint::new(10.0, 10.0),
+            TriangulationPoint::new(15.0, 25.0),
+            TriangulationPoint::new(25.0, 15.0),
+            TriangulationPoint::new(30.0, 25.0),
+            TriangulationPoint::new(40.0, 15.0),
+        ];
+        let tris = triangulate(&points).unwrap();
+        let hull = hull_with_normals(&points, &tris);
+        let edges: Vec<(usize, usize)> = hull.iter().map(|h| (h.0 .0, h.0 .1)).collect();
+        assert_eq!(edges, [(0, 4), (4, 3), (3, 1), (1, 0)]);
+        assert_eq!(hull.len(), boundary_edges(&tris).len());
+    }
+}
@@ -0,0 +1,254 @@
+// Copyright 2017-2018 Samuel Loretan <tynril@gmail.com> -- See LICENSE file
+
+//! Export of triangulations to the glTF 2.0 format.
+
+use std::io::{Result, Write};
+
+use num_traits::Float;
+
+use super::{Point, Triangle};
+
+/// Writes a triangulation, lifted in 3D by a height per point, as a glTF 2.0 asset.
+///
+/// The asset is a single JSON document, with the binary data embedded as a base64 data URI. It
+/// contains one mesh made of one primitive, with the vertex positions, the vertex normals, and
+/// the triangle indices. The `heights` are aligned with `points`.
+///
+/// glTF uses a right-handed, Y-up coordinate system: the point `(x, y)` with height `h` becomes
+/// the vertex `(x, h, y)`, and the clockwise triangles output by `triangulate` are front-facing
+/// when looked at from above. The normals are the area-weighted average of the normals of the
+/// triangles around every vertex.
+///
+/// Example:
+///
+/// ```rust
+/// use rtriangulate::{triangulate, write_gltf, TriangulationPoint};
+///
+/// let points = [
+///     TriangulationPoint::new(10.0, 50.0),
+///     TriangulationPoint::new(25.0, 40.0),
+///     TriangulationPoint::new(30.0, 40.0)
+/// ];
+/// let triangles = triangulate(&points).unwrap();
+///
+/// let mut gltf = Vec::new();
+/// write_gltf(&points, &[1.0, 2.0, 0.5], &triangles, &mut gltf).unwrap();
+/// assert!(String::from_utf8(gltf).unwrap().starts_with("{\"asset\":{\"version\":\"2.0\""));
+/// ```
+pub fn write_gltf<T, P, W>(
+    points: &[P],
+    heights: &[T],
+    triangles: &[Triangle],
+    w: &mut W,
+) -> Result<()>
+where
+    T: Float,
+    P: Point<T>,
+    W: Write,
+{
+    assert_eq!(
+        points.len(),
+        heights.len(),
+        "there must be one height per point"
+    );
+
+    let positions: Vec<[f32; 3]> = points
+        .iter()
+        .zip(heights)
+        .map(|(p, &h)| [to_f32(p.x()), to_f32(h), to_f32(p.y())])
+        .collect();
+    let normals = vertex_normals(&positions, triangles);
+
+    // The binary buffer holds the positions, then the normals, then the indices.
+    let mut buffer = Vec::with_capacity(positions.len() * 24 + triangles.len() * 12);
+    for v in positions.iter().chain(normals.iter()) {
+        for c in v {
+            buffer.extend_from_slice(&c.to_bits().to_le_bytes());
+        }
+    }
+    for t in triangles {
+        for &i in &[t.0, t.1, t.2] {
+            buffer.extend_from_slice(&(i as u32).to_le_bytes());
+        }
+    }
+    let vectors_length = positions.len() * 12;
+    let indices_length = triangles.len() * 12;
+
+    let mut min = [f32::INFINITY; 3];
+    let mut max = [f32::NEG_INFINITY; 3];
+    for v in &positions {
+        for c in 0..3 {
+            min[c] = min[c].min(v[c]);
+            max[c] = max[c].max(v[c]);
+        }
+    }
+
+    write!(
+        w,
+        "{{\"asset\":{{\"version\":\"2.0\",\"generator\":\"rtriangulate\"}},\
+         \"scene\":0,\"scenes\":[{{\"nodes\":[0]}}],\"nodes\":[{{\"mesh\":0}}],\
+         \"meshes\":[{{\"primitives\":[{{\"attributes\":{{\"POSITION\":0,\"NORMAL\":1}},\
+         \"indices\":2,\"mode\":4}}]}}],"
+    )?;
+    write!(
+        w,
+        "\"accessors\":[\
+         {{\"bufferView\":0,\"componentType\":5126,\"count\":{count},\"type\":\"VEC3\",\
+         \"min\":[{},{},{}],\"max\":[{},{},{}]}},\
+         {{\"bufferView\":1,\"componentType\":5126,\"count\":{count},\"type\":\"VEC3\"}},\
+         {{\"bufferView\":2,\"componentType\":5125,\"count\":{},\"type\":\"SCALAR\"}}],",
+        min[0],
+        min[1],
+        min[2],
+        max[0],
+        max[1],
+        max[2],
+        triangles.len() * 3,
+        count = positions.len()
+    )?;
+    write!(
+        w,
+        "\"bufferViews\":[\
+         {{\"buffer\":0,\"byteOffset\":0,\"byteLength\":{vectors},\"target\":34962}},\
+         {{\"buffer\":0,\"byteOffset\":{vectors},\"byteLength\":{vectors},\"target\":34962}},\
+         {{\"buffer\":0,\"byteOffset\":{},\"byteLength\":{},\"target\":34963}}],",
+        vectors_length * 2,
+        indices_length,
+        vectors = vectors_length
+    )?;
+    write!(
+        w,
+        "\"buffers\":[{{\"byteLength\":{},\"uri\":\"data:application/octet-stream;base64,",
+        buffer.len()
+    )?;
+    write_base64(&buffer, w)?;
+    write!(w, "\"}}]}}")
+}
+
+/// Converts a coordinate to the `f32` precision of the glTF buffers.
+#[inline(always)]
+fn to_f32<T: Float>(v: T) -> f32 {
+    v.to_f32().unwrap_or(f32::NAN)
+}
+
+/// Computes the unit normal of every vertex, as the normalized sum of the (area-weighted) normals
+/// of the triangles around it.
+fn vertex_normals(positions: &[[f32; 3]], triangles: &[Triangle]) -> Vec<[f32; 3]> {
+    let mut normals = vec![[0.0f32; 3]; positions.len()];
+    for t in triangles {
+        let (a, b, c) = (positions[t.0], positions[t.1], positions[t.2]);
+        let u = [b[0] - a[0], b[1] - a[1], b[2] - a[2]];
+        let v = [c[0] - a[0], c[1] - a[1], c[2] - a[2]];
+        let n = [
+            u[1] * v[2] - u[2] * v[1],
+            u[2] * v[0] - u[0] * v[2],
+            u[0] * v[1] - u[1] * v[0],
+        ];
+        for &i in &[t.0, t.1, t.2] {
+            for c in 0..3 {
+                normals[i][c] += n[c];
+            }
+        }
+    }
+
+    for n in &mut normals {
+        let length = (n[0] * n[0] + n[1] * n[1] + n[2] * n[2]).sqrt();
+        *n = if length > 0.0 {
+            [n[0] / length, n[1] / length, n[2] / length]
+        } else {
+            // Unused vertices still need a valid normal.
+            [0.0, 1.0, 0.0]
+        };
+    }
+    normals
+}
+
+/// Writes the standard base64 encoding (with padding) of some bytes.
+fn write_base64<W: Write>(bytes: &[u8], w: &mut W) -> Result<()> {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut encoded = Vec::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let b = [
+            chunk[0],
+            *chunk.get(1).unwrap_or(&0),
+            *chunk.get(2).unwrap_or(&0),
+        ];
+        let n = (u32::from(b[0]) << 16) | (u32::from(b[1]) << 8) | u32::from(b[2]);
+        encoded.push(ALPHABET[(n >> 18) as usize & 63]);
+        encoded.push(ALPHABET[(n >> 12) as usize & 63]);
+        encoded.push(if chunk.len() > 1 {
+            ALPHABET[(n >> 6) as usize & 63]
+        } else {
+            b'='
+        });
+        encoded.push(if chunk.len() > 2 {
+            ALPHABET[n as usize & 63]
+        } else {
+            b'='
+        });
+    }
+    w.write_all(&encoded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{triangulate, TriangulationPoint};
+    use super::{vertex_normals, write_base64, write_gltf};
+
+    #[test]
+    fn test_base64() {
+        let encode = |bytes: &[u8]| {
+            let mut encoded = Vec::new();
+            write_base64(bytes, &mut encoded).unwrap();
+            String::from_utf8(encoded).unwrap()
+        };
+        assert_eq!(encode(b""), "");
+        assert_eq!(encode(b"f"), "Zg==");
+        assert_eq!(encode(b"fo"), "Zm8=");
+        assert_eq!(encode(b"foo"), "Zm9v");
+        assert_eq!(encode(b"foobar"), "Zm9vYmFy");
+    }
+
+    #[test]
+    fn test_flat_normals() {
+        let points = [
+            TriangulationPoint::new(10.0, 10.0),
+            TriangulationPoint::new(15.0, 25.0),
+            TriangulationPoint::new(25.0, 15.0),
+            TriangulationPoint::new(30.0, 25.0),
+        ];
+        let tris = triangulate(&points).unwrap();
+        let positions: Vec<_> = points.iter().map(|p| [p.x, 2.0, p.y]).collect();
+
+        // A flat mesh has all its normals pointing up.
+        for n in vertex_normals(&positions, &tris) {
+            assert_eq!(n, [0.0, 1.0, 0.0]);
+        }
+    }
+
+    #[test]
+    fn test_write_gltf() {
+        let points = [
+            TriangulationPoint::new(10.0, 10.0),
+            TriangulationPoint::new(15.0, 25.0),
+            TriangulationPoint::new(25.0, 15.0),
+            TriangulationPoint::new(30.0, 25.0),
+        ];
+        let tris = triangulate(&points).unwrap();
+
+        let mut gltf = Vec::new();
+        write_gltf(&points, &[0.0, 1.0, 2.0, 3.0], &tris, &mut gltf).unwrap();
+        let gltf = String::from_utf8(gltf).unwrap();
+
+        // 4 positions and 4 normals of 12 bytes each, then 3 indices of 4 bytes per triangle.
+        let length = 4 * 12 * 2 + tris.len() * 12;
+        assert!(gltf.contains(&format!("\"byteLength\":{},\"uri\"", length)));
+        assert!(gltf.contains("\"min\":[10,0,10],\"max\":[30,3,25]"));
+        assert!(gltf.contains(&format!("\"count\":{},\"type\":\"SCALAR\"", tris.len() * 3)));
+        assert!(gltf.ends_with("\"}]}"));
+
+        let encoded = &gltf[gltf.find("base64,").unwrap() + 7..gltf.len() - 4];
+        assert_eq!(encoded.len(), length.div_ceil(3) * 4);
+    }
+}
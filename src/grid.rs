@@ -0,0 +1,76 @@
+// Copyright 2017-2018 Samuel Loretan <tynril@gmail.com> -- See LICENSE file
+
+//! Direct triangulation of points laid out on a regular grid.
+
+use super::Triangle;
+
+/// Returns a Delaunay triangulation of the points of a regular grid, without computing anything.
+///
+/// The grid has `cols` points per row and `rows` rows, indexed in row-major order: the point at
+/// column `x` of row `y` has the index `y * cols + x`. The points are assumed to be evenly spaced,
+/// along both axes (such as with unit spacing, the point `(x, y)` at that index), and every cell
+/// is split into two triangles along the same diagonal. As the four corners of a cell are
+/// cocircular, either diagonal makes for a Delaunay triangulation, and this one always picks the
+/// same for a deterministic output. Like with `triangulate`, the triangles are clockwise.
+///
+/// Grids with fewer than two rows or columns have no triangle.
+///
+/// Example:
+///
+/// ```rust
+/// use rtriangulate::{triangulate_grid, Triangle};
+///
+/// // 3 4 5
+/// // 0 1 2
+/// assert_eq!(
+///     triangulate_grid(3, 2),
+///     [Triangle(0, 3, 1), Triangle(1, 3, 4), Triangle(1, 4, 2), Triangle(2, 4, 5)]
+/// );
+/// ```
+pub fn triangulate_grid(cols: usize, rows: usize) -> Vec<Triangle> {
+    if cols < 2 || rows < 2 {
+        return Vec::new();
+    }
+
+    let mut triangles = Vec::with_capacity(2 * (cols - 1) * (rows - 1));
+    for y in 0..rows - 1 {
+        for x in 0..cols - 1 {
+            let i = y * cols + x;
+            triangles.push(Triangle(i, i + cols, i + 1));
+            triangles.push(Triangle(i + 1, i + cols, i + cols + 1));
+        }
+    }
+    triangles
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::validate::covers_hull;
+    use super::super::{is_delaunay, TriangulationPoint};
+    use super::triangulate_grid;
+
+    #[test]
+    fn test_grid() {
+        let (cols, rows) = (7, 4);
+        let points: Vec<TriangulationPoint<f64>> = (0..rows)
+            .flat_map(|y| (0..cols).map(move |x| TriangulationPoint::new(x as f64, y as f64)))
+            .collect();
+        let tris = triangulate_grid(cols, rows);
+
+        assert_eq!(tris.len(), 2 * (cols - 1) * (rows - 1));
+        assert!(is_delaunay(&points, &tris));
+        assert!(covers_hull(&points, &tris));
+        for t in &tris {
+            let (a, b, c) = (points[t.0], points[t.1], points[t.2]);
+            assert_eq!((b.x - a.x) * (c.y - a.y) - (b.y - a.y) * (c.x - a.x), -1.0);
+        }
+    }
+
+    #[test]
+    fn test_grid_too_small() {
+        assert!(triangulate_grid(0, 0).is_empty());
+        assert!(triangulate_grid(1, 10).is_empty());
+        assert!(triangulate_grid(10, 1).is_empty());
+        assert_eq!(triangulate_grid(2, 2).len(), 2);
+    }
+}
@@ -0,0 +1,231 @@
+// Copyright 2017-2018 Samuel Loretan <tynril@gmail.com> -- See LICENSE file
+
+//! A flat, index-based half-edge representation of a triangulation.
+
+use super::{Point, Triangle};
+
+/// A triangulation as half-edges, stored as flat arrays of `u32` indices.
+///
+/// Every triangle is made of three half-edges, one per edge, going around it in the same order as
+/// its vertices: the half-edges `3 * f`, `3 * f + 1` and `3 * f + 2` belong to the triangle `f`,
+/// and start from its first, second and third vertex. An interior edge is made of two half-edges
+/// going in opposite directions, one in each triangle, which are each other's `twin`. The arrays
+/// are indexed by half-edge, except `vertex_edge` which is indexed by point.
+///
+/// This is meant for meshes too large for the `Vec<Vec<usize>>` adjacencies, such as those of
+/// `vertex_triangles`: traversals only go through a few flat arrays, without any per-vertex
+/// allocation, and with indices half the size of `usize` on 64-bit targets. The mesh takes 16 bytes
+/// per half-edge, so 48 bytes per triangle, plus 4 bytes per point; building it also needs 16
+/// bytes per half-edge of temporary storage. A mesh of 10 million triangles thus takes about
+/// 480 MB, peaking at about 960 MB while built.
+///
+/// Example:
+///
+/// ```rust
+/// use rtriangulate::{to_half_edge_flat, HalfEdgeMesh, Triangle, TriangulationPoint};
+///
+/// let points = [
+///     TriangulationPoint::new(0.0, 0.0),
+///     TriangulationPoint::new(0.0, 2.0),
+///     TriangulationPoint::new(2.0, 0.0),
+///     TriangulationPoint::new(2.0, 2.0)
+/// ];
+/// let mesh = to_half_edge_flat(&points, &[Triangle(0, 1, 2), Triangle(2, 1, 3)]);
+/// assert_eq!(mesh.vertex, [0, 1, 2, 2, 1, 3]);
+/// assert_eq!(mesh.next, [1, 2, 0, 4, 5, 3]);
+///
+/// // The edge going from 1 to 2 in the first triangle goes from 2 to 1 in the second one.
+/// assert_eq!(mesh.twin[1], 3);
+/// assert_eq!(mesh.twin[0], HalfEdgeMesh::NONE);
+/// assert_eq!(mesh.face[3], 1);
+/// ```
+#[derive(Clone, PartialEq, Eq, Debug, Default)]
+pub struct HalfEdgeMesh {
+    /// The next half-edge around the same triangle.
+    pub next: Vec<u32>,
+    /// The half-edge going the other way along the same edge, in the neighbouring triangle, or
+    /// `HalfEdgeMesh::NONE` for a boundary edge. The edges shared by more than two triangles don't
+    /// have twins either.
+    pub twin: Vec<u32>,
+    /// The point the half-edge starts from.
+    pub vertex: Vec<u32>,
+    /// The triangle the half-edge belongs to.
+    pub face: Vec<u32>,
+    /// A half-edge starting from every point, or `HalfEdgeMesh::NONE` for the points used by no
+    /// triangle. For a point of the boundary, it is one with no twin, so that `outgoing` goes all
+    /// around the point.
+    pub vertex_edge: Vec<u32>,
+}
+
+impl HalfEdgeMesh {
+    /// The index standing for a missing half-edge.
+    pub const NONE: u32 = u32::MAX;
+
+    /// Returns the number of half-edges.
+    pub fn len(&self) -> usize {
+        self.next.len()
+    }
+
+    /// Returns true if there is no half-edge.
+    pub fn is_empty(&self) -> bool {
+        self.next.is_empty()
+    }
+
+    /// Returns the previous half-edge around the same triangle.
+    pub fn prev(&self, half_edge: u32) -> u32 {
+        self.next[self.next[half_edge as usize] as usize]
+    }
+
+    /// Returns the point the half-edge goes to.
+    pub fn destination(&self, half_edge: u32) -> u32 {
+        self.vertex[self.next[half_edge as usize] as usize]
+    }
+
+    /// Iterates over the half-edges starting from a point, turning around it from one triangle to
+    /// the next. This yields nothing for a point used by no triangle.
+    ///
+    /// For an interior point, this goes all around it. For a point of the boundary, with the
+    /// triangles of the mesh on a single side of it, this goes from one boundary edge to the other.
+    pub fn outgoing<'a>(&'a self, vertex: u32) -> impl Iterator<Item = u32> + 'a {
+        let start = self.vertex_edge[vertex as usize];
+        let mut current = start;
+        std::iter::from_fn(move || {
+            if current == Self::NONE {
+                return None;
+            }
+            let half_edge = current;
+            current = self.twin[self.prev(half_edge) as usize];
+            if current == start {
+                current = Self::NONE;
+            }
+            Some(half_edge)
+        })
+    }
+}
+
+/// Builds the half-edge representation of a triangulation.
+///
+/// The `triangles` must index into `points`, as returned by `triangulate`, and be consistently
+/// oriented for the twins to go in opposite directions. The twins are found by sorting the
+/// half-edges by the points they join, rather than through a map of the edges, which keeps the
+/// construction fast and compact for large meshes. See `HalfEdgeMesh` about the memory it takes.
+///
+/// This panics if there are too many points or triangles to be indexed by `u32`.
+pub fn to_half_edge_flat<T, P>(points: &[P], triangles: &[Triangle]) -> HalfEdgeMesh
+where
+    P: Point<T>,
+{
+    assert!(
+        points.len() < HalfEdgeMesh::NONE as usize
+            && triangles.len() * 3 < HalfEdgeMesh::NONE as usize,
+        "too many points or triangles for u32 indices"
+    );
+
+    let count = triangles.len() * 3;
+    let mut mesh = HalfEdgeMesh {
+        next: Vec::with_capacity(count),
+        twin: vec![HalfEdgeMesh::NONE; count],
+        vertex: Vec::with_capacity(count),
+        face: Vec::with_capacity(count),
+        vertex_edge: vec![HalfEdgeMesh::NONE; points.len()],
+    };
+    for (f, t) in triangles.iter().enumerate() {
+        let first = (f * 3) as u32;
+        mesh.next.extend_from_slice(&[first + 1, first + 2, first]);
+        mesh.vertex
+            .extend_from_slice(&[t.0 as u32, t.1 as u32, t.2 as u32]);
+        mesh.face.extend_from_slice(&[f as u32; 3]);
+    }
+
+    // Sort the half-edges by their undirected edge, so that the two halves of an edge end up next
+    // to each other.
+    let mut keys: Vec<(u64, u32)> = (0..count as u32)
+        .map(|h| {
+            let (a, b) = (mesh.vertex[h as usize], mesh.destination(h));
+            let (low, high) = if a < b { (a, b) } else { (b, a) };
+            ((u64::from(low) << 32) | u64::from(high), h)
+        })
+        .collect();
+    keys.sort_unstable();
+    let mut start = 0;
+    while start < keys.len() {
+        let mut end = start + 1;
+        while end < keys.len() && keys[end].0 == keys[start].0 {
+            end += 1;
+        }
+        if end - start == 2 {
+            let (a, b) = (keys[start].1, keys[start + 1].1);
+            mesh.twin[a as usize] = b;
+            mesh.twin[b as usize] = a;
+        }
+        start = end;
+    }
+
+    // Prefer the half-edges without a twin, which start the fans of the boundary points.
+    for h in 0..count as u32 {
+        let v = mesh.vertex[h as usize] as usize;
+        if mesh.vertex_edge[v] == HalfEdgeMesh::NONE || mesh.twin[h as usize] == HalfEdgeMesh::NONE
+        {
+            mesh.vertex_edge[v] = h;
+        }
+    }
+
+    mesh
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{boundary_edges, triangulate, vertex_triangles, Edge, TriangulationPoint};
+    use super::{to_half_edge_flat, HalfEdgeMesh};
+
+    #[test]
+    fn test_half_edge_flat() {
+        let points: Vec<_> = (0..25)
+            .map(|i| {
+                let (x, y) = ((i / 5) as f64, (i % 5) as f64);
+                TriangulationPoint::new(x * 10.0 + y * 1.5, y * 10.0 - x * 0.5)
+            })
+            .collect();
+        let tris = triangulate(&points).unwrap();
+        let mesh = to_half_edge_flat(&points, &tris);
+        assert_eq!(mesh.len(), tris.len() * 3);
+
+        for h in 0..mesh.len() as u32 {
+            let i = h as usize;
+            assert_eq!(mesh.next[mesh.next[mesh.next[i] as usize] as usize], h);
+            assert_eq!(mesh.face[i], h / 3);
+            let twin = mesh.twin[i];
+            if twin != HalfEdgeMesh::NONE {
+                assert_eq!(mesh.twin[twin as usize], h);
+                assert_eq!(mesh.vertex[twin as usize], mesh.destination(h));
+                assert_eq!(mesh.destination(twin), mesh.vertex[i]);
+            }
+        }
+
+        // The half-edges without twins are the boundary edges.
+        let mut boundary: Vec<_> = (0..mesh.len() as u32)
+            .filter(|&h| mesh.twin[h as usize] == HalfEdgeMesh::NONE)
+            .map(|h| {
+                Edge(
+                    mesh.vertex[h as usize] as usize,
+                    mesh.destination(h) as usize,
+                )
+            })
+            .collect();
+        let mut expected: Vec<_> = boundary_edges(&tris).into_iter().collect();
+        boundary.sort();
+        expected.sort();
+        assert_eq!(boundary, expected);
+
+        // Turning around a point goes through all of its triangles.
+        for (v, incident) in vertex_triangles(&tris, points.len()).iter().enumerate() {
+            let mut faces: Vec<_> = mesh
+                .outgoing(v as u32)
+                .inspect(|&h| assert_eq!(mesh.vertex[h as usize], v as u32))
+                .map(|h| mesh.face[h as usize] as usize)
+                .collect();
+            faces.sort();
+            assert_eq!(&faces, incident);
+        }
+    }
+}
@@ -0,0 +1,161 @@
+// Copyright 2017-2018 Samuel Loretan <tynril@gmail.com> -- See LICENSE file
+
+//! Computation of the convex hull of a set of points.
+
+use super::{Coordinate, Point, Result, TriangulateError};
+
+/// Computes the convex hull of a set of points, without triangulating them.
+///
+/// This returns the same vertices as the boundary of the output of `triangulate`, but only the
+/// hull is computed, with Andrew's monotone chain algorithm, which takes a linear time. The
+/// points must be sorted as `triangulate` requires.
+///
+/// The indices of the hull vertices are returned in clockwise order, like the vertices of the
+/// triangles of `triangulate`, starting from the first point. Points lying on the hull edges,
+/// between two vertices, are not included. As with `triangulate`, fewer than three points make
+/// `TriangulateError::NotEnoughPoints` be returned, and points which are all collinear or
+/// coincident `TriangulateError::Degenerate`.
+///
+/// Example:
+///
+/// ```rust
+/// use rtriangulate::{convex_hull_only, TriangulationPoint};
+///
+/// let points = [
+///     TriangulationPoint::new(10.0, 10.0),
+///     TriangulationPoint::new(15.0, 25.0),
+///     TriangulationPoint::new(25.0, 15.0),
+///     TriangulationPoint::new(30.0, 25.0),
+///     TriangulationPoint::new(40.0, 15.0)
+/// ];
+/// assert_eq!(convex_hull_only(&points).unwrap(), [0, 1, 3, 4]);
+/// ```
+pub fn convex_hull_only<T, P>(points: &[P]) -> Result<Vec<usize>>
+where
+    T: Coordinate,
+    P: Point<T>,
+{
+    if points.len() < 3 {
+        return Err(TriangulateError::NotEnoughPoints);
+    }
+    let hull = monotone_chain(points);
+    if hull.len() < 3 {
+        return Err(TriangulateError::Degenerate);
+    }
+    Ok(hull)
+}
+
+/// Computes the convex hull of points sorted as `triangulate` requires, with Andrew's monotone
+/// chain algorithm.
+///
+/// Returns the indices of the hull vertices in clockwise order, starting from the first point.
+/// Points lying on the hull edges, between two vertices, are not included. If all the points are
+/// collinear, only the two extremities are returned.
+pub(crate) fn monotone_chain<T, P>(points: &[P]) -> Vec<usize>
+where
+    T: Coordinate,
+    P: Point<T>,
+{
+    if points.len() < 2 {
+        return (0..points.len()).collect();
+    }
+
+    // Cross product of (a -> b) and (a -> c), negative when turning clockwise.
+    let cross = |a: usize, b: usize, c: usize| {
+        let (a, b, c) = (&points[a], &points[b], &points[c]);
+        (b.x() - a.x()) * (c.y() - a.y()) - (b.y() - a.y()) * (c.x() - a.x())
+    };
+
+    // The upper chain from left to right, then the lower one from right to left, both only
+    // turning clockwise.
+    let mut hull: Vec<usize> = Vec::with_capacity(points.len() + 1);
+    for i in 0..points.len() {
+        while hull.len() >= 2 && cross(hull[hull.len() - 2], hull[hull.len() - 1], i) >= T::zero() {
+            hull.pop();
+        }
+        hull.push(i);
+    }
+    let lower_start = hull.len() + 1;
+    for i in (0..points.len() - 1).rev() {
+        while hull.len() >= lower_start
+            && cross(hull[hull.len() - 2], hull[hull.len() - 1], i) >= T::zero()
+        {
+            hull.pop();
+        }
+        hull.push(i);
+    }
+
+    // The lower chain ends where the upper one started.
+    hull.pop();
+    hull
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{boundary_edges, triangulate, Edge, TriangulateError, TriangulationPoint};
+    use super::{convex_hull_only, monotone_chain};
+
+    #[test]
+    fn test_monotone_chain() {
+        let points = [
+            TriangulationPoint::new(0.0, 0.0),
+            TriangulationPoint::new(0.0, 2.0),
+            TriangulationPoint::new(1.0, 1.0),
+            TriangulationPoint::new(1.0, 2.0),
+            TriangulationPoint::new(2.0, -1.0),
+            TriangulationPoint::new(3.0, 1.0),
+        ];
+        assert_eq!(monotone_chain(&points), [0, 1, 3, 5, 4]);
+    }
+
+    #[test]
+    fn test_monotone_chain_collinear() {
+        let points = [
+            TriangulationPoint::new(0.0, 0.0),
+            TriangulationPoint::new(1.0, 1.0),
+            TriangulationPoint::new(2.0, 2.0),
+        ];
+        assert_eq!(monotone_chain(&points), [0, 2]);
+        assert_eq!(monotone_chain(&points[..1]), [0]);
+        assert_eq!(monotone_chain::<f64, TriangulationPoint<f64>>(&[]), []);
+    }
+
+    #[test]
+    fn test_convex_hull_only() {
+        let points = [
+            TriangulationPoint::new(0.0, 0.0),
+            TriangulationPoint::new(0.0, 2.0),
+            TriangulationPoint::new(1.0, 1.0),
+            TriangulationPoint::new(1.0, 2.0),
+            TriangulationPoint::new(2.0, -1.0),
+            TriangulationPoint::new(3.0, 1.0),
+        ];
+        let hull = convex_hull_only(&points).unwrap();
+        assert_eq!(hull, monotone_chain(&points));
+
+        // The hull edges are the boundary of the triangulation.
+        let mut boundary: Vec<_> = boundary_edges(&triangulate(&points).unwrap())
+            .into_iter()
+            .collect();
+        boundary.sort();
+        let mut edges: Vec<_> = (0..hull.len())
+            .map(|i| Edge(hull[i], hull[(i + 1) % hull.len()]))
+            .collect();
+        edges.sort();
+        assert_eq!(edges, boundary);
+
+        match convex_hull_only(&points[..2]) {
+            Err(TriangulateError::NotEnoughPoints) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        let collinear = [
+            TriangulationPoint::new(0.0, 0.0),
+            TriangulationPoint::new(1.0, 1.0),
+            TriangulationPoint::new(2.0, 2.0),
+        ];
+        match convex_hull_only(&collinear) {
+            Err(TriangulateError::Degenerate) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+}
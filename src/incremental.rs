@@ -0,0 +1,403 @@
+// Copyright 2017-2018 Samuel Loretan <tynril@gmail.com> -- See LICENSE file
+
+//! Incremental construction of a triangulation, one point at a time.
+
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+
+use super::xorshift::XorShift;
+use super::{
+    in_circumcircle, remove_shared_edges, sort_points, Coordinate, Edge, Point, Result, Triangle,
+    TriangulateError, TriangulationPoint,
+};
+
+/// The index of the first supertriangle vertex, beyond any index of an inserted point.
+const SUPER_VERTEX: usize = usize::MAX - 2;
+
+/// The triangles created and deleted by the insertion of a point in a `Triangulation`.
+#[derive(Clone, PartialEq, Debug, Default)]
+pub struct Diff {
+    /// The triangles which were deleted, as their circumcircle contained the new point.
+    pub removed: Vec<Triangle>,
+    /// The triangles which were created, all using the new point.
+    pub added: Vec<Triangle>,
+}
+
+/// A Delaunay triangulation built incrementally, by inserting one point after the other.
+///
+/// The bounds of the points to insert must be known upfront, as they are used to compute the
+/// supertriangle which encompasses them. Unlike `triangulate`, the points don't have to be sorted.
+/// Every insertion reports the triangles it changed, which makes it possible to keep a copy of the
+/// mesh up to date without sending it whole.
+///
+/// Example:
+///
+/// ```rust
+/// use rtriangulate::{Triangle, Triangulation, TriangulationPoint};
+///
+/// let mut triangulation = Triangulation::new(
+///     TriangulationPoint::new(0.0, 0.0),
+///     TriangulationPoint::new(10.0, 10.0)
+/// ).unwrap();
+/// triangulation.insert(&TriangulationPoint::new(0.0, 0.0));
+/// triangulation.insert(&TriangulationPoint::new(10.0, 0.0));
+/// let diff = triangulation.insert(&TriangulationPoint::new(5.0, 10.0));
+/// assert_eq!(diff.removed, []);
+/// assert_eq!(diff.added, [Triangle(1, 0, 2)]);
+/// assert_eq!(triangulation.triangles(), [Triangle(1, 0, 2)]);
+/// ```
+#[derive(Clone, Debug)]
+pub struct Triangulation<T> {
+    points: Vec<TriangulationPoint<T>>,
+    supertriangle: [TriangulationPoint<T>; 3],
+    triangles: Vec<Triangle>,
+    edges: Vec<Edge>,
+    to_remove: Vec<bool>,
+}
+
+impl<T> Triangulation<T>
+where
+    T: Coordinate,
+{
+    /// Makes an empty triangulation, for points within the given bounds.
+    ///
+    /// The supertriangle is computed the same way as `triangulate` does, so that inserting the
+    /// points of a set bounded by `min` and `max` results in the same mesh. Returns
+    /// `TriangulateError::Degenerate` if the bounds have no extent at all.
+    pub fn new(min: TriangulationPoint<T>, max: TriangulationPoint<T>) -> Result<Self> {
+        Ok(Triangulation {
+            points: Vec::new(),
+            supertriangle: bounds_supertriangle(min, max)?,
+            triangles: vec![Triangle(SUPER_VERTEX, SUPER_VERTEX + 1, SUPER_VERTEX + 2)],
+            edges: Vec::with_capacity(18),
+            to_remove: Vec::with_capacity(18),
+        })
+    }
+
+    /// Inserts a point in the triangulation, and returns the triangles this changed.
+    ///
+    /// The point gets the next index, which is the number of points inserted before it. It must
+    /// lie within the bounds given to `new`. The returned diff only lists triangles between
+    /// inserted points, as returned by `triangles`: applying it to the previous triangles gives
+    /// the current ones.
+    pub fn insert<P>(&mut self, point: &P) -> Diff
+    where
+        P: Point<T>,
+    {
+        let i = self.points.len();
+        self.points
+            .push(TriangulationPoint::new(point.x(), point.y()));
+
+        let mut diff = Diff::default();
+        {
+            let points = &self.points;
+            let supertriangle = &self.supertriangle;
+            let get = |index: usize| -> &TriangulationPoint<T> {
+                if index >= SUPER_VERTEX {
+                    &supertriangle[index - SUPER_VERTEX]
+                } else {
+                    &points[index]
+                }
+            };
+
+            let edges = &mut self.edges;
+            let removed = &mut diff.removed;
+            self.triangles.retain(|t| {
+                if in_circumcircle(get(i), get(t.0), get(t.1), get(t.2), false) {
+                    edges.extend_from_slice(&t.edges());
+                    if is_inner(t) {
+                        removed.push(*t);
+                    }
+                    false
+                } else {
+                    true
+                }
+            });
+        }
+
+        remove_shared_edges(&mut self.edges, &mut self.to_remove);
+        for e in &self.edges {
+            let t = Triangle(e.0, e.1, i);
+            self.triangles.push(t);
+            if is_inner(&t) {
+                diff.added.push(t);
+            }
+        }
+        self.edges.clear();
+
+        diff
+    }
+
+    /// The points inserted so far, in their insertion order.
+    pub fn points(&self) -> &[TriangulationPoint<T>] {
+        &self.points
+    }
+
+    /// The triangles between the points inserted so far.
+    pub fn triangles(&self) -> Vec<Triangle> {
+        self.triangles
+            .iter()
+            .filter(|t| is_inner(t))
+            .cloned()
+            .collect()
+    }
+}
+
+/// Computes the supertriangle encompassing the given bounds, the same way as `triangulate` does,
+/// clockwise. Returns `TriangulateError::Degenerate` if the bounds have no extent at all.
+pub(crate) fn bounds_supertriangle<T>(
+    min: TriangulationPoint<T>,
+    max: TriangulationPoint<T>,
+) -> Result<[TriangulationPoint<T>; 3]>
+where
+    T: Coordinate,
+{
+    let half = T::from_f64(0.5).ok_or(TriangulateError::UnsupportedCoordinate)?;
+    let two = T::from_f64(2.0).ok_or(TriangulateError::UnsupportedCoordinate)?;
+
+    let delta_max = (max.x - min.x).max(max.y - min.y);
+    if delta_max <= T::zero() || !delta_max.is_finite() {
+        return Err(TriangulateError::Degenerate);
+    }
+    let mid_x = (max.x + min.x) * half;
+    let mid_y = (max.y + min.y) * half;
+
+    Ok([
+        TriangulationPoint::new(mid_x - two * delta_max, mid_y - delta_max),
+        TriangulationPoint::new(mid_x, mid_y + two * delta_max),
+        TriangulationPoint::new(mid_x + two * delta_max, mid_y - delta_max),
+    ])
+}
+
+/// Triangulates a set of points progressively, yielding ever more detailed meshes.
+///
+/// The points are inserted in a pseudo-random order, which is the same from one call to the next,
+/// into a `Triangulation` bounded by them. The iterator yields `stages` meshes: the last one
+/// triangulates all the points, and every one before it about a quarter of the points of the next
+/// one, with at least three points. As the order is random, every stage is a subsample spread over
+/// the whole set, and each mesh is the Delaunay triangulation of the points inserted so far, indexing
+/// into `points`. The meshes are computed lazily, the next stage continuing from the previous one.
+///
+/// This is meant for showing a coarse mesh as soon as possible, then refining it, such as when
+/// streaming a terrain. Duplicate points are only inserted once. Unlike `triangulate`, the points
+/// don't have to be sorted. This returns `TriangulateError::NotEnoughPoints` for less than three
+/// points, and `TriangulateError::Degenerate` if they are all at the same position.
+///
+/// Example:
+///
+/// ```rust
+/// use rtriangulate::{triangulate_progressive, TriangulationPoint};
+///
+/// let points: Vec<_> = (0..64)
+///     .map(|i| TriangulationPoint::new((i % 8) as f64 + 0.1 * (i / 8) as f64, (i / 8) as f64))
+///     .collect();
+/// let meshes: Vec<_> = triangulate_progressive(&points, 3).unwrap().collect();
+/// assert_eq!(meshes.len(), 3);
+/// assert!(meshes[0].len() < meshes[1].len() && meshes[1].len() < meshes[2].len());
+/// ```
+pub fn triangulate_progressive<T, P>(
+    points: &[P],
+    stages: usize,
+) -> Result<impl Iterator<Item = Vec<Triangle>>>
+where
+    T: Coordinate,
+    P: Point<T>,
+{
+    if points.len() < 3 {
+        return Err(TriangulateError::NotEnoughPoints);
+    }
+
+    // Shuffle the points, with a fixed seed so that the stages are reproducible.
+    let mut order: Vec<usize> = (0..points.len()).collect();
+    XorShift::new(0).shuffle(&mut order);
+
+    // Only keep the first of identical points, in the shuffled order.
+    let mut by_position: Vec<usize> = (0..order.len()).collect();
+    by_position.sort_by(
+        |&a, &b| match sort_points(&points[order[a]], &points[order[b]]) {
+            Ordering::Equal => a.cmp(&b),
+            other => other,
+        },
+    );
+    let mut duplicate = vec![false; order.len()];
+    for w in by_position.windows(2) {
+        duplicate[w[1]] =
+            sort_points(&points[order[w[0]]], &points[order[w[1]]]) == Ordering::Equal;
+    }
+    let order: Vec<usize> = order
+        .into_iter()
+        .enumerate()
+        .filter(|&(k, _)| !duplicate[k])
+        .map(|(_, i)| i)
+        .collect();
+
+    let first = TriangulationPoint::new(points[0].x(), points[0].y());
+    let (min, max) = points.iter().fold((first, first), |(min, max), p| {
+        (
+            TriangulationPoint::new(min.x.min(p.x()), min.y.min(p.y())),
+            TriangulationPoint::new(max.x.max(p.x()), max.y.max(p.y())),
+        )
+    });
+    let mut triangulation = Triangulation::new(min, max)?;
+    let shuffled: Vec<TriangulationPoint<T>> = order
+        .iter()
+        .map(|&i| TriangulationPoint::new(points[i].x(), points[i].y()))
+        .collect();
+
+    let mut stage = 0;
+    Ok(std::iter::from_fn(move || {
+        if stage >= stages {
+            return None;
+        }
+        stage += 1;
+        let coarseness = u32::try_from(stages - stage)
+            .unwrap_or(u32::MAX)
+            .saturating_mul(2);
+        let count = shuffled
+            .len()
+            .checked_shr(coarseness)
+            .unwrap_or(0)
+            .max(3)
+            .min(shuffled.len());
+        for p in &shuffled[triangulation.points().len().min(count)..count] {
+            triangulation.insert(p);
+        }
+        Some(
+            triangulation
+                .triangles()
+                .into_iter()
+                .map(|t| Triangle(order[t.0], order[t.1], order[t.2]))
+                .collect(),
+        )
+    }))
+}
+
+/// Returns true if the triangle uses no supertriangle vertex.
+#[inline(always)]
+fn is_inner(t: &Triangle) -> bool {
+    t.0 < SUPER_VERTEX && t.1 < SUPER_VERTEX && t.2 < SUPER_VERTEX
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::xorshift::XorShift;
+    use super::super::{is_delaunay, sort_points, triangulate, Triangle, TriangulationPoint};
+    use super::{triangulate_progressive, Triangulation};
+
+    fn points() -> Vec<TriangulationPoint<f64>> {
+        let mut rng = XorShift::new(0);
+        let mut next = || (rng.next_u64() % 1000) as f64;
+        (0..40)
+            .map(|_| TriangulationPoint::new(next(), next()))
+            .collect()
+    }
+
+    #[test]
+    fn test_insert_matches_triangulate() {
+        let mut points = points();
+        points.sort_by(|a, b| {
+            a.x.partial_cmp(&b.x)
+                .unwrap()
+                .then(a.y.partial_cmp(&b.y).unwrap())
+        });
+        points.dedup();
+        let bounds = points.iter().fold((points[0], points[0]), |(min, max), p| {
+            (
+                TriangulationPoint::new(min.x.min(p.x), min.y.min(p.y)),
+                TriangulationPoint::new(max.x.max(p.x), max.y.max(p.y)),
+            )
+        });
+
+        let mut triangulation = Triangulation::new(bounds.0, bounds.1).unwrap();
+        for p in &points {
+            triangulation.insert(p);
+        }
+        assert_eq!(triangulation.points(), &points[..]);
+        assert_eq!(triangulation.triangles(), triangulate(&points).unwrap());
+    }
+
+    #[test]
+    fn test_diffs() {
+        let mut triangulation = Triangulation::new(
+            TriangulationPoint::new(0.0, 0.0),
+            TriangulationPoint::new(1000.0, 1000.0),
+        )
+        .unwrap();
+
+        let mut mirror: Vec<Triangle> = Vec::new();
+        for p in &points() {
+            let diff = triangulation.insert(p);
+            for t in &diff.removed {
+                let position = mirror.iter().position(|m| m == t).unwrap();
+                mirror.swap_remove(position);
+            }
+            mirror.extend_from_slice(&diff.added);
+            let i = triangulation.points().len() - 1;
+            assert!(diff.added.iter().all(|t| t.2 == i));
+
+            let mut expected = triangulation.triangles();
+            let key = |t: &Triangle| (t.0, t.1, t.2);
+            expected.sort_by_key(key);
+            let mut actual = mirror.clone();
+            actual.sort_by_key(key);
+            assert_eq!(actual, expected);
+        }
+    }
+
+    #[test]
+    fn test_progressive() {
+        let mut points = points();
+        points.extend_from_slice(&points.clone()[..5]);
+        let meshes: Vec<_> = triangulate_progressive(&points, 3).unwrap().collect();
+        assert_eq!(meshes.len(), 3);
+
+        // Every stage keeps the points of the previous one, and is Delaunay.
+        let mut previous = Vec::new();
+        for mesh in &meshes {
+            let mut used: Vec<usize> = mesh.iter().flat_map(|t| vec![t.0, t.1, t.2]).collect();
+            used.sort();
+            used.dedup();
+            assert!(previous.iter().all(|v| used.contains(v)));
+            assert!(used.len() > previous.len());
+            let subset: Vec<_> = used.iter().map(|&i| points[i]).collect();
+            let index = |v: usize| used.binary_search(&v).unwrap();
+            let remapped: Vec<_> = mesh
+                .iter()
+                .map(|t| Triangle(index(t.0), index(t.1), index(t.2)))
+                .collect();
+            assert!(is_delaunay(&subset, &remapped));
+            previous = used;
+        }
+
+        // The last stage has all the points.
+        let mut sorted = points[..40].to_vec();
+        sorted.sort_by(sort_points);
+        sorted.dedup();
+        let canonical = |triangles: Vec<Triangle>, points: &[TriangulationPoint<f64>]| {
+            let mut corners: Vec<_> = triangles
+                .into_iter()
+                .map(|t| {
+                    // Start from the same corner whatever the indices, keeping the winding.
+                    let mut corners = [t.0, t.1, t.2]
+                        .iter()
+                        .map(|&i| format!("{:?}", points[i]))
+                        .collect::<Vec<_>>();
+                    let first = (0..3).min_by_key(|&k| corners[k].clone()).unwrap();
+                    corners.rotate_left(first);
+                    corners.join(" ")
+                })
+                .collect();
+            corners.sort();
+            corners
+        };
+        assert_eq!(
+            canonical(meshes[2].clone(), &points),
+            canonical(triangulate(&sorted).unwrap(), &sorted)
+        );
+        assert!(triangulate_progressive(&points[..2], 3).is_err());
+
+        // The first of very many stages only has the three first points.
+        let mut meshes = triangulate_progressive(&points, usize::MAX).unwrap();
+        assert_eq!(meshes.next().unwrap().len(), 1);
+    }
+}
@@ -0,0 +1,316 @@
+// Copyright 2017-2018 Samuel Loretan <tynril@gmail.com> -- See LICENSE file
+
+//! Events of the Delaunay triangulation of moving points.
+
+use num_traits::Float;
+
+use super::topology::triangle_adjacency;
+use super::{Coordinate, Point, Result, Triangle, TriangulateError};
+
+/// Returns the earliest time at which an edge of the triangulation stops being Delaunay, as the
+/// points move.
+///
+/// Every point moves linearly from its position in `points`, at the speed given by the matching
+/// entry of `velocities`: at the time `t`, the point `i` is at `points[i] + t * velocities[i]`.
+/// The `triangles` must be a Delaunay triangulation of the points at the time `0`, as returned by
+/// `triangulate`. An edge shared by two triangles stops being Delaunay when the point across it
+/// enters the circumcircle of the other triangle: this finds the first time that happens, over
+/// all the edges, which is when the edge has to be flipped for the triangulation to stay Delaunay.
+/// This is `Some(0)` if an edge already isn't Delaunay, and `None` if none ever stops being so.
+/// If the constants of the computation can't be represented by `T`,
+/// `TriangulateError::UnsupportedCoordinate` is returned.
+///
+/// This is meant to drive the event loop of a kinetic triangulation: move the points up to the
+/// returned time, flip the edge (such as with `triangulate_warm`), then look for the next event
+/// with the new positions and triangles. Only the motion until that next event matters, so the
+/// motion only needs to be linear between events. Only the edges inside of the triangulation are
+/// checked: the changes of the convex hull, when a point moves out of it or into it, aren't
+/// detected.
+///
+/// Example:
+///
+/// ```rust
+/// use rtriangulate::{next_flip_time, Triangle, TriangulationPoint};
+///
+/// let points = [
+///     TriangulationPoint::new(-1.0, 0.0),
+///     TriangulationPoint::new(1.0, 0.0),
+///     TriangulationPoint::new(0.0, -1.0),
+///     TriangulationPoint::new(0.0, 2.0)
+/// ];
+/// let triangles = [Triangle(0, 1, 2), Triangle(1, 0, 3)];
+/// let still = TriangulationPoint::new(0.0, 0.0);
+///
+/// // The last point moves down, getting on the circumcircle of the first triangle at `t = 1`.
+/// let velocities = [still, still, still, TriangulationPoint::new(0.0, -1.0)];
+/// let time = next_flip_time(&points, &velocities, &triangles).unwrap().unwrap();
+/// assert!((time - 1.0f64).abs() < 1e-9);
+///
+/// // It never gets there when moving up.
+/// let velocities = [still, still, still, TriangulationPoint::new(0.0, 1.0)];
+/// assert_eq!(next_flip_time(&points, &velocities, &triangles).unwrap(), None);
+/// ```
+pub fn next_flip_time<T, P, V>(
+    points: &[P],
+    velocities: &[V],
+    triangles: &[Triangle],
+) -> Result<Option<T>>
+where
+    T: Float + Coordinate,
+    P: Point<T>,
+    V: Point<T>,
+{
+    assert_eq!(
+        points.len(),
+        velocities.len(),
+        "every point must have a velocity"
+    );
+
+    // The in-circle determinant is a polynomial of the time of degree 4 at most, whose derivative
+    // needs its degrees as `T`.
+    let unsupported = || TriangulateError::UnsupportedCoordinate;
+    let constants = Constants {
+        tolerance: T::from_f64(1024.0).ok_or_else(unsupported)?,
+        degrees: (0..5)
+            .map(|i| T::from_f64(f64::from(i)))
+            .collect::<Option<Vec<T>>>()
+            .ok_or_else(unsupported)?,
+    };
+
+    let mut earliest: Option<T> = None;
+    for (t, neighbours) in triangle_adjacency(triangles).into_iter().enumerate() {
+        for (edge, neighbour) in triangles[t].edges().iter().zip(neighbours.iter()) {
+            // Check every edge once, from the triangle with the lowest index.
+            let n = match *neighbour {
+                Some(n) if n > t => n,
+                _ => continue,
+            };
+            let other = &triangles[n];
+            let across = [other.0, other.1, other.2]
+                .iter()
+                .cloned()
+                .find(|&v| v != edge.0 && v != edge.1)
+                .unwrap();
+            let triangle = &triangles[t];
+            let third = [triangle.0, triangle.1, triangle.2]
+                .iter()
+                .cloned()
+                .find(|&v| v != edge.0 && v != edge.1)
+                .unwrap();
+
+            let time = entering_time(
+                points,
+                velocities,
+                [edge.0, edge.1, third],
+                across,
+                &constants,
+            );
+            earliest = match (earliest, time) {
+                (Some(earliest), Some(time)) => Some(Float::min(earliest, time)),
+                (earliest, time) => earliest.or(time),
+            };
+        }
+    }
+    Ok(earliest)
+}
+
+/// The constants of the computation, converted to `T` once.
+struct Constants<T> {
+    /// The factor of the rounding errors of the in-circle determinant.
+    tolerance: T,
+    /// The integers from zero, up to the highest degree of the polynomials.
+    degrees: Vec<T>,
+}
+
+/// Returns the earliest time at which the point `d` is strictly inside of the circumcircle of the
+/// triangle, as the points move.
+fn entering_time<T, P, V>(
+    points: &[P],
+    velocities: &[V],
+    triangle: [usize; 3],
+    d: usize,
+    constants: &Constants<T>,
+) -> Option<T>
+where
+    T: Float,
+    P: Point<T>,
+    V: Point<T>,
+{
+    // The coordinates of the triangle, relative to `d`, are polynomials of the time.
+    let relative = |i: usize| {
+        let x = [
+            points[i].x() - points[d].x(),
+            velocities[i].x() - velocities[d].x(),
+        ];
+        let y = [
+            points[i].y() - points[d].y(),
+            velocities[i].y() - velocities[d].y(),
+        ];
+        let lifted = add(&mul(&x, &x), &mul(&y, &y));
+        (x, y, lifted)
+    };
+    let (ax, ay, al) = relative(triangle[0]);
+    let (bx, by, bl) = relative(triangle[1]);
+    let (cx, cy, cl) = relative(triangle[2]);
+
+    // The in-circle determinant is positive when `d` is inside of the circumcircle of a
+    // counter-clockwise triangle, so turn it around for a clockwise one.
+    let orientation = (bx[0] - ax[0]) * (cy[0] - ay[0]) - (by[0] - ay[0]) * (cx[0] - ax[0]);
+    if orientation == T::zero() {
+        return None;
+    }
+    let minors = [
+        mul(&ax, &sub(&mul(&by, &cl), &mul(&bl, &cy))),
+        mul(&ay, &sub(&mul(&bx, &cl), &mul(&bl, &cx))),
+        mul(&al, &sub(&mul(&bx, &cy), &mul(&by, &cx))),
+    ];
+    let mut determinant = add(&sub(&minors[0], &minors[1]), &minors[2]);
+    if orientation < T::zero() {
+        determinant = determinant.into_iter().map(|c| -c).collect();
+    }
+
+    // Values this close to zero, relative to the size of the triangle, are rounding errors.
+    let scale = al[0] + bl[0] + cl[0];
+    let tolerance = scale * scale * T::epsilon() * constants.tolerance;
+
+    // Check between every time the determinant changes sign.
+    let bound = root_bound(&determinant);
+    let mut times = vec![T::zero()];
+    times.extend(roots(&determinant, T::zero(), bound, &constants.degrees));
+    times.push(bound);
+    let two = T::one() + T::one();
+    times
+        .windows(2)
+        .find(|w| evaluate(&determinant, (w[0] + w[1]) / two) > tolerance)
+        .map(|w| w[0])
+}
+
+/// Adds two polynomials, given by their coefficients in increasing degrees.
+fn add<T: Float>(a: &[T], b: &[T]) -> Vec<T> {
+    (0..a.len().max(b.len()))
+        .map(|i| *a.get(i).unwrap_or(&T::zero()) + *b.get(i).unwrap_or(&T::zero()))
+        .collect()
+}
+
+/// Subtracts two polynomials.
+fn sub<T: Float>(a: &[T], b: &[T]) -> Vec<T> {
+    let negated: Vec<T> = b.iter().map(|&c| -c).collect();
+    add(a, &negated)
+}
+
+/// Multiplies two polynomials.
+fn mul<T: Float>(a: &[T], b: &[T]) -> Vec<T> {
+    let mut product = vec![T::zero(); a.len() + b.len() - 1];
+    for (i, &x) in a.iter().enumerate() {
+        for (j, &y) in b.iter().enumerate() {
+            product[i + j] = product[i + j] + x * y;
+        }
+    }
+    product
+}
+
+/// Evaluates a polynomial.
+fn evaluate<T: Float>(p: &[T], t: T) -> T {
+    p.iter().rev().fold(T::zero(), |value, &c| value * t + c)
+}
+
+/// Returns a time after which a polynomial doesn't change sign anymore, with Cauchy's bound on
+/// its roots.
+fn root_bound<T: Float>(p: &[T]) -> T {
+    match p.iter().rposition(|&c| c != T::zero()) {
+        Some(degree) if degree > 0 => {
+            let leading = p[degree].abs();
+            let largest = p[..degree]
+                .iter()
+                .fold(T::zero(), |largest, &c| largest.max(c.abs() / leading));
+            (T::one() + largest).min(T::max_value())
+        }
+        _ => T::one(),
+    }
+}
+
+/// Returns the roots of a polynomial within `[low, high]`, in increasing order.
+///
+/// The roots of the derivative split the interval into parts where the polynomial is monotonic,
+/// and so has at most one root, found by bisection. The `degrees` are the integers from zero, up to
+/// the degree of the polynomial.
+fn roots<T: Float>(p: &[T], low: T, high: T, degrees: &[T]) -> Vec<T> {
+    let degree = match p.iter().rposition(|&c| c != T::zero()) {
+        Some(degree) if degree > 0 => degree,
+        _ => return Vec::new(),
+    };
+    let derivative: Vec<T> = (1..=degree).map(|i| p[i] * degrees[i]).collect();
+
+    let mut bounds = vec![low];
+    bounds.extend(roots(&derivative, low, high, degrees));
+    bounds.push(high);
+    let mut found: Vec<T> = Vec::new();
+    for w in bounds.windows(2) {
+        let (mut a, mut b) = (w[0], w[1]);
+        let (fa, fb) = (evaluate(p, a), evaluate(p, b));
+        let root = if fa == T::zero() {
+            a
+        } else if fb == T::zero() {
+            b
+        } else if (fa < T::zero()) == (fb < T::zero()) {
+            continue;
+        } else {
+            // Halve the interval until it can't be split anymore.
+            let two = T::one() + T::one();
+            loop {
+                let mid = a + (b - a) / two;
+                if mid <= a || mid >= b {
+                    break mid;
+                }
+                if (evaluate(p, mid) < T::zero()) == (fa < T::zero()) {
+                    a = mid;
+                } else {
+                    b = mid;
+                }
+            }
+        };
+        if found.last().is_none_or(|&last| root > last) {
+            found.push(root);
+        }
+    }
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{is_delaunay, triangulate, TriangulationPoint};
+    use super::next_flip_time;
+
+    #[test]
+    fn test_next_flip_time() {
+        let points: Vec<_> = (0..30)
+            .map(|i| TriangulationPoint::new(i as f64 * 3.3, ((i * 37) % 17) as f64 * 2.0))
+            .collect();
+        let velocities: Vec<_> = (0..30)
+            .map(|i| {
+                let (vx, vy) = (((i * 13) % 7) as f64 - 3.0, ((i * 11) % 5) as f64 - 2.0);
+                TriangulationPoint::new(vx * 0.1, vy * 0.1)
+            })
+            .collect();
+        let triangles = triangulate(&points).unwrap();
+        let at = |t: f64| -> Vec<_> {
+            points
+                .iter()
+                .zip(velocities.iter())
+                .map(|(p, v)| TriangulationPoint::new(p.x + v.x * t, p.y + v.y * t))
+                .collect()
+        };
+
+        // The triangles stay Delaunay up to the event, and stop being so right after it.
+        let time = next_flip_time(&points, &velocities, &triangles)
+            .unwrap()
+            .unwrap();
+        assert!(time > 0.0);
+        assert!(is_delaunay(&at(time * 0.99), &triangles));
+        assert!(!is_delaunay(&at(time * 1.05), &triangles));
+
+        // Nothing happens when the points all move together.
+        let drift = vec![TriangulationPoint::new(1.0, -2.0); points.len()];
+        assert_eq!(next_flip_time(&points, &drift, &triangles).unwrap(), None);
+    }
+}
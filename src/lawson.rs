@@ -0,0 +1,311 @@
+// Copyright 2017-2018 Samuel Loretan <tynril@gmail.com> -- See LICENSE file
+
+//! Delaunay triangulation by the incremental insertion of points with edge flips.
+
+use super::validate::{circle_tolerance, circumcircle};
+use super::{bounding_supertriangle, Coordinate, Point, Result, Triangle, TriangulationPoint};
+
+/// Generate the Delaunay triangulation of given set of points, with Lawson's flip algorithm.
+///
+/// This is an alternative to the Bowyer-Watson algorithm of `triangulate`, using other predicates:
+/// every point is inserted by finding the triangle containing it, which is split in three (or the
+/// two triangles on either side of the edge it lies on, in four), before flipping the edges around
+/// the point which aren't locally Delaunay anymore. Starting from the same supertriangle, both give
+/// the same triangles for points in general position, though not in the same order. When four
+/// points or more are cocircular, they can be triangulated differently, while still being Delaunay:
+/// this only flips the edges of circumcircles which strictly contain a point, with some tolerance.
+///
+/// The triangles are clockwise, as with `triangulate`, and the points which are duplicates of
+/// previous ones are left out of the triangulation. The points don't need to be sorted, but the
+/// triangle containing every point is searched for by walking from the last inserted one, which is
+/// faster when consecutive points are close to each other, as when sorted.
+///
+/// Example:
+///
+/// ```rust
+/// use rtriangulate::{triangulate_lawson, TriangulationPoint, Triangle};
+///
+/// let points = [
+///     TriangulationPoint::new(10.0, 50.0),
+///     TriangulationPoint::new(25.0, 40.0),
+///     TriangulationPoint::new(30.0, 40.0)
+/// ];
+/// assert_eq!(triangulate_lawson(&points).unwrap(), [Triangle(1, 0, 2)]);
+/// ```
+pub fn triangulate_lawson<T, P>(points: &[P]) -> Result<Vec<Triangle>>
+where
+    T: Coordinate,
+    P: Point<T>,
+{
+    let supertriangle = bounding_supertriangle(points)?;
+    let points_count = points.len();
+    let mut all_points: Vec<TriangulationPoint<T>> = points
+        .iter()
+        .map(|p| TriangulationPoint::new(p.x(), p.y()))
+        .collect();
+    all_points.extend_from_slice(&supertriangle);
+
+    let mut mesh = Mesh {
+        points: &all_points,
+        triangles: vec![[points_count, points_count + 1, points_count + 2]],
+        adjacency: vec![[None; 3]],
+        pending: Vec::new(),
+    };
+    let mut last = 0;
+    for i in 0..points_count {
+        if let Some(t) = mesh.insert(i, last) {
+            last = t;
+        }
+    }
+
+    Ok(mesh
+        .triangles
+        .into_iter()
+        .filter(|t| t.iter().all(|&v| v < points_count))
+        .map(|t| Triangle(t[0], t[1], t[2]))
+        .collect())
+}
+
+/// A triangulation being built, with the neighbours of every triangle.
+struct Mesh<'a, T: 'a> {
+    points: &'a [TriangulationPoint<T>],
+    /// The clockwise triangles.
+    triangles: Vec<[usize; 3]>,
+    /// The triangles across the edges of every triangle, the edge `k` going from its vertex `k` to
+    /// its vertex `k + 1`.
+    adjacency: Vec<[Option<usize>; 3]>,
+    /// The edges to check after an insertion, as a triangle and the two vertices of its edge.
+    pending: Vec<(usize, usize, usize)>,
+}
+
+impl<'a, T> Mesh<'a, T>
+where
+    T: Coordinate,
+{
+    /// Returns how the point `p` is turned from the edge going from `a` to `b`: positive when the
+    /// point is on the left, outside of a clockwise triangle.
+    fn orientation(&self, a: usize, b: usize, p: usize) -> T {
+        let (a, b, p) = (&self.points[a], &self.points[b], &self.points[p]);
+        (b.x - a.x) * (p.y - a.y) - (b.y - a.y) * (p.x - a.x)
+    }
+
+    /// Inserts the point `p`, returning one of the triangles using it, or `None` if it is the
+    /// duplicate of a point already in the mesh.
+    fn insert(&mut self, p: usize, start: usize) -> Option<usize> {
+        let t = self.locate(p, start);
+        let triangle = self.triangles[t];
+        let point = &self.points[p];
+        if triangle.iter().any(|&v| self.points[v] == *point) {
+            return None;
+        }
+
+        match (0..3).find(|&k| self.orientation(triangle[k], triangle[(k + 1) % 3], p) == T::zero())
+        {
+            Some(k) if self.adjacency[t][k].is_some() => self.split_edge(t, k, p),
+            _ => self.split_triangle(t, p),
+        }
+        self.legalize();
+        Some(t)
+    }
+
+    /// Finds the triangle containing the point `p`, walking towards it from the `start` triangle.
+    fn locate(&self, p: usize, start: usize) -> usize {
+        let mut t = start;
+        for _ in 0..self.triangles.len() {
+            let triangle = self.triangles[t];
+            let outside = (0..3).find(|&k| {
+                self.adjacency[t][k].is_some()
+                    && self.orientation(triangle[k], triangle[(k + 1) % 3], p) > T::zero()
+            });
+            match outside {
+                Some(k) => t = self.adjacency[t][k].unwrap(),
+                None => return t,
+            }
+        }
+
+        // The walk can go around in circles when rounding errors are involved, so look at all
+        // the triangles instead.
+        (0..self.triangles.len())
+            .find(|&t| {
+                let triangle = self.triangles[t];
+                (0..3).all(|k| self.orientation(triangle[k], triangle[(k + 1) % 3], p) <= T::zero())
+            })
+            .unwrap_or(t)
+    }
+
+    /// Splits the triangle `t` in three around the point `p`.
+    fn split_triangle(&mut self, t: usize, p: usize) {
+        let [a, b, c] = self.triangles[t];
+        let [n_ab, n_bc, n_ca] = self.adjacency[t];
+        let (t1, t2) = (self.triangles.len(), self.triangles.len() + 1);
+
+        self.triangles[t] = [a, b, p];
+        self.adjacency[t] = [n_ab, Some(t1), Some(t2)];
+        self.triangles.push([b, c, p]);
+        self.adjacency.push([n_bc, Some(t2), Some(t)]);
+        self.triangles.push([c, a, p]);
+        self.adjacency.push([n_ca, Some(t), Some(t1)]);
+        self.replace_neighbour(n_bc, t, t1);
+        self.replace_neighbour(n_ca, t, t2);
+
+        self.pending
+            .extend_from_slice(&[(t, a, b), (t1, b, c), (t2, c, a)]);
+    }
+
+    /// Splits the edge `k` of the triangle `t` at the point `p`, along with the triangle on its
+    /// other side, making four triangles.
+    fn split_edge(&mut self, t: usize, k: usize, p: usize) {
+        let (a, b, c) = (
+            self.triangles[t][k],
+            self.triangles[t][(k + 1) % 3],
+            self.triangles[t][(k + 2) % 3],
+        );
+        let (n_bc, n_ca) = (
+            self.adjacency[t][(k + 1) % 3],
+            self.adjacency[t][(k + 2) % 3],
+        );
+        let u = self.adjacency[t][k].unwrap();
+        let j = self.edge_index(u, b, a).unwrap();
+        let d = self.triangles[u][(j + 2) % 3];
+        let (n_ad, n_db) = (
+            self.adjacency[u][(j + 1) % 3],
+            self.adjacency[u][(j + 2) % 3],
+        );
+        let (t2, u2) = (self.triangles.len(), self.triangles.len() + 1);
+
+        self.triangles[t] = [a, p, c];
+        self.adjacency[t] = [Some(u2), Some(t2), n_ca];
+        self.triangles[u] = [b, p, d];
+        self.adjacency[u] = [Some(t2), Some(u2), n_db];
+        self.triangles.push([p, b, c]);
+        self.adjacency.push([Some(u), n_bc, Some(t)]);
+        self.triangles.push([p, a, d]);
+        self.adjacency.push([Some(t), n_ad, Some(u)]);
+        self.replace_neighbour(n_bc, t, t2);
+        self.replace_neighbour(n_ad, u, u2);
+
+        self.pending
+            .extend_from_slice(&[(t, c, a), (t2, b, c), (u, d, b), (u2, a, d)]);
+    }
+
+    /// Flips the pending edges which aren't locally Delaunay, until they all are.
+    fn legalize(&mut self) {
+        let tolerance = circle_tolerance::<T>();
+        while let Some((t, a, b)) = self.pending.pop() {
+            // The edge may have been flipped away since it was added.
+            let k = match self.edge_index(t, a, b) {
+                Some(k) => k,
+                None => continue,
+            };
+            let u = match self.adjacency[t][k] {
+                Some(u) => u,
+                None => continue,
+            };
+            let p = self.triangles[t][(k + 2) % 3];
+            let j = self.edge_index(u, b, a).unwrap();
+            let d = self.triangles[u][(j + 2) % 3];
+
+            let strictly_inside =
+                match circumcircle(&self.points[a], &self.points[b], &self.points[p]) {
+                    Some((center_x, center_y, radius_sq)) => {
+                        let (dx, dy) = (self.points[d].x - center_x, self.points[d].y - center_y);
+                        radius_sq - (dx * dx + dy * dy) > radius_sq * tolerance
+                    }
+                    None => false,
+                };
+            if !strictly_inside
+                || self.orientation(a, d, p) >= T::zero()
+                || self.orientation(d, b, p) >= T::zero()
+            {
+                continue;
+            }
+
+            // Replace the triangles `a, b, p` and `b, a, d` by `a, d, p` and `d, b, p`.
+            let (n_bp, n_pa) = (
+                self.adjacency[t][(k + 1) % 3],
+                self.adjacency[t][(k + 2) % 3],
+            );
+            let (n_ad, n_db) = (
+                self.adjacency[u][(j + 1) % 3],
+                self.adjacency[u][(j + 2) % 3],
+            );
+            self.triangles[t] = [a, d, p];
+            self.adjacency[t] = [n_ad, Some(u), n_pa];
+            self.triangles[u] = [d, b, p];
+            self.adjacency[u] = [n_db, n_bp, Some(t)];
+            self.replace_neighbour(n_ad, u, t);
+            self.replace_neighbour(n_bp, t, u);
+
+            self.pending.extend_from_slice(&[(t, a, d), (u, d, b)]);
+        }
+    }
+
+    /// Returns the index of the edge going from `a` to `b` in the triangle `t`.
+    fn edge_index(&self, t: usize, a: usize, b: usize) -> Option<usize> {
+        let triangle = self.triangles[t];
+        (0..3).find(|&k| triangle[k] == a && triangle[(k + 1) % 3] == b)
+    }
+
+    /// Makes the triangle `neighbour` point to `new` instead of `old`.
+    fn replace_neighbour(&mut self, neighbour: Option<usize>, old: usize, new: usize) {
+        if let Some(n) = neighbour {
+            for across in &mut self.adjacency[n] {
+                if *across == Some(old) {
+                    *across = Some(new);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{
+        is_delaunay, is_edge_manifold, sort_points, triangulate, Triangle, TriangulationPoint,
+    };
+    use super::triangulate_lawson;
+
+    fn canonical(triangles: Vec<Triangle>) -> Vec<Triangle> {
+        let mut triangles: Vec<_> = triangles.iter().map(Triangle::canonical).collect();
+        triangles.sort();
+        triangles
+    }
+
+    #[test]
+    fn test_lawson() {
+        // Points in general position give the same triangles as `triangulate`.
+        let mut points: Vec<_> = (0..200)
+            .map(|i| {
+                TriangulationPoint::new(
+                    (i * 37 % 401) as f64,
+                    (i * 59 % 397) as f64 + 0.1 * (i % 7) as f64,
+                )
+            })
+            .collect();
+        points.sort_by(sort_points);
+        assert_eq!(
+            canonical(triangulate_lawson(&points).unwrap()),
+            canonical(triangulate(&points).unwrap())
+        );
+
+        // Cocircular points are triangulated differently, but still Delaunay.
+        let grid: Vec<_> = (0..36)
+            .map(|i| TriangulationPoint::new((i / 6) as f64, (i % 6) as f64))
+            .collect();
+        let triangles = triangulate_lawson(&grid).unwrap();
+        assert!(is_delaunay(&grid, &triangles));
+        assert!(is_edge_manifold(&triangles));
+        assert_eq!(triangles.len(), triangulate(&grid).unwrap().len());
+
+        // Duplicates are left out, and the points don't need to be sorted.
+        let mut shuffled = points.clone();
+        shuffled.reverse();
+        shuffled.push(shuffled[3]);
+        let triangles = triangulate_lawson(&shuffled).unwrap();
+        assert!(triangles
+            .iter()
+            .all(|t| t.0 != 200 && t.1 != 200 && t.2 != 200));
+        assert!(is_delaunay(&shuffled, &triangles));
+        assert_eq!(triangles.len(), triangulate(&points).unwrap().len());
+    }
+}
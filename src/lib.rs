@@ -32,10 +32,92 @@
 //! # }
 //! ```
 
+#![cfg_attr(feature = "allocator-api", feature(allocator_api))]
+#![cfg_attr(all(test, feature = "f128"), feature(f128))]
+
 extern crate num_traits;
 
-use num_traits::float::FloatCore;
+#[cfg(feature = "allocator-api")]
+use std::alloc::Allocator;
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+use std::fmt;
+use std::hash::{Hash, Hasher};
 use std::marker::PhantomData;
+use std::ops::ControlFlow;
+
+#[cfg(feature = "stack-scratch")]
+use buffer::SpillBuffer;
+use buffer::{Buffer, FixedBuffer};
+
+mod approx;
+mod buffer;
+mod cavity;
+mod constraint;
+mod coordinate;
+mod decimate;
+mod ext;
+mod filter;
+mod geometry;
+#[cfg(feature = "gltf")]
+mod gltf;
+mod grid;
+mod half_edge;
+mod hull;
+mod incremental;
+mod kinetic;
+mod lawson;
+mod mesh;
+mod preprocess;
+mod raster;
+mod refine;
+#[cfg(any(test, feature = "test-util"))]
+mod test_util;
+mod tiling;
+mod topology;
+mod validate;
+mod voronoi;
+mod warm;
+mod xorshift;
+mod xy;
+
+pub use approx::triangulate_approx;
+pub use cavity::{cavity_boundary, find_cavity};
+pub use constraint::{insert_constraint_edge, triangulate_constrained, ConstrainedTriangulation};
+pub use coordinate::Coordinate;
+pub use decimate::decimate;
+pub use ext::TriangulateExt;
+pub use filter::{compact, ensure_winding, filter_long_edges, sort_triangles_morton, Winding};
+pub use geometry::{
+    aspect_ratios, bisector_direction, bounding_circle, circumcenter, circumcircles, circumradius,
+    clip_triangle_convex, distance_to_hull, edge_lengths, edge_midpoint, hull_with_normals,
+    triangle_qualities, triangles_near,
+};
+#[cfg(feature = "gltf")]
+pub use gltf::write_gltf;
+pub use grid::triangulate_grid;
+pub use half_edge::{to_half_edge_flat, HalfEdgeMesh};
+pub use hull::convex_hull_only;
+pub use incremental::{triangulate_progressive, Diff, Triangulation};
+pub use kinetic::next_flip_time;
+pub use lawson::triangulate_lawson;
+pub use mesh::{triangulate_ref, MeshRef};
+pub use preprocess::snap_to_grid;
+pub use raster::{rasterize, rasterize_triangle};
+pub use refine::{refine, triangulate_conforming, SteinerStrategy};
+#[cfg(feature = "test-util")]
+pub use test_util::{assert_valid_triangulation, near_cocircular_points, random_points};
+pub use tiling::{merge_triangulations, suggest_tiling};
+pub use topology::{
+    boundary_edges, connected_components, dedup_triangles, dual_graph, duplicate_triangles,
+    edge_iter, for_each_edge, for_each_triangle, hull_triangles, hull_vertex_fan, is_boundary_edge,
+    is_edge_manifold, k_nearest_graph, non_manifold_edges, retain_triangles, triangle_adjacency,
+    triangle_path, triangles_containing, vertex_triangles,
+};
+pub use validate::is_delaunay;
+pub use voronoi::{lloyd_relax_weighted, voronoi, voronoi_cell_areas, voronoi_vertices, Voronoi};
+pub use warm::triangulate_warm;
+pub use xy::{read_xy, write_xy};
 
 pub type Result<T> = std::result::Result<T, TriangulateError>;
 
@@ -44,6 +126,36 @@ pub type Result<T> = std::result::Result<T, TriangulateError>;
 pub enum TriangulateError {
     /// At least three points are necessary to triangulate.
     NotEnoughPoints,
+    /// There are more points than the output indices can address, or than the `N` points
+    /// `triangulate_fixed` was sized for.
+    TooManyPoints,
+    /// The points don't span a two-dimensional area (such as when they are all coincident), so
+    /// they can't be triangulated.
+    Degenerate,
+    /// The progress callback given to `triangulate_with_progress` asked to stop.
+    Aborted,
+    /// A point isn't strictly inside of the supertriangle given to
+    /// `triangulate_with_supertriangle`, or that triangle is degenerate.
+    OutsideSupertriangle,
+    /// A vertex of the edge given to `insert_constraint_edge` isn't a vertex of the triangulation.
+    MissingVertex,
+    /// The edge given to `ConstrainedTriangulation::insert_constraint` crosses a previous
+    /// constraint.
+    IntersectingConstraints,
+    /// A constraint edge given to `triangulate_constrained` or `triangulate_conforming` has a
+    /// vertex which isn't an index of the points.
+    ConstraintOutOfRange {
+        /// The first constraint edge out of range.
+        edge: Edge,
+    },
+    /// A constant the triangulation needs, such as `0.5` or `2`, can't be represented by the
+    /// coordinate type, as `Coordinate::from_f64` returned `None` for it, or a value which has to
+    /// be converted to `f64` can't be, as `Coordinate::to_f64` returned `None` for it.
+    UnsupportedCoordinate,
+    /// The output slice given to `triangulate_fixed` can't hold all of the triangles.
+    OutputTooSmall,
+    /// A point has a `NaN` or infinite coordinate.
+    NonFiniteCoordinate,
 }
 
 /// A trait for two-dimensional points.
@@ -54,10 +166,11 @@ pub enum TriangulateError {
 /// For your convenience, a `sort_points` function is provided. This function can be used in
 /// the `sort_by` or `sort_unstable_by` functions on slices, and will order the points in the
 /// order necessary for `triangulate` to work (which is in ascending `x` order).
-pub trait Point<T>
-where
-    T: FloatCore,
-{
+///
+/// The trait itself puts no bound on the coordinate type `T`, so that it can be used both with
+/// the `Coordinate` requirement of `triangulate`, and with the `Float` requirement of the geometric
+/// helpers which need square roots.
+pub trait Point<T> {
     /// Returns the `x` component of this point.
     fn x(&self) -> T;
 
@@ -69,17 +182,52 @@ where
 ///
 /// Use this function by passing it to `sort_by` or `sort_unstable_by` on your slice of points.
 /// The ordering this function applies is what the `triangulate` function expects, which is an
-/// ascending `x` order.
+/// ascending `x` order. Points with a `NaN` coordinate are ordered after all the others, which
+/// keeps the order total, although `triangulate` then refuses them.
 pub fn sort_points<T, P1, P2>(a: &P1, b: &P2) -> std::cmp::Ordering
 where
-    T: FloatCore,
+    T: Coordinate,
     P1: Point<T>,
     P2: Point<T>,
 {
-    match a.x().partial_cmp(&b.x()) {
-        Some(std::cmp::Ordering::Equal) => a.y().partial_cmp(&b.y()),
-        other => other,
-    }.unwrap_or(std::cmp::Ordering::Greater)
+    // Only `NaN` isn't comparable, not even to itself, and it is ordered after any number.
+    let is_nan = |v: T| v.partial_cmp(&v).is_none();
+    let order = |a: T, b: T| {
+        a.partial_cmp(&b)
+            .unwrap_or_else(|| is_nan(a).cmp(&is_nan(b)))
+    };
+    order(a.x(), b.x()).then_with(|| order(a.y(), b.y()))
+}
+
+/// Returns true if the points are sorted the way `triangulate` expects, as `sort_points` does.
+///
+/// Points sharing the same `x` must be in ascending `y` order, and points with a `NaN` coordinate
+/// come last, as `sort_points` orders them. The triangulation functions which need sorted points
+/// check this in debug builds, and panic if the points aren't sorted. They check before that that
+/// all the coordinates are finite, and return `TriangulateError::NonFiniteCoordinate` otherwise.
+///
+/// Example:
+///
+/// ```rust
+/// use rtriangulate::{is_sorted_for_triangulation, sort_points, TriangulationPoint};
+///
+/// let mut points = [
+///     TriangulationPoint::new(25.0, 40.0),
+///     TriangulationPoint::new(10.0, 50.0),
+///     TriangulationPoint::new(25.0, 30.0)
+/// ];
+/// assert!(!is_sorted_for_triangulation(&points));
+/// points.sort_by(sort_points);
+/// assert!(is_sorted_for_triangulation(&points));
+/// ```
+pub fn is_sorted_for_triangulation<T, P>(points: &[P]) -> bool
+where
+    T: Coordinate,
+    P: Point<T>,
+{
+    points
+        .windows(2)
+        .all(|w| sort_points(&w[0], &w[1]) != std::cmp::Ordering::Greater)
 }
 
 /// A two-dimensional point of generic precision, which implements the `Point` trait.
@@ -88,24 +236,23 @@ where
 /// It is very simple, and doesn't provide much features, but it is functional. Internally, this
 /// type is used for temporary storage of points that need to be created during triangulation.
 #[derive(Clone, Copy, PartialEq, Debug)]
-pub struct TriangulationPoint<T>
-where
-    T: FloatCore,
-{
+pub struct TriangulationPoint<T> {
     pub x: T,
     pub y: T,
 }
 
-impl<T> TriangulationPoint<T>
-where
-    T: FloatCore,
-{
+impl<T> TriangulationPoint<T> {
     /// Makes a new point from xy coordinates.
     #[inline(always)]
     pub fn new(x: T, y: T) -> Self {
         TriangulationPoint { x, y }
     }
+}
 
+impl<T> TriangulationPoint<T>
+where
+    T: Coordinate,
+{
     /// A point located at the infinity.
     #[inline(always)]
     fn infinity() -> Self {
@@ -127,7 +274,7 @@ where
 
 impl<T> Point<T> for TriangulationPoint<T>
 where
-    T: FloatCore,
+    T: Copy,
 {
     /// The `x` component of this triangulation point.
     #[inline(always)]
@@ -142,10 +289,70 @@ where
     }
 }
 
+impl<T> fmt::Display for TriangulationPoint<T>
+where
+    T: fmt::Display,
+{
+    /// Formats the point as `(x, y)`.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "({}, {})", self.x, self.y)
+    }
+}
+
 /// A triangle, represented by indexes into a list of points.
-#[derive(Debug, PartialEq)]
+///
+/// Triangles are ordered lexicographically by their indices. Along with `canonical`, this makes it
+/// easy to compare two triangulations regardless of the order of their triangles:
+///
+/// ```rust
+/// use rtriangulate::Triangle;
+///
+/// let canonical = |triangles: &[Triangle]| {
+///     let mut triangles: Vec<Triangle> = triangles.iter().map(Triangle::canonical).collect();
+///     triangles.sort();
+///     triangles
+/// };
+/// assert_eq!(
+///     canonical(&[Triangle(0, 1, 2), Triangle(3, 2, 1)]),
+///     canonical(&[Triangle(1, 3, 2), Triangle(2, 0, 1)])
+/// );
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct Triangle(pub usize, pub usize, pub usize);
 
+impl Triangle {
+    /// Returns the three edges of this triangle, following its winding: `(0, 1)`, `(1, 2)` and
+    /// `(2, 0)`.
+    #[inline(always)]
+    pub fn edges(&self) -> [Edge; 3] {
+        [
+            Edge(self.0, self.1),
+            Edge(self.1, self.2),
+            Edge(self.2, self.0),
+        ]
+    }
+
+    /// Returns the same triangle, with its vertices rotated so that the smallest index comes
+    /// first. The winding of the triangle is preserved.
+    #[inline(always)]
+    pub fn canonical(&self) -> Triangle {
+        if self.0 <= self.1 && self.0 <= self.2 {
+            *self
+        } else if self.1 <= self.2 {
+            Triangle(self.1, self.2, self.0)
+        } else {
+            Triangle(self.2, self.0, self.1)
+        }
+    }
+}
+
+impl fmt::Display for Triangle {
+    /// Formats the triangle as `[a, b, c]`.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "[{}, {}, {}]", self.0, self.1, self.2)
+    }
+}
+
 /// An edge, represented by indexes into a list of points.
 ///
 /// When compared, ignore the directionality of the edge, such as:
@@ -154,6 +361,18 @@ pub struct Triangle(pub usize, pub usize, pub usize);
 /// use rtriangulate::Edge;
 /// assert_eq!(Edge(0, 1), Edge(1, 0));
 /// ```
+///
+/// The same goes for ordering, which sorts edges by their smallest index first, then by their
+/// largest one. This makes it possible to sort and deduplicate a list of edges:
+///
+/// ```rust
+/// use rtriangulate::Edge;
+///
+/// let mut edges = vec![Edge(2, 1), Edge(0, 2), Edge(1, 2), Edge(1, 0)];
+/// edges.sort();
+/// edges.dedup();
+/// assert_eq!(edges, [Edge(0, 1), Edge(0, 2), Edge(1, 2)]);
+/// ```
 #[derive(Debug, Clone)]
 pub struct Edge(pub usize, pub usize);
 
@@ -165,27 +384,173 @@ impl PartialEq for Edge {
     }
 }
 
-/// A view over two slices that can be indexed seamlessly across both.
+impl Eq for Edge {}
+
+impl Edge {
+    /// The indices of the edge, smallest first, which is the same for both directions.
+    #[inline(always)]
+    fn normalized(&self) -> (usize, usize) {
+        (self.0.min(self.1), self.0.max(self.1))
+    }
+}
+
+impl Hash for Edge {
+    /// Hash edges regardless of directionality, consistently with `PartialEq`.
+    #[inline(always)]
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.normalized().hash(state);
+    }
+}
+
+impl PartialOrd for Edge {
+    #[inline(always)]
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Edge {
+    /// Order edges by their smallest index, then by their largest one, regardless of
+    /// directionality and consistently with `PartialEq`.
+    #[inline(always)]
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.normalized().cmp(&other.normalized())
+    }
+}
+
+/// Options tweaking the behavior of the triangulation, for use with `triangulate_with`.
+///
+/// The default options give the same behavior as `triangulate`. The options are plain data, and
+/// are only read by the triangulation: a single set of options can be shared by reference between
+/// threads triangulating at the same time.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct TriangulateOptions<T> {
+    /// Computes and compares the squared distances of the circumcircle test in `f64`, whatever
+    /// the coordinate type.
+    ///
+    /// With large `f32` coordinates, the squared distance of a point from a circumcircle center
+    /// and its squared radius are both large, and rounding them to `f32` can flip their comparison
+    /// for points close to the circumcircle. Widening them avoids that loss of precision, at a
+    /// small performance cost. This doesn't change anything for `f64` coordinates. If a coordinate
+    /// can't be converted to `f64`, `TriangulateError::UnsupportedCoordinate` is returned.
+    pub widen_accumulate: bool,
+
+    /// Removes the output triangles whose smallest angle, in radians, is below this threshold.
+    ///
+    /// When some input points are nearly collinear, the triangulation contains very thin
+    /// triangles, mostly along the convex hull. This drops them from the output, which can leave
+    /// the hull slightly concave. No point is added to the triangulation, unlike what a mesh
+    /// refinement would do. The default, `None`, keeps all the triangles. If the threshold can't be
+    /// converted to and from `f64`, `TriangulateError::UnsupportedCoordinate` is returned.
+    pub drop_slivers_below_angle: Option<T>,
+
+    /// Chooses the edges between cocircular points by their indices, rather than by the order the
+    /// points were inserted in.
+    ///
+    /// When four points or more are on the same circle, such as the corners of the squares of a
+    /// regular grid, several triangulations of them are Delaunay, and the one returned depends on
+    /// the insertion order, which tiny changes of the coordinates can alter. With this, the edges
+    /// between cocircular triangles are flipped so that every one is the diagonal of its quad with
+    /// the lowest-indexed vertex: the same points always give the same triangles, even slightly
+    /// perturbed. The default, `false`, keeps the triangles as they were made.
+    pub break_cocircular_ties: bool,
+}
+
+impl<T> Default for TriangulateOptions<T> {
+    fn default() -> Self {
+        TriangulateOptions {
+            widen_accumulate: false,
+            drop_slivers_below_angle: None,
+            break_cocircular_ties: false,
+        }
+    }
+}
+
+/// Statistics about a triangulation, as returned by `triangulate_with_stats`.
+#[derive(Clone, PartialEq, Eq, Default, Debug)]
+pub struct TriangulateStats {
+    /// The size of the cavity made by the insertion of every point, in the order of the points.
+    ///
+    /// The cavity of a point is made of the triangles whose circumcircle contains it, which are
+    /// replaced by triangles joining the point to the boundary of the cavity. This includes the
+    /// triangles made with the supertriangle vertices. Cavities are usually small, so large ones
+    /// reveal points close to be cocircular with many others.
+    pub cavity_sizes: Vec<usize>,
+    /// The size of the largest cavity.
+    pub max_cavity_size: usize,
+    /// The number of circumcircle tests decided without computing the circumcircle.
+    ///
+    /// This happens when the triangle is degenerate, with its three points on a horizontal line,
+    /// or when the tested point lies on a horizontal or vertical line through two of its
+    /// vertices, which is common with gridded data.
+    pub shortcuts: usize,
+}
+
+/// The structure spanned by a set of points of any size, as returned by `triangulate_lenient_small`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SmallResult {
+    /// There are no points.
+    Empty,
+    /// There is a single point, of the given index.
+    Point(usize),
+    /// There are two points, making a single edge.
+    Edge(Edge),
+    /// There are at least three points, which aren't degenerate: these are their triangles.
+    Mesh(Vec<Triangle>),
+}
+
+/// A view over two slices of points that can be indexed seamlessly across both, as if they were
+/// concatenated.
 ///
 /// This is used internally by the `triangulate` function as a way to treat the supertriangle
-/// vertices as any other vertice, but without having to modify the input list of vertices.
-struct TwoPointsSlices<'a, S1: 'a, S2: 'a, T>(&'a [S1], &'a [S2], PhantomData<T>);
+/// vertices as any other vertice, but without having to modify the input list of vertices. It can
+/// be used the same way to append sentinel points to a slice of points without copying it.
+///
+/// Example:
+///
+/// ```rust
+/// use rtriangulate::{ConcatPoints, TriangulationPoint};
+///
+/// let points = [TriangulationPoint::new(10.0, 50.0), TriangulationPoint::new(25.0, 40.0)];
+/// let sentinels = [TriangulationPoint::new(0.0, 0.0)];
+///
+/// let all_points = ConcatPoints::new(&points, &sentinels);
+/// assert_eq!(all_points.len(), 3);
+/// assert_eq!(all_points.get(1).x(), 25.0);
+/// assert_eq!(all_points.get(2).x(), 0.0);
+/// ```
+pub struct ConcatPoints<'a, P: 'a, Q: 'a, T>(&'a [P], &'a [Q], PhantomData<T>);
 
-impl<'a, S1, S2, T> TwoPointsSlices<'a, S1, S2, T>
+impl<'a, P, Q, T> ConcatPoints<'a, P, Q, T>
 where
-    T: FloatCore,
-    S1: Point<T>,
-    S2: Point<T>,
+    T: Coordinate,
+    P: Point<T>,
+    Q: Point<T>,
 {
     /// Make a new view over two slices of points.
     #[inline(always)]
-    fn new(first: &'a [S1], second: &'a [S2]) -> Self {
-        TwoPointsSlices(first, second, PhantomData)
+    pub fn new(first: &'a [P], second: &'a [Q]) -> Self {
+        ConcatPoints(first, second, PhantomData)
+    }
+
+    /// Returns the number of points across both slices.
+    #[inline(always)]
+    pub fn len(&self) -> usize {
+        self.0.len() + self.1.len()
+    }
+
+    /// Returns true if both slices are empty.
+    #[inline(always)]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty() && self.1.is_empty()
     }
 
     /// Get the point at a given index across both slices of points.
+    ///
+    /// Indices from `0` to the length of the first slice refer to its points, following indices
+    /// refer to the points of the second slice. Panics if the index is out of bounds.
     #[inline(always)]
-    fn get(&self, index: usize) -> &Point<T> {
+    pub fn get(&self, index: usize) -> &dyn Point<T> {
         let first_slice_len = self.0.len();
         if index < first_slice_len {
             &self.0[index]
@@ -193,6 +558,22 @@ where
             &self.1[index - first_slice_len]
         }
     }
+
+    /// Get a copy of the coordinates of the point at a given index across both slices of points.
+    ///
+    /// This is the same as `get`, but without going through a trait object: the coordinates are
+    /// read with static dispatch, which the hot loops of the triangulation rely on.
+    #[inline(always)]
+    pub fn point(&self, index: usize) -> TriangulationPoint<T> {
+        let first_slice_len = self.0.len();
+        if index < first_slice_len {
+            let p = &self.0[index];
+            TriangulationPoint::new(p.x(), p.y())
+        } else {
+            let p = &self.1[index - first_slice_len];
+            TriangulationPoint::new(p.x(), p.y())
+        }
+    }
 }
 
 /// Generate the Delaunay triangulation of given set of points.
@@ -201,9 +582,20 @@ where
 /// list of points must have a least three entries, otherwise this function will panic. The points
 /// *needs* to be sorted by increasing `x` value. The `sort_points` function provided in this
 /// module can be used, in conjunction with `sort_by` or `sort_unstable_by`, to order your slice
-/// of points as necessary.
+/// of points as necessary. Debug builds check it with `is_sorted_for_triangulation`, and panic if
+/// the points aren't sorted. Points with a `NaN` or infinite coordinate are refused with
+/// `TriangulateError::NonFiniteCoordinate`.
 ///
-/// The returned triangles are indices into the input slice of points.
+/// The returned triangles are indices into the input slice of points. If all the points are
+/// coincident, on a single horizontal or vertical line, or if there are only three of them and
+/// they are collinear, there is no triangle to make, and `TriangulateError::Degenerate` is
+/// returned.
+///
+/// The output only depends on the input: a given slice of points always yields the same triangles,
+/// in the same order. The computation relies solely on IEEE 754 arithmetic, which the compiler
+/// never reorders or fuses regardless of the optimization level, so the output is reproducible
+/// across builds and platforms. The only exception are targets performing floating point math with
+/// x87 extended precision (such as `i586`), whose intermediate rounding differs.
 ///
 /// Example:
 ///
@@ -230,158 +622,1572 @@ where
 /// ```
 pub fn triangulate<T, P>(points: &[P]) -> Result<Vec<Triangle>>
 where
-    T: FloatCore,
+    T: Coordinate,
     P: Point<T>,
 {
-    // Make sure we have enough points to do a triangulation.
-    let points_count = points.len();
-    if points_count < 3 {
-        return Err(TriangulateError::NotEnoughPoints);
-    }
-
-    // Compute a constant we'll need later.
-    let half = T::from(0.5).unwrap();
-    let two = T::from(2.0).unwrap();
-
-    // Find the bounds of the space that contains our points.
-    let (min_point, max_point) = points.iter().fold(
-        (
-            TriangulationPoint::<T>::infinity(),
-            TriangulationPoint::<T>::neg_infinity(),
-        ),
-        |acc, p| {
-            (
-                TriangulationPoint::<T>::new(acc.0.x().min(p.x()), acc.0.y().min(p.y())),
-                TriangulationPoint::<T>::new(acc.1.x().max(p.x()), acc.1.y().max(p.y())),
-            )
-        },
-    );
-    let delta_point =
-        TriangulationPoint::new(max_point.x() - min_point.x(), max_point.y() - min_point.y());
-    let delta_max = delta_point.x.max(delta_point.y);
-    let mid_point = TriangulationPoint::new(
-        (max_point.x() + min_point.x()) * half,
-        (max_point.y() + min_point.y()) * half,
-    );
-
-    // Compute the supertriangle, which encompasses all the input points.
-    let supertriangle = [
-        TriangulationPoint::<T>::new(mid_point.x - two * delta_max, mid_point.y - delta_max),
-        TriangulationPoint::<T>::new(mid_point.x, mid_point.y + two * delta_max),
-        TriangulationPoint::<T>::new(mid_point.x + two * delta_max, mid_point.y - delta_max),
-    ];
+    let mut triangles = Vec::new();
+    triangulate_into(points, &mut triangles)?;
+    Ok(triangles)
+}
 
-    // Make an iterable slice of our points and the supertriangle.
-    let all_points = TwoPointsSlices::new(points, &supertriangle);
+/// Generate the Delaunay triangulation of given set of points, into an existing vector.
+///
+/// This behaves like `triangulate`, but the triangles are written to `out`, which is cleared
+/// first. As its allocation is reused, triangulating point sets of similar sizes over and over
+/// doesn't allocate the output anymore after the first time. If an error is returned, `out` is
+/// left empty.
+///
+/// Example:
+///
+/// ```rust
+/// use rtriangulate::{TriangulationPoint, Triangle, triangulate_into};
+///
+/// let points = [
+///     TriangulationPoint::new(10.0, 50.0),
+///     TriangulationPoint::new(25.0, 40.0),
+///     TriangulationPoint::new(30.0, 40.0)
+/// ];
+/// let mut triangles = vec![Triangle(0, 0, 0); 20];
+/// triangulate_into(&points, &mut triangles).unwrap();
+/// assert_eq!(triangles, [Triangle(1, 0, 2)]);
+/// assert!(triangles.capacity() >= 20);
+/// ```
+pub fn triangulate_into<T, P>(points: &[P], out: &mut Vec<Triangle>) -> Result<()>
+where
+    T: Coordinate,
+    P: Point<T>,
+{
+    out.clear();
+    out.reserve(2 * points.len() + 3);
+    triangulate_impl(points, out, &TriangulateOptions::default(), |_, _| {})
+}
 
-    // The list of triangles we're gonna fill, initialized with the super-triangle.
-    let mut triangles = vec![Triangle(points_count, points_count + 1, points_count + 2)];
+/// Generate the Delaunay triangulation of at most `N` points, into a caller-provided slice.
+///
+/// This behaves like `triangulate`, but without any heap allocation: the triangles are written to
+/// the front of `out`, and their count is returned. The scratch buffers of the triangulation are
+/// arrays on the stack, sized from `N`, which take about `190 * N` bytes: `N` should thus be kept
+/// small enough for the stack of the calling thread. If `points` has more than `N` points,
+/// `TriangulateError::TooManyPoints` is returned.
+///
+/// A triangulation of `n` points, `h` of which are on the convex hull, has `2n - 2 - h`
+/// triangles. As there are at least three hull points, an `out` slice of `2n - 5` triangles is
+/// always enough, and `2 * N` triangles is enough for any set of `N` points. If `out` is too small
+/// to hold the triangles, `TriangulateError::OutputTooSmall` is returned. Whenever an error is
+/// returned, the contents of `out` are unspecified.
+///
+/// With the `debug_validate` feature, the validation of the result still allocates.
+///
+/// Example:
+///
+/// ```rust
+/// use rtriangulate::{TriangulationPoint, Triangle, triangulate_fixed};
+///
+/// let points = [
+///     TriangulationPoint::new(10.0, 50.0),
+///     TriangulationPoint::new(25.0, 40.0),
+///     TriangulationPoint::new(30.0, 40.0)
+/// ];
+/// let mut out = [Triangle(0, 0, 0); 32];
+/// let count = triangulate_fixed::<_, _, 16>(&points, &mut out).unwrap();
+/// assert_eq!(out[..count], [Triangle(1, 0, 2)]);
+/// ```
+pub fn triangulate_fixed<T, P, const N: usize>(points: &[P], out: &mut [Triangle]) -> Result<usize>
+where
+    T: Coordinate,
+    P: Point<T>,
+{
+    const NO_EDGE: Edge = Edge(0, 0);
+    const NO_EDGES: [Edge; 7] = [NO_EDGE; 7];
 
-    // Include each of the input point into the mesh.
-    let mut edges = Vec::<Edge>::with_capacity(18);
-    let mut to_remove = Vec::<usize>::with_capacity(10);
-    for i in 0..points_count {
-        triangles.retain(|t| {
-            if in_circumcircle(
-                all_points.get(i),
-                all_points.get(t.0),
-                all_points.get(t.1),
-                all_points.get(t.2),
-            ) {
-                edges.extend_from_slice(&[Edge(t.0, t.1), Edge(t.1, t.2), Edge(t.2, t.0)]);
-                false
-            } else {
-                true
-            }
-        });
+    if points.len() > N {
+        return Err(TriangulateError::TooManyPoints);
+    }
 
-        // Remove duplicate edges (both pairs).
-        let edges_count = edges.len();
-        for (j, e1) in edges.iter().enumerate().rev().skip(1) {
-            for (k, e2) in edges.iter().enumerate().rev().take(edges_count - j - 1) {
-                if e1 == e2 {
-                    to_remove.extend_from_slice(&[j, k]);
-                    break;
-                }
-            }
-        }
-        to_remove.sort();
-        to_remove.dedup();
-        for j in to_remove.iter().rev() {
-            edges.remove(*j);
-        }
-        to_remove.clear();
+    // With the supertriangle, there are at most 2N + 1 triangles while building, and a cavity
+    // has at most three edges per triangle.
+    let mut triangle_storage = [[Triangle(0, 0, 0); 3]; N];
+    let mut edge_storage = [NO_EDGES; N];
+    let mut to_remove_storage = [[false; 7]; N];
+    let mut triangles = FixedBuffer::new(triangle_storage.as_flattened_mut());
+    let mut edges = FixedBuffer::new(edge_storage.as_flattened_mut());
+    let mut to_remove = FixedBuffer::new(to_remove_storage.as_flattened_mut());
+    triangulate_buffers(
+        points,
+        &mut triangles,
+        &mut edges,
+        &mut to_remove,
+        &TriangulateOptions::default(),
+        None,
+        |_, _| {},
+    )?;
 
-        // Form new triangles from the remaining edges. Edges are added in clockwise order.
-        triangles.extend(edges.iter().map(|e| Triangle(e.0, e.1, i)));
-        edges.clear();
+    if triangles.overflowed() || edges.overflowed() || to_remove.overflowed() {
+        return Err(TriangulateError::OutputTooSmall);
     }
+    let count = triangles.len();
+    if out.len() < count {
+        return Err(TriangulateError::OutputTooSmall);
+    }
+    out[..count].copy_from_slice(&triangles);
+    Ok(count)
+}
 
-    // Remove triangles with supertriangle vertices
-    triangles.retain(|t| t.0 < points_count && t.1 < points_count && t.2 < points_count);
-
+/// Generate the Delaunay triangulation of given set of points, allocating with the given allocator.
+///
+/// This behaves like `triangulate`, but the returned vector, along with the scratch buffers used
+/// during the triangulation, are allocated with `alloc`, such as a per-frame arena. This is only
+/// available with the `allocator-api` feature, which requires a nightly compiler.
+///
+/// Example:
+///
+/// ```rust
+/// #![feature(allocator_api)]
+///
+/// use rtriangulate::{TriangulationPoint, Triangle, triangulate_in};
+/// use std::alloc::Global;
+///
+/// let points = [
+///     TriangulationPoint::new(10.0, 50.0),
+///     TriangulationPoint::new(25.0, 40.0),
+///     TriangulationPoint::new(30.0, 40.0)
+/// ];
+/// let triangles = triangulate_in(&points, Global).unwrap();
+/// assert_eq!(*triangles, [Triangle(1, 0, 2)]);
+/// ```
+#[cfg(feature = "allocator-api")]
+pub fn triangulate_in<T, P, A>(points: &[P], alloc: A) -> Result<Vec<Triangle, A>>
+where
+    T: Coordinate,
+    P: Point<T>,
+    A: Allocator + Clone,
+{
+    let mut triangles = Vec::with_capacity_in(2 * points.len() + 3, alloc.clone());
+    let mut edges = Vec::with_capacity_in(18, alloc.clone());
+    let mut to_remove = Vec::with_capacity_in(18, alloc);
+    triangulate_buffers(
+        points,
+        &mut triangles,
+        &mut edges,
+        &mut to_remove,
+        &TriangulateOptions::default(),
+        None,
+        |_, _| {},
+    )?;
     Ok(triangles)
 }
 
-/// Returns true if the point lies inside (or on the edge of) the circumcircle made from the
-/// triangle made off of points t0, t1, and t2.
-#[inline(always)]
-fn in_circumcircle<T>(point: &Point<T>, t0: &Point<T>, t1: &Point<T>, t2: &Point<T>) -> bool
+/// Generate the Delaunay triangulation of given set of points, with a hint of the number of
+/// triangles to expect.
+///
+/// This behaves exactly like `triangulate`, but pre-allocates room for `expected_triangles`
+/// triangles in the vector the triangulation is built into, which avoids repeated reallocations
+/// when the approximate output size is known. The scratch buffers holding the boundary of the
+/// cavity made by every insertion are sized from the same hint. The hint only affects
+/// performance: any value, including zero, gives the same result. A triangulation of `n` points
+/// has at most `2n` triangles; `triangulate` reserves room for `2n + 3` of them, as the mesh it
+/// builds still has the triangles touching the supertriangle until the end, and is at most that
+/// large.
+///
+/// Example:
+///
+/// ```rust
+/// use rtriangulate::{TriangulationPoint, Triangle, triangulate_with_capacity};
+///
+/// let points = [
+///     TriangulationPoint::new(10.0, 50.0),
+///     TriangulationPoint::new(25.0, 40.0),
+///     TriangulationPoint::new(30.0, 40.0)
+/// ];
+/// let triangles = triangulate_with_capacity(&points, 1).unwrap();
+/// assert_eq!(triangles, [Triangle(1, 0, 2)]);
+/// ```
+pub fn triangulate_with_capacity<T, P>(
+    points: &[P],
+    expected_triangles: usize,
+) -> Result<Vec<Triangle>>
 where
-    T: FloatCore,
+    T: Coordinate,
+    P: Point<T>,
 {
-    // Handle coincident points in the input triangle.
-    if (t0.y() - t1.y()).abs() < T::epsilon() && (t1.y() - t2.y()).abs() < T::epsilon() {
-        return false;
-    }
-
-    let half = T::from(0.5).unwrap();
-
-    // Compute the center of the triangle's circumcircle.
-    let (circ_x, circ_y) = if (t1.y() - t0.y()).abs() < T::epsilon() {
-        let mid = T::zero() - (t2.x() - t1.x()) / (t2.y() - t1.y());
-        let mid_point = TriangulationPoint::new((t1.x() + t2.x()) * half, (t1.y() + t2.y()) * half);
-        let x = (t1.x() + t0.x()) * half;
-        (x, mid * (x - mid_point.x) + mid_point.y)
-    } else if (t2.y() - t1.y()).abs() < T::epsilon() {
-        let mid = T::zero() - (t1.x() - t0.x()) / (t1.y() - t0.y());
-        let mid_point = TriangulationPoint::new((t0.x() + t1.x()) * half, (t0.y() + t1.y()) * half);
-        let x = (t2.x() + t1.x()) * half;
-        (x, mid * (x - mid_point.x) + mid_point.y)
-    } else {
-        let mid1 = T::zero() - (t1.x() - t0.x()) / (t1.y() - t0.y());
-        let mid2 = T::zero() - (t2.x() - t1.x()) / (t2.y() - t1.y());
-        let mid_point1 =
-            TriangulationPoint::new((t0.x() + t1.x()) * half, (t0.y() + t1.y()) * half);
-        let mid_point2 =
-            TriangulationPoint::new((t1.x() + t2.x()) * half, (t1.y() + t2.y()) * half);
-        let x = (mid1 * mid_point1.x - mid2 * mid_point2.x + mid_point2.y - mid_point1.y)
-            / (mid1 - mid2);
-        (x, mid1 * (x - mid_point1.x) + mid_point1.y)
-    };
+    // For points in general position, the cavity of an insertion has a handful of triangles on
+    // average, and the largest one grows about as the logarithm of the number of triangles: with
+    // uniformly scattered points, it is around 7 triangles for 100 points, 12 for 1000, and 20 for
+    // 10000. Twice the binary logarithm covers that, while the buffers still grow on the rare
+    // larger cavities. Its boundary has at most three edges per triangle.
+    let largest_cavity = 2 * (usize::BITS - expected_triangles.leading_zeros()) as usize;
+    let scratch = (3 * largest_cavity).max(18);
 
-    // Check the radius of the circumcircle against the point's distance from its center.
-    let circumcircle_radius_sq = (t1.x() - circ_x).powi(2) + (t1.y() - circ_y).powi(2);
-    let point_distance_sq = (point.x() - circ_x).powi(2) + (point.y() - circ_y).powi(2);
-
-    point_distance_sq <= circumcircle_radius_sq
+    let mut triangles = Vec::with_capacity(expected_triangles + 3);
+    let mut edges = Vec::with_capacity(scratch);
+    let mut to_remove = Vec::with_capacity(scratch);
+    triangulate_buffers(
+        points,
+        &mut triangles,
+        &mut edges,
+        &mut to_remove,
+        &TriangulateOptions::default(),
+        None,
+        |_, _| {},
+    )?;
+    Ok(triangles)
 }
 
-#[cfg(test)]
-mod tests {
-    use super::{sort_points, triangulate, Triangle, TriangulationPoint};
-
-    #[test]
-    fn test_simple() {
+/// Generate the Delaunay triangulation of given set of points, with custom options.
+///
+/// This behaves like `triangulate`, with its behavior tweaked as described by the given
+/// `TriangulateOptions`.
+///
+/// Example:
+///
+/// ```rust
+/// use rtriangulate::{TriangulationPoint, Triangle, TriangulateOptions, triangulate_with};
+///
+/// let points = [
+///     TriangulationPoint::new(10.0f32, 50.0),
+///     TriangulationPoint::new(25.0, 40.0),
+///     TriangulationPoint::new(30.0, 40.0)
+/// ];
+/// let options = TriangulateOptions {
+///     widen_accumulate: true,
+///     drop_slivers_below_angle: Some(1.0f32.to_radians()),
+///     ..Default::default()
+/// };
+/// let triangles = triangulate_with(&points, &options).unwrap();
+/// assert_eq!(triangles, [Triangle(1, 0, 2)]);
+/// ```
+pub fn triangulate_with<T, P>(
+    points: &[P],
+    options: &TriangulateOptions<T>,
+) -> Result<Vec<Triangle>>
+where
+    T: Coordinate,
+    P: Point<T>,
+{
+    let mut triangles = Vec::with_capacity(2 * points.len() + 3);
+    triangulate_impl(points, &mut triangles, options, |_, _| {})?;
+    Ok(triangles)
+}
+
+/// Generate the Delaunay triangulation of given set of points, along with extra sentinel points.
+///
+/// The `extra` points are triangulated along with the input `points`, as if they were appended
+/// to them: the indices from `0` to `points.len()` refer to the input points, and the following
+/// ones, up to `points.len() + extra.len()`, to the extra points. This is the same mechanism as
+/// the one `triangulate` uses for the vertices of the supertriangle, which can be used for ghost
+/// points around the input, such as for periodic boundaries.
+///
+/// If `keep_extra_triangles` is false, the triangles using an extra point are removed from the
+/// output, as `triangulate` does with the ones using the supertriangle. The extra points then
+/// only shape the triangles between the input points. Otherwise, all the triangles are kept.
+///
+/// The extra points usually lie around the input ones, so the points and the extra points don't
+/// need to be sorted together: a sorted copy of all of them is triangulated, and the triangles are
+/// mapped back to the indices above.
+///
+/// Example:
+///
+/// ```rust
+/// use rtriangulate::{TriangulationPoint, Triangle, triangulate_with_extra};
+///
+/// let points = [
+///     TriangulationPoint::new(10.0, 10.0),
+///     TriangulationPoint::new(15.0, 25.0),
+///     TriangulationPoint::new(25.0, 15.0)
+/// ];
+/// let extra = [TriangulationPoint::new(30.0, 25.0)];
+/// assert_eq!(
+///     triangulate_with_extra(&points, &extra, true).unwrap(),
+///     [Triangle(0, 1, 2), Triangle(2, 1, 3)]
+/// );
+/// assert_eq!(
+///     triangulate_with_extra(&points, &extra, false).unwrap(),
+///     [Triangle(0, 1, 2)]
+/// );
+/// ```
+pub fn triangulate_with_extra<T, P>(
+    points: &[P],
+    extra: &[TriangulationPoint<T>],
+    keep_extra_triangles: bool,
+) -> Result<Vec<Triangle>>
+where
+    T: Coordinate,
+    P: Point<T>,
+{
+    let points_count = points.len();
+    let all_points: Vec<TriangulationPoint<T>> = points
+        .iter()
+        .map(|p| TriangulationPoint::new(p.x(), p.y()))
+        .chain(extra.iter().cloned())
+        .collect();
+
+    // The sort is stable, so an extra point identical to an input one stays after it.
+    let mut order: Vec<usize> = (0..all_points.len()).collect();
+    order.sort_by(|&a, &b| sort_points(&all_points[a], &all_points[b]));
+    let sorted: Vec<TriangulationPoint<T>> = order.iter().map(|&i| all_points[i]).collect();
+
+    let mut triangles = Vec::with_capacity(2 * all_points.len() + 3);
+    triangulate_impl(
+        &sorted,
+        &mut triangles,
+        &TriangulateOptions::default(),
+        |_, _| {},
+    )?;
+    for t in &mut triangles {
+        *t = Triangle(order[t.0], order[t.1], order[t.2]);
+    }
+    if !keep_extra_triangles {
+        triangles.retain(|t| t.0 < points_count && t.1 < points_count && t.2 < points_count);
+    }
+    Ok(triangles)
+}
+
+/// Generate the Delaunay triangulation of given set of points, starting from a given
+/// supertriangle.
+///
+/// `triangulate` starts from a supertriangle computed from the bounds of the points, whose
+/// vertices can be part of the circumcircles of the triangles along the hull, and so change the
+/// output there. Using the same supertriangle for several sets of points makes them all be
+/// triangulated in the same frame instead, whatever their bounds: a triangle whose circumcircle
+/// only contains points present in each set is found in all of their triangulations.
+///
+/// The supertriangle can be given in any order, but every point must be strictly inside of it:
+/// otherwise, `TriangulateError::OutsideSupertriangle` is returned. A larger supertriangle makes
+/// the hull of the output more complete. As with `triangulate`, the points must be sorted.
+///
+/// Example:
+///
+/// ```rust
+/// use rtriangulate::{TriangulationPoint, Triangle, triangulate_with_supertriangle};
+///
+/// let points = [
+///     TriangulationPoint::new(10.0, 50.0),
+///     TriangulationPoint::new(25.0, 40.0),
+///     TriangulationPoint::new(30.0, 40.0)
+/// ];
+/// let supertriangle = [
+///     TriangulationPoint::new(-1000.0, -1000.0),
+///     TriangulationPoint::new(0.0, 1000.0),
+///     TriangulationPoint::new(1000.0, -1000.0)
+/// ];
+/// assert_eq!(
+///     triangulate_with_supertriangle(&points, supertriangle).unwrap(),
+///     [Triangle(1, 0, 2)]
+/// );
+/// ```
+pub fn triangulate_with_supertriangle<T, P>(
+    points: &[P],
+    supertriangle: [TriangulationPoint<T>; 3],
+) -> Result<Vec<Triangle>>
+where
+    T: Coordinate,
+    P: Point<T>,
+{
+    let cross = |a: &TriangulationPoint<T>, b: &TriangulationPoint<T>, x: T, y: T| {
+        (b.x - a.x) * (y - a.y) - (b.y - a.y) * (x - a.x)
+    };
+
+    // The triangles are built clockwise from the edges of the supertriangle.
+    let [a, b, c] = supertriangle;
+    let supertriangle = match cross(&a, &b, c.x, c.y) {
+        area if area < T::zero() => [a, b, c],
+        area if area > T::zero() => [a, c, b],
+        _ => return Err(TriangulateError::OutsideSupertriangle),
+    };
+    let [a, b, c] = supertriangle;
+    let inside = |p: &P| {
+        cross(&a, &b, p.x(), p.y()) < T::zero()
+            && cross(&b, &c, p.x(), p.y()) < T::zero()
+            && cross(&c, &a, p.x(), p.y()) < T::zero()
+    };
+    if !points.iter().all(inside) {
+        return Err(TriangulateError::OutsideSupertriangle);
+    }
+
+    let mut triangles = Vec::with_capacity(2 * points.len() + 3);
+    let mut edges = Vec::with_capacity(18);
+    let mut to_remove = Vec::with_capacity(18);
+    triangulate_buffers(
+        points,
+        &mut triangles,
+        &mut edges,
+        &mut to_remove,
+        &TriangulateOptions::default(),
+        Some(supertriangle),
+        |_, _| {},
+    )?;
+    Ok(triangles)
+}
+
+/// Generate the Delaunay triangulation of given set of points, keeping a snapshot of the mesh
+/// after the insertion of every point.
+///
+/// This returns one list of triangles per input point: the `i`-th one is the mesh right after the
+/// `i`-th point was inserted, and the last one is the final triangulation. It is meant to observe
+/// how the Bowyer-Watson algorithm builds the mesh, such as to debug it or to animate it for
+/// teaching purposes. As all the snapshots are kept, memory grows quadratically with the number of
+/// points, which makes it unsuitable for production use.
+///
+/// If `keep_supertriangle` is true, the snapshots include the triangles made with the vertices of
+/// the supertriangle, whose indices come right after the input points (`points.len()`,
+/// `points.len() + 1` and `points.len() + 2`). Otherwise, only the triangles between input points
+/// are kept, as in the output of `triangulate`.
+///
+/// Example:
+///
+/// ```rust
+/// use rtriangulate::{TriangulationPoint, Triangle, triangulate_steps};
+///
+/// let points = [
+///     TriangulationPoint::new(10.0, 50.0),
+///     TriangulationPoint::new(25.0, 40.0),
+///     TriangulationPoint::new(30.0, 40.0)
+/// ];
+/// let steps = triangulate_steps(&points, false).unwrap();
+/// assert_eq!(steps, [vec![], vec![], vec![Triangle(1, 0, 2)]]);
+/// ```
+pub fn triangulate_steps<T, P>(points: &[P], keep_supertriangle: bool) -> Result<Vec<Vec<Triangle>>>
+where
+    T: Coordinate,
+    P: Point<T>,
+{
+    let points_count = points.len();
+    let mut steps = Vec::with_capacity(points_count);
+    let mut triangles = Vec::with_capacity(2 * points_count + 3);
+    triangulate_impl(
+        points,
+        &mut triangles,
+        &TriangulateOptions::default(),
+        |_, triangles| {
+            steps.push(
+                triangles
+                    .iter()
+                    .filter(|t| {
+                        keep_supertriangle
+                            || (t.0 < points_count && t.1 < points_count && t.2 < points_count)
+                    })
+                    .cloned()
+                    .collect(),
+            );
+        },
+    )?;
+    Ok(steps)
+}
+
+/// Generate the Delaunay triangulation of given set of points, reporting the progress after the
+/// insertion of every point.
+///
+/// This behaves like `triangulate`, but `progress` is called with the index of every point right
+/// after it was inserted, such as to update a progress bar. If it returns `ControlFlow::Break`,
+/// the triangulation stops there, and `TriangulateError::Aborted` is returned.
+///
+/// Example:
+///
+/// ```rust
+/// use rtriangulate::{TriangulateError, TriangulationPoint, Triangle, triangulate_with_progress};
+/// use std::ops::ControlFlow;
+///
+/// let points = [
+///     TriangulationPoint::new(10.0, 50.0),
+///     TriangulationPoint::new(25.0, 40.0),
+///     TriangulationPoint::new(30.0, 40.0)
+/// ];
+/// let mut inserted = 0;
+/// let triangles = triangulate_with_progress(&points, &mut |i| {
+///     inserted = i + 1;
+///     ControlFlow::Continue(())
+/// });
+/// assert_eq!(triangles.unwrap(), [Triangle(1, 0, 2)]);
+/// assert_eq!(inserted, 3);
+///
+/// let aborted = triangulate_with_progress(&points, &mut |_| ControlFlow::Break(()));
+/// assert!(matches!(aborted, Err(TriangulateError::Aborted)));
+/// ```
+pub fn triangulate_with_progress<T, P, F>(points: &[P], progress: &mut F) -> Result<Vec<Triangle>>
+where
+    T: Coordinate,
+    P: Point<T>,
+    F: FnMut(usize) -> ControlFlow<()>,
+{
+    let mut inserted = 0;
+    let mut triangles = Vec::with_capacity(2 * points.len() + 3);
+    triangulate_impl(
+        points,
+        &mut triangles,
+        &TriangulateOptions::default(),
+        |_, _| {
+            let flow = progress(inserted);
+            inserted += 1;
+            flow
+        },
+    )?;
+    Ok(triangles)
+}
+
+/// Generate the Delaunay triangulation of given set of points, along with statistics about how it
+/// was built.
+///
+/// This behaves like `triangulate`, and also returns a `TriangulateStats`, which helps understand
+/// why a mesh looks wrong, or how the precision of the coordinates affects the triangulation.
+///
+/// Example:
+///
+/// ```rust
+/// use rtriangulate::{TriangulationPoint, Triangle, triangulate_with_stats};
+///
+/// let points = [
+///     TriangulationPoint::new(10.0, 50.0),
+///     TriangulationPoint::new(25.0, 40.0),
+///     TriangulationPoint::new(30.0, 40.0)
+/// ];
+/// let (triangles, stats) = triangulate_with_stats(&points).unwrap();
+/// assert_eq!(triangles, [Triangle(1, 0, 2)]);
+/// assert_eq!(stats.cavity_sizes, [1, 2, 2]);
+/// assert_eq!(stats.max_cavity_size, 2);
+/// ```
+pub fn triangulate_with_stats<T, P>(points: &[P]) -> Result<(Vec<Triangle>, TriangulateStats)>
+where
+    T: Coordinate,
+    P: Point<T>,
+{
+    let mut stats = TriangulateStats {
+        cavity_sizes: Vec::with_capacity(points.len()),
+        ..TriangulateStats::default()
+    };
+    let mut triangles = Vec::with_capacity(2 * points.len() + 3);
+    triangulate_impl(
+        points,
+        &mut triangles,
+        &TriangulateOptions::default(),
+        |insertion, _| {
+            stats.cavity_sizes.push(insertion.cavity);
+            stats.max_cavity_size = stats.max_cavity_size.max(insertion.cavity);
+            stats.shortcuts += insertion.shortcuts;
+        },
+    )?;
+    Ok((triangles, stats))
+}
+
+/// What the insertion of a point did, as reported by `triangulate_impl`.
+struct Insertion {
+    /// The number of triangles whose circumcircle contained the point, which were removed.
+    cavity: usize,
+    /// The number of circumcircle tests decided by `is_outside_shortcut`.
+    shortcuts: usize,
+}
+
+/// What an insertion callback of `triangulate_impl` returns, which tells whether to go on.
+///
+/// Callbacks which never stop the triangulation return `()`, which makes the check free.
+trait InsertionFlow {
+    fn should_stop(self) -> bool;
+}
+
+impl InsertionFlow for () {
+    #[inline(always)]
+    fn should_stop(self) -> bool {
+        false
+    }
+}
+
+impl InsertionFlow for ControlFlow<()> {
+    #[inline(always)]
+    fn should_stop(self) -> bool {
+        self.is_break()
+    }
+}
+
+/// The actual triangulation, shared by all the public `triangulate` functions.
+///
+/// The triangles are built into `triangles`, which must be empty. As it can hold more triangles
+/// than the output while the supertriangle ones are there, it should have room for three more
+/// triangles than expected in the output.
+///
+/// After the insertion of every point, `on_insertion` is called with what the insertion did and
+/// the current triangles, including the ones made with the supertriangle vertices. If it returns
+/// `ControlFlow::Break`, the triangulation stops with `TriangulateError::Aborted`.
+fn triangulate_impl<T, P, F, C>(
+    points: &[P],
+    triangles: &mut Vec<Triangle>,
+    options: &TriangulateOptions<T>,
+    on_insertion: F,
+) -> Result<()>
+where
+    T: Coordinate,
+    P: Point<T>,
+    F: FnMut(&Insertion, &[Triangle]) -> C,
+    C: InsertionFlow,
+{
+    // The boundary of a cavity of up to 16 triangles stays on the stack, which covers nearly
+    // every insertion: larger cavities move it to the heap.
+    #[cfg(feature = "stack-scratch")]
+    const NO_EDGE: Edge = Edge(0, 0);
+    #[cfg(feature = "stack-scratch")]
+    let (mut edge_storage, mut to_remove_storage) = ([NO_EDGE; 48], [false; 48]);
+    #[cfg(feature = "stack-scratch")]
+    let (mut edges, mut to_remove) = (
+        SpillBuffer::new(&mut edge_storage),
+        SpillBuffer::new(&mut to_remove_storage),
+    );
+    #[cfg(not(feature = "stack-scratch"))]
+    let (mut edges, mut to_remove) = (
+        Vec::<Edge>::with_capacity(18),
+        Vec::<bool>::with_capacity(18),
+    );
+    triangulate_buffers(
+        points,
+        triangles,
+        &mut edges,
+        &mut to_remove,
+        options,
+        None,
+        on_insertion,
+    )
+}
+
+/// The triangulation itself, building into buffers of any allocator.
+///
+/// This behaves like `triangulate_impl`, with its scratch buffers also passed in: `edges` holds
+/// the boundary of the cavity made by every insertion, and `to_remove` the shared edges to remove
+/// from it. If a `supertriangle` is given, it must be clockwise and contain all the points, and is
+/// used instead of the one computed from the bounds of the points.
+fn triangulate_buffers<T, P, B, E, R, F, C>(
+    points: &[P],
+    triangles: &mut B,
+    edges: &mut E,
+    to_remove: &mut R,
+    options: &TriangulateOptions<T>,
+    supertriangle: Option<[TriangulationPoint<T>; 3]>,
+    mut on_insertion: F,
+) -> Result<()>
+where
+    T: Coordinate,
+    P: Point<T>,
+    B: Buffer<Triangle>,
+    E: Buffer<Edge>,
+    R: Buffer<bool>,
+    F: FnMut(&Insertion, &[Triangle]) -> C,
+    C: InsertionFlow,
+{
+    // Check the points, and compute the supertriangle which encompasses them, unless one was given.
+    let points_count = points.len();
+    let supertriangle = match supertriangle {
+        Some(supertriangle) => {
+            check_points(points)?;
+            supertriangle
+        }
+        None => bounding_supertriangle(points)?,
+    };
+    debug_assert!(
+        is_sorted_for_triangulation(points),
+        "the points must be sorted with `sort_points` before being triangulated"
+    );
+
+    // Make an iterable slice of our points and the supertriangle.
+    let all_points = ConcatPoints::new(points, &supertriangle);
+
+    // The widened circumcircle test converts the coordinates to `f64`, so check once that they
+    // all can be, rather than in every test.
+    if options.widen_accumulate
+        && !(0..points_count + 3).all(|i| {
+            let point = all_points.point(i);
+            point.x().to_f64().is_some() && point.y().to_f64().is_some()
+        })
+    {
+        return Err(TriangulateError::UnsupportedCoordinate);
+    }
+
+    // Trigonometry isn't available on `Coordinate`, so the slivers are found by comparing the
+    // squared sines of their angles against a threshold, computed in `f64`. The smallest angle of
+    // a triangle is at most 60 degrees: from a right angle on, every triangle is dropped.
+    let min_sin_sq = match options.drop_slivers_below_angle {
+        Some(min_angle) => {
+            let unsupported = || TriangulateError::UnsupportedCoordinate;
+            let half_pi = T::from_f64(std::f64::consts::FRAC_PI_2).ok_or_else(unsupported)?;
+            if min_angle >= half_pi {
+                Some(T::infinity())
+            } else {
+                let sin = min_angle.to_f64().ok_or_else(unsupported)?.sin();
+                Some(T::from_f64(sin * sin).ok_or_else(unsupported)?)
+            }
+        }
+        None => None,
+    };
+
+    // The list of triangles we're gonna fill, initialized with the super-triangle.
+    triangles.push(Triangle(points_count, points_count + 1, points_count + 2));
+
+    // Include each of the input point into the mesh.
+    for i in 0..points_count {
+        let mut insertion = Insertion {
+            cavity: 0,
+            shortcuts: 0,
+        };
+        let point = all_points.point(i);
+        triangles.retain(|t| {
+            let (t0, t1, t2) = (
+                all_points.point(t.0),
+                all_points.point(t.1),
+                all_points.point(t.2),
+            );
+            if is_outside_shortcut(&point, &t0, &t1, &t2) {
+                insertion.shortcuts += 1;
+                true
+            } else if in_computed_circumcircle(&point, &t0, &t1, &t2, options.widen_accumulate) {
+                edges.extend_from_slice(&t.edges());
+                insertion.cavity += 1;
+                false
+            } else {
+                true
+            }
+        });
+
+        remove_shared_edges(edges, to_remove);
+
+        // Form new triangles from the remaining edges. Edges are added in clockwise order.
+        triangles.extend(edges.iter().map(|e| Triangle(e.0, e.1, i)));
+        edges.clear();
+
+        if on_insertion(&insertion, triangles).should_stop() {
+            return Err(TriangulateError::Aborted);
+        }
+    }
+
+    // Remove triangles with supertriangle vertices
+    triangles.retain(|t| t.0 < points_count && t.1 < points_count && t.2 < points_count);
+
+    if options.break_cocircular_ties {
+        filter::break_cocircular_ties(points, triangles);
+    }
+    if let Some(min_sin_sq) = min_sin_sq {
+        filter::drop_slivers(points, triangles, min_sin_sq);
+    }
+
+    #[cfg(feature = "debug_validate")]
+    validate::assert_valid(points, triangles);
+
+    Ok(())
+}
+
+/// Generate the Delaunay triangulation of given set of points, and check that it covers the
+/// convex hull of the points.
+///
+/// The supertriangle which `triangulate` starts from has a fixed scale, relative to the bounds of
+/// the points. When some points are close to be collinear along the hull, the circumcircle of the
+/// thin triangles they make can grow large enough to contain a supertriangle vertex. Those
+/// triangles are then never created, and the ones replacing them are removed at the end with the
+/// supertriangle, which leaves a notch in the hull of the mesh, or even points out of it.
+///
+/// This returns, along with the triangles, a flag which is true when the mesh was found to be
+/// complete: its boundary is convex, and every point (save for duplicates) is part of a triangle.
+/// When the flag is false, the supertriangle was most likely too small for these points.
+///
+/// Example:
+///
+/// ```rust
+/// use rtriangulate::{TriangulationPoint, Triangle, triangulate_checked};
+///
+/// let points = [
+///     TriangulationPoint::new(10.0, 50.0),
+///     TriangulationPoint::new(25.0, 40.0),
+///     TriangulationPoint::new(30.0, 40.0)
+/// ];
+/// let (triangles, complete) = triangulate_checked(&points).unwrap();
+/// assert_eq!(triangles, [Triangle(1, 0, 2)]);
+/// assert!(complete);
+/// ```
+pub fn triangulate_checked<T, P>(points: &[P]) -> Result<(Vec<Triangle>, bool)>
+where
+    T: Coordinate,
+    P: Point<T>,
+{
+    let triangles = triangulate(points)?;
+    let complete = validate::covers_hull(points, &triangles);
+    Ok((triangles, complete))
+}
+
+/// Generate the Delaunay triangulation of given set of points, accepting fewer than three points.
+///
+/// Instead of failing with `TriangulateError::NotEnoughPoints`, this returns the trivial structure
+/// made by fewer than three points: `SmallResult::Empty` for no point, `SmallResult::Point` for a
+/// single one, and `SmallResult::Edge` for two of them, even when they are coincident. Otherwise,
+/// this triangulates the points as `triangulate` does, and returns its triangles in a
+/// `SmallResult::Mesh`. That variant is thus only produced for three points or more, which don't
+/// all lie on a line: degenerate points still make `TriangulateError::Degenerate` be returned.
+///
+/// Example:
+///
+/// ```rust
+/// use rtriangulate::{triangulate_lenient_small, Edge, SmallResult, Triangle, TriangulationPoint};
+///
+/// let points = [
+///     TriangulationPoint::new(10.0, 50.0),
+///     TriangulationPoint::new(25.0, 40.0),
+///     TriangulationPoint::new(30.0, 40.0)
+/// ];
+/// assert_eq!(triangulate_lenient_small(&points[..0]).unwrap(), SmallResult::Empty);
+/// assert_eq!(triangulate_lenient_small(&points[..1]).unwrap(), SmallResult::Point(0));
+/// assert_eq!(triangulate_lenient_small(&points[..2]).unwrap(), SmallResult::Edge(Edge(0, 1)));
+/// assert_eq!(
+///     triangulate_lenient_small(&points).unwrap(),
+///     SmallResult::Mesh(vec![Triangle(1, 0, 2)])
+/// );
+/// ```
+pub fn triangulate_lenient_small<T, P>(points: &[P]) -> Result<SmallResult>
+where
+    T: Coordinate,
+    P: Point<T>,
+{
+    match points.len() {
+        0 => Ok(SmallResult::Empty),
+        1 => Ok(SmallResult::Point(0)),
+        2 => Ok(SmallResult::Edge(Edge(0, 1))),
+        _ => triangulate(points).map(SmallResult::Mesh),
+    }
+}
+
+/// Generate the Delaunay triangulation of given set of points, with indices of a custom type.
+///
+/// This works like `triangulate`, but returns every triangle as an array of three indices of type
+/// `I`, such as `u16` or `u32`, to save memory or to directly match the index type of a GPU buffer.
+/// All the indices used during the triangulation, including the three which follow the input
+/// points for the supertriangle, must fit in `I`: otherwise, `TriangulateError::TooManyPoints` is
+/// returned. For instance, at most 253 points can be triangulated with `u8` indices.
+///
+/// Example:
+///
+/// ```rust
+/// use rtriangulate::{TriangulationPoint, triangulate_indexed};
+///
+/// let points = [
+///     TriangulationPoint::new(10.0, 50.0),
+///     TriangulationPoint::new(25.0, 40.0),
+///     TriangulationPoint::new(30.0, 40.0)
+/// ];
+/// assert_eq!(triangulate_indexed::<u16, _, _>(&points).unwrap(), [[1, 0, 2]]);
+/// ```
+pub fn triangulate_indexed<I, T, P>(points: &[P]) -> Result<Vec<[I; 3]>>
+where
+    I: TryFrom<usize>,
+    T: Coordinate,
+    P: Point<T>,
+{
+    let last_index = points
+        .len()
+        .checked_add(2)
+        .ok_or(TriangulateError::TooManyPoints)?;
+    if I::try_from(last_index).is_err() {
+        return Err(TriangulateError::TooManyPoints);
+    }
+
+    let index = |i: usize| I::try_from(i).map_err(|_| TriangulateError::TooManyPoints);
+    triangulate(points)?
+        .iter()
+        .map(|t| Ok([index(t.0)?, index(t.1)?, index(t.2)?]))
+        .collect()
+}
+
+/// Generate the Delaunay triangulation of given set of points, as a flat buffer of indices.
+///
+/// This is a thin wrapper over `triangulate_indexed`, which returns every triangle as three
+/// consecutive `u32` indices (`[i0, j0, k0, i1, j1, k1, ...]`) into the input slice of points. The
+/// buffer can directly be used as an index buffer, such as the element indices of a WebGL draw
+/// call. If the points can't all be addressed by an `u32` index, `TriangulateError::TooManyPoints`
+/// is returned.
+///
+/// Example:
+///
+/// ```rust
+/// use rtriangulate::{TriangulationPoint, triangulate_flat};
+///
+/// let points = [
+///     TriangulationPoint::new(10.0, 50.0),
+///     TriangulationPoint::new(25.0, 40.0),
+///     TriangulationPoint::new(30.0, 40.0)
+/// ];
+/// assert_eq!(triangulate_flat(&points).unwrap(), [1, 0, 2]);
+/// ```
+pub fn triangulate_flat<T, P>(points: &[P]) -> Result<Vec<u32>>
+where
+    T: Coordinate,
+    P: Point<T>,
+{
+    let triangles = triangulate_indexed::<u32, T, P>(points)?;
+    let mut indices = Vec::with_capacity(triangles.len() * 3);
+    for t in &triangles {
+        indices.extend_from_slice(t);
+    }
+    Ok(indices)
+}
+
+/// Generate the Delaunay triangulation of the `xy` projection of a set of 3D points.
+///
+/// The points must be sorted the way `triangulate` expects, which brings the points sharing the
+/// same `xy` coordinates next to each other. Of those, only the one with the largest `z`, as
+/// computed by the given function, is kept for the triangulation (the first one wins ties), so that
+/// no degenerate overlap is produced. This is typically what building a height field from
+/// scattered 3D scans requires.
+///
+/// Returns the indices of the points which were kept, in ascending order, along with the
+/// triangles. The triangles directly index into the input slice of points.
+///
+/// Example:
+///
+/// ```rust
+/// use rtriangulate::{Point, Triangle, triangulate_projected};
+///
+/// struct Point3(f64, f64, f64);
+/// impl Point<f64> for Point3 {
+///     fn x(&self) -> f64 { self.0 }
+///     fn y(&self) -> f64 { self.1 }
+/// }
+///
+/// let points = [
+///     Point3(10.0, 50.0, 1.0),
+///     Point3(25.0, 40.0, 1.0),
+///     Point3(25.0, 40.0, 3.0),
+///     Point3(30.0, 40.0, 1.0),
+/// ];
+/// let (kept, triangles) = triangulate_projected(&points, |p| p.2).unwrap();
+/// assert_eq!(kept, [0, 2, 3]);
+/// assert_eq!(triangles, [Triangle(2, 0, 3)]);
+/// ```
+pub fn triangulate_projected<T, P, F>(points: &[P], z: F) -> Result<(Vec<usize>, Vec<Triangle>)>
+where
+    T: Coordinate,
+    P: Point<T>,
+    F: Fn(&P) -> T,
+{
+    let mut kept: Vec<usize> = Vec::with_capacity(points.len());
+    for (i, point) in points.iter().enumerate() {
+        match kept.last_mut() {
+            Some(last) if points[*last].x() == point.x() && points[*last].y() == point.y() => {
+                if z(point) > z(&points[*last]) {
+                    *last = i;
+                }
+            }
+            _ => kept.push(i),
+        }
+    }
+
+    let projected: Vec<TriangulationPoint<T>> = kept
+        .iter()
+        .map(|&i| TriangulationPoint::new(points[i].x(), points[i].y()))
+        .collect();
+    let mut triangles = triangulate(&projected)?;
+    for t in &mut triangles {
+        *t = Triangle(kept[t.0], kept[t.1], kept[t.2]);
+    }
+    Ok((kept, triangles))
+}
+
+/// Generate the Delaunay triangulation of given set of points, each tagged with some data.
+///
+/// Only the point of every `(point, data)` pair is triangulated, and the triangles index into the
+/// slice of pairs, so that the data of their vertices can be looked up directly, without keeping
+/// the points and their data in separate slices. The pairs must be sorted by their point, as
+/// `triangulate` requires.
+///
+/// Example:
+///
+/// ```rust
+/// use rtriangulate::{TriangulationPoint, Triangle, triangulate_tagged};
+///
+/// let points = [
+///     (TriangulationPoint::new(10.0, 50.0), "a"),
+///     (TriangulationPoint::new(25.0, 40.0), "b"),
+///     (TriangulationPoint::new(30.0, 40.0), "c")
+/// ];
+/// let triangles = triangulate_tagged(&points).unwrap();
+/// assert_eq!(triangles, [Triangle(1, 0, 2)]);
+/// assert_eq!(points[triangles[0].0].1, "b");
+/// ```
+pub fn triangulate_tagged<T, D, P>(points: &[(P, D)]) -> Result<Vec<Triangle>>
+where
+    T: Coordinate,
+    P: Point<T>,
+{
+    let untagged: Vec<TriangulationPoint<T>> = points
+        .iter()
+        .map(|tagged| TriangulationPoint::new(tagged.0.x(), tagged.0.y()))
+        .collect();
+    triangulate(&untagged)
+}
+
+/// Generate the Delaunay triangulation of a subset of the given points.
+///
+/// Only the points whose index is in `subset` are triangulated, and the triangles directly index
+/// into the input slice of points. The subset doesn't need to be in any particular order, as the
+/// selected points are sorted (with `sort_points`) before being triangulated: only the subset,
+/// not the whole slice, is copied and sorted. Every index of `subset` must be in bounds, and
+/// should only appear once, as a duplicated index makes a duplicated point.
+///
+/// Example:
+///
+/// ```rust
+/// use rtriangulate::{TriangulationPoint, Triangle, triangulate_subset};
+///
+/// let points = [
+///     TriangulationPoint::new(10.0, 50.0),
+///     TriangulationPoint::new(20.0, 10.0),
+///     TriangulationPoint::new(25.0, 40.0),
+///     TriangulationPoint::new(30.0, 40.0)
+/// ];
+/// assert_eq!(triangulate_subset(&points, &[3, 0, 2]).unwrap(), [Triangle(2, 0, 3)]);
+/// ```
+pub fn triangulate_subset<T, P>(points: &[P], subset: &[usize]) -> Result<Vec<Triangle>>
+where
+    T: Coordinate,
+    P: Point<T>,
+{
+    let mut selected = subset.to_vec();
+    selected.sort_by(|&a, &b| sort_points(&points[a], &points[b]));
+
+    let gathered: Vec<TriangulationPoint<T>> = selected
+        .iter()
+        .map(|&i| TriangulationPoint::new(points[i].x(), points[i].y()))
+        .collect();
+    let mut triangles = triangulate(&gathered)?;
+    for t in &mut triangles {
+        *t = Triangle(selected[t.0], selected[t.1], selected[t.2]);
+    }
+    Ok(triangles)
+}
+
+/// Generate the Delaunay triangulation of the given points, in a linearly transformed space.
+///
+/// Every point `(x, y)` is mapped to `(a * x + b * y, c * x + d * y)`, with the transform given
+/// as `[[a, b], [c, d]]`, and the mapped points are triangulated: the triangles are Delaunay for
+/// the metric of the transformed space, which makes for anisotropic triangles in the original one.
+/// Stretching `x` by 3, for instance, favours triangles three times as wide as they are tall.
+///
+/// The triangles index into the input slice of points, and are clockwise in the original space.
+/// As the transform may not preserve the order of the points, they don't need to be sorted. The
+/// transform must be invertible: a zero determinant would map all the points to a line, and makes
+/// `TriangulateError::Degenerate` be returned.
+///
+/// Example:
+///
+/// ```rust
+/// use rtriangulate::{triangulate, triangulate_transformed, Triangle, TriangulationPoint};
+///
+/// let points = [
+///     TriangulationPoint::new(0.0, 0.0),
+///     TriangulationPoint::new(1.0, -1.2),
+///     TriangulationPoint::new(1.0, 1.2),
+///     TriangulationPoint::new(2.0, 0.0)
+/// ];
+/// assert_eq!(triangulate(&points).unwrap(), [Triangle(1, 0, 3), Triangle(0, 2, 3)]);
+///
+/// // Squashing the points vertically flips the diagonal of the quad.
+/// let squash = [[1.0, 0.0], [0.0, 0.5]];
+/// assert_eq!(
+///     triangulate_transformed(&points, squash).unwrap(),
+///     [Triangle(1, 0, 2), Triangle(1, 2, 3)]
+/// );
+/// ```
+pub fn triangulate_transformed<T, P>(points: &[P], transform: [[T; 2]; 2]) -> Result<Vec<Triangle>>
+where
+    T: Coordinate,
+    P: Point<T>,
+{
+    let [[a, b], [c, d]] = transform;
+    let determinant = a * d - b * c;
+    if determinant == T::zero() {
+        return Err(TriangulateError::Degenerate);
+    }
+
+    let transformed: Vec<TriangulationPoint<T>> = points
+        .iter()
+        .map(|p| TriangulationPoint::new(a * p.x() + b * p.y(), c * p.x() + d * p.y()))
+        .collect();
+    let mut order: Vec<usize> = (0..points.len()).collect();
+    order.sort_by(|&i, &j| sort_points(&transformed[i], &transformed[j]));
+    let sorted: Vec<TriangulationPoint<T>> = order.iter().map(|&i| transformed[i]).collect();
+
+    // A transform with a negative determinant mirrors the points, and so the winding.
+    let mut triangles = triangulate(&sorted)?;
+    for t in &mut triangles {
+        *t = if determinant < T::zero() {
+            Triangle(order[t.0], order[t.2], order[t.1])
+        } else {
+            Triangle(order[t.0], order[t.1], order[t.2])
+        };
+    }
+    Ok(triangles)
+}
+
+/// Checks that there are enough points to do a triangulation, that the supertriangle vertices can
+/// be indexed after them, and that their coordinates are finite.
+fn check_points<T, P>(points: &[P]) -> Result<()>
+where
+    T: Coordinate,
+    P: Point<T>,
+{
+    let points_count = points.len();
+    if points_count < 3 {
+        return Err(TriangulateError::NotEnoughPoints);
+    }
+    if points_count.checked_add(2).is_none() {
+        return Err(TriangulateError::TooManyPoints);
+    }
+    if !points
+        .iter()
+        .all(|p| p.x().is_finite() && p.y().is_finite())
+    {
+        return Err(TriangulateError::NonFiniteCoordinate);
+    }
+    Ok(())
+}
+
+/// Checks that the points can be triangulated, and computes the clockwise supertriangle which
+/// encompasses all of them.
+fn bounding_supertriangle<T, P>(points: &[P]) -> Result<[TriangulationPoint<T>; 3]>
+where
+    T: Coordinate,
+    P: Point<T>,
+{
+    check_points(points)?;
+
+    // Compute a constant we'll need later.
+    let half = T::from_f64(0.5).ok_or(TriangulateError::UnsupportedCoordinate)?;
+    let two = T::from_f64(2.0).ok_or(TriangulateError::UnsupportedCoordinate)?;
+
+    // Find the bounds of the space that contains our points.
+    let (min_point, max_point) = points.iter().fold(
+        (
+            TriangulationPoint::<T>::infinity(),
+            TriangulationPoint::<T>::neg_infinity(),
+        ),
+        |acc, p| {
+            (
+                TriangulationPoint::<T>::new(acc.0.x().min(p.x()), acc.0.y().min(p.y())),
+                TriangulationPoint::<T>::new(acc.1.x().max(p.x()), acc.1.y().max(p.y())),
+            )
+        },
+    );
+    let delta_point =
+        TriangulationPoint::new(max_point.x() - min_point.x(), max_point.y() - min_point.y());
+    if delta_point.x == T::zero() || delta_point.y == T::zero() {
+        // All the points are on a horizontal or a vertical line (or coincident), so there is no
+        // triangle to make, or even no supertriangle to start from.
+        return Err(TriangulateError::Degenerate);
+    }
+    let delta_max = delta_point.x.max(delta_point.y);
+    if points.len() == 3 {
+        // Three collinear points would only make a flat triangle.
+        let (a, b, c) = (&points[0], &points[1], &points[2]);
+        if (b.x() - a.x()) * (c.y() - a.y()) == (b.y() - a.y()) * (c.x() - a.x()) {
+            return Err(TriangulateError::Degenerate);
+        }
+    }
+    let mid_point = TriangulationPoint::new(
+        (max_point.x() + min_point.x()) * half,
+        (max_point.y() + min_point.y()) * half,
+    );
+
+    // Compute the supertriangle, which encompasses all the input points.
+    Ok([
+        TriangulationPoint::<T>::new(mid_point.x - two * delta_max, mid_point.y - delta_max),
+        TriangulationPoint::<T>::new(mid_point.x, mid_point.y + two * delta_max),
+        TriangulationPoint::<T>::new(mid_point.x + two * delta_max, mid_point.y - delta_max),
+    ])
+}
+
+/// Removes the edges found more than once from the boundary of a cavity (every copy of them).
+///
+/// Edges are flagged first and then removed in a single ordered pass, so that the surviving edges
+/// keep their relative order. The flags are stored in `to_remove`, to reuse its allocation.
+fn remove_shared_edges<E, R>(edges: &mut E, to_remove: &mut R)
+where
+    E: Buffer<Edge>,
+    R: Buffer<bool>,
+{
+    let edges_count = edges.len();
+    to_remove.clear();
+    to_remove.resize(edges_count, false);
+    for j in 0..edges_count {
+        for k in (j + 1)..edges_count {
+            if edges[j] == edges[k] {
+                to_remove[j] = true;
+                to_remove[k] = true;
+            }
+        }
+    }
+    let mut j = 0;
+    edges.retain(|_| {
+        j += 1;
+        !to_remove[j - 1]
+    });
+}
+
+/// Returns true if the point lies inside (or on the edge of) the circumcircle made from the
+/// triangle made off of points t0, t1, and t2.
+///
+/// Points exactly on the circumcircle are always considered inside, and any comparison involving
+/// a non-finite value (as produced by collinear triangles) is always considered outside, so that
+/// borderline cases are always resolved the same way.
+///
+/// If `widen` is true, the final squared distances are computed and compared in `f64`. A value
+/// which can't be converted is taken as `NaN`, which makes the point considered outside.
+#[inline(always)]
+fn in_circumcircle<T>(
+    point: &TriangulationPoint<T>,
+    t0: &TriangulationPoint<T>,
+    t1: &TriangulationPoint<T>,
+    t2: &TriangulationPoint<T>,
+    widen: bool,
+) -> bool
+where
+    T: Coordinate,
+{
+    !is_outside_shortcut(point, t0, t1, t2) && in_computed_circumcircle(point, t0, t1, t2, widen)
+}
+
+/// Returns true if the point is known to be outside of the circumcircle of the triangle made off
+/// of points t0, t1, and t2, without computing the circumcircle.
+///
+/// This is the case when the triangle is degenerate, with its three points on a horizontal line,
+/// or when the point lies on a horizontal or vertical line through two of its vertices.
+#[inline(always)]
+fn is_outside_shortcut<T>(
+    point: &TriangulationPoint<T>,
+    t0: &TriangulationPoint<T>,
+    t1: &TriangulationPoint<T>,
+    t2: &TriangulationPoint<T>,
+) -> bool
+where
+    T: Coordinate,
+{
+    // Handle coincident points in the input triangle.
+    if (t0.y() - t1.y()).abs() < T::epsilon() && (t1.y() - t2.y()).abs() < T::epsilon() {
+        return true;
+    }
+
+    // A point on the line through two vertices, but out of the segment between them, is always
+    // outside of the circumcircle. Rounding errors can tell otherwise when the circle is large, so
+    // this is checked exactly for vertical and horizontal lines, which gridded data is full of:
+    // the point would otherwise make a flat triangle with those two vertices.
+    for &(a, b) in &[(t0, t1), (t1, t2), (t2, t0)] {
+        let beyond = |p: T, a: T, b: T| (p < a && p < b) || (p > a && p > b);
+        if (point.x() == a.x() && point.x() == b.x() && beyond(point.y(), a.y(), b.y()))
+            || (point.y() == a.y() && point.y() == b.y() && beyond(point.x(), a.x(), b.x()))
+        {
+            return true;
+        }
+    }
+
+    false
+}
+
+/// Returns true if the point lies inside (or on the edge of) the circumcircle of the triangle made
+/// off of points t0, t1, and t2, as `in_circumcircle` does, after its shortcuts were ruled out.
+#[inline(always)]
+fn in_computed_circumcircle<T>(
+    point: &TriangulationPoint<T>,
+    t0: &TriangulationPoint<T>,
+    t1: &TriangulationPoint<T>,
+    t2: &TriangulationPoint<T>,
+    widen: bool,
+) -> bool
+where
+    T: Coordinate,
+{
+    let (circ_x, circ_y) = circumcircle_center(t0, t1, t2);
+
+    // Check the radius of the circumcircle against the point's distance from its center.
+    if widen {
+        let wide = |v: T| v.to_f64().unwrap_or(f64::NAN);
+        let (circ_x, circ_y) = (wide(circ_x), wide(circ_y));
+        let circumcircle_radius_sq =
+            (wide(t1.x()) - circ_x).powi(2) + (wide(t1.y()) - circ_y).powi(2);
+        let point_distance_sq =
+            (wide(point.x()) - circ_x).powi(2) + (wide(point.y()) - circ_y).powi(2);
+
+        point_distance_sq <= circumcircle_radius_sq
+    } else {
+        let sq = |v: T| v * v;
+        let circumcircle_radius_sq = sq(t1.x() - circ_x) + sq(t1.y() - circ_y);
+        let point_distance_sq = sq(point.x() - circ_x) + sq(point.y() - circ_y);
+
+        point_distance_sq <= circumcircle_radius_sq
+    }
+}
+
+/// Computes the center of the circumcircle of the triangle made off of points t0, t1, and t2.
+///
+/// The center is made of non-finite values for degenerate triangles.
+#[inline(always)]
+fn circumcircle_center<T>(
+    t0: &TriangulationPoint<T>,
+    t1: &TriangulationPoint<T>,
+    t2: &TriangulationPoint<T>,
+) -> (T, T)
+where
+    T: Coordinate,
+{
+    // This can't report an unsupported coordinate type, so it divides instead of converting.
+    let half = T::one() / (T::one() + T::one());
+    if (t1.y() - t0.y()).abs() < T::epsilon() {
+        let mid = T::zero() - (t2.x() - t1.x()) / (t2.y() - t1.y());
+        let mid_point = TriangulationPoint::new((t1.x() + t2.x()) * half, (t1.y() + t2.y()) * half);
+        let x = (t1.x() + t0.x()) * half;
+        (x, mid * (x - mid_point.x) + mid_point.y)
+    } else if (t2.y() - t1.y()).abs() < T::epsilon() {
+        let mid = T::zero() - (t1.x() - t0.x()) / (t1.y() - t0.y());
+        let mid_point = TriangulationPoint::new((t0.x() + t1.x()) * half, (t0.y() + t1.y()) * half);
+        let x = (t2.x() + t1.x()) * half;
+        (x, mid * (x - mid_point.x) + mid_point.y)
+    } else {
+        let mid1 = T::zero() - (t1.x() - t0.x()) / (t1.y() - t0.y());
+        let mid2 = T::zero() - (t2.x() - t1.x()) / (t2.y() - t1.y());
+        let mid_point1 =
+            TriangulationPoint::new((t0.x() + t1.x()) * half, (t0.y() + t1.y()) * half);
+        let mid_point2 =
+            TriangulationPoint::new((t1.x() + t2.x()) * half, (t1.y() + t2.y()) * half);
+        let x = (mid1 * mid_point1.x - mid2 * mid_point2.x + mid_point2.y - mid_point1.y)
+            / (mid1 - mid2);
+        (x, mid1 * (x - mid_point1.x) + mid_point1.y)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cmp::Ordering;
+    use std::ops::ControlFlow;
+
+    use super::{
+        in_circumcircle, is_delaunay, is_sorted_for_triangulation, sort_points, triangulate,
+        triangulate_approx, triangulate_checked, triangulate_fixed, triangulate_flat,
+        triangulate_indexed, triangulate_into, triangulate_lawson, triangulate_lenient_small,
+        triangulate_progressive, triangulate_projected, triangulate_steps, triangulate_subset,
+        triangulate_tagged, triangulate_transformed, triangulate_with, triangulate_with_capacity,
+        triangulate_with_extra, triangulate_with_progress, triangulate_with_stats,
+        triangulate_with_supertriangle, validate, ConcatPoints, Diff, Edge, HalfEdgeMesh, MeshRef,
+        Point, SmallResult, Triangle, TriangulateError, TriangulateOptions, TriangulateStats,
+        Triangulation, TriangulationPoint, Voronoi,
+    };
+
+    #[test]
+    fn test_simple() {
         let points = [
             TriangulationPoint::new(10.0, 10.0),
             TriangulationPoint::new(15.0, 25.0),
             TriangulationPoint::new(25.0, 15.0),
         ];
-        let tris: Vec<Triangle> = triangulate(&points).unwrap();
+        let tris: Vec<Triangle> = triangulate(&points).unwrap();
+
+        assert_eq!(tris.len(), 1);
+        assert_eq!(tris[..], [Triangle(0, 1, 2)][..]);
+    }
+
+    #[test]
+    fn test_three_collinear_points() {
+        let lines = [
+            [(10.0, 10.0), (15.0, 10.0), (25.0, 10.0)],
+            [(10.0, 10.0), (10.0, 15.0), (10.0, 25.0)],
+            [(10.0, 10.0), (15.0, 25.0), (20.0, 40.0)],
+        ];
+        for line in &lines {
+            let points: Vec<_> = line
+                .iter()
+                .map(|&(x, y)| TriangulationPoint::new(x, y))
+                .collect();
+            match triangulate(&points) {
+                Err(TriangulateError::Degenerate) => {}
+                other => panic!("unexpected result for {:?}: {:?}", line, other),
+            }
+        }
+    }
+
+    #[test]
+    fn test_lenient_small() {
+        let points = [
+            TriangulationPoint::new(10.0, 10.0),
+            TriangulationPoint::new(10.0, 10.0),
+            TriangulationPoint::new(25.0, 10.0),
+            TriangulationPoint::new(30.0, 25.0),
+        ];
+        assert_eq!(
+            triangulate_lenient_small(&points[..2]).unwrap(),
+            SmallResult::Edge(Edge(0, 1))
+        );
+        match triangulate_lenient_small(&points[..3]) {
+            Err(TriangulateError::Degenerate) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        assert_eq!(
+            triangulate_lenient_small(&points).unwrap(),
+            SmallResult::Mesh(triangulate(&points).unwrap())
+        );
+    }
+
+    #[test]
+    fn test_subset() {
+        let points = complex_points();
+        let subset: Vec<usize> = (0..points.len()).rev().filter(|i| i % 3 != 1).collect();
+        let tris = triangulate_subset(&points, &subset).unwrap();
+
+        let mut sorted = subset.clone();
+        sorted.sort();
+        let gathered: Vec<_> = sorted.iter().map(|&i| points[i]).collect();
+        let expected: Vec<Triangle> = triangulate(&gathered)
+            .unwrap()
+            .into_iter()
+            .map(|t| Triangle(sorted[t.0], sorted[t.1], sorted[t.2]))
+            .collect();
+        assert_eq!(tris, expected);
+
+        match triangulate_subset(&points, &[4, 2]) {
+            Err(TriangulateError::NotEnoughPoints) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_transformed() {
+        let points = complex_points();
+        let identity = [[1.0, 0.0], [0.0, 1.0]];
+        assert_eq!(
+            triangulate_transformed(&points, identity).unwrap(),
+            triangulate(&points).unwrap()
+        );
+
+        // Stretching keeps the points sorted, so the triangles are the same as the ones of the
+        // stretched points.
+        let stretched: Vec<_> = points
+            .iter()
+            .map(|p| TriangulationPoint::new(p.x * 3.0, p.y))
+            .collect();
+        let stretch = [[3.0, 0.0], [0.0, 1.0]];
+        assert_eq!(
+            triangulate_transformed(&points, stretch).unwrap(),
+            triangulate(&stretched).unwrap()
+        );
+
+        // Mirroring keeps the circles, and so the triangles, which stay clockwise.
+        let canonical = |tris: Vec<Triangle>| {
+            let mut tris: Vec<_> = tris.iter().map(Triangle::canonical).collect();
+            tris.sort();
+            tris
+        };
+        let mirror = [[-1.0, 0.0], [0.0, 1.0]];
+        assert_eq!(
+            canonical(triangulate_transformed(&points, mirror).unwrap()),
+            canonical(triangulate(&points).unwrap())
+        );
+
+        match triangulate_transformed(&points, [[1.0, 2.0], [2.0, 4.0]]) {
+            Err(TriangulateError::Degenerate) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_tagged() {
+        let points = complex_points();
+        let tagged: Vec<_> = points
+            .iter()
+            .enumerate()
+            .map(|(i, &p)| (p, i * 10))
+            .collect();
+        let tris = triangulate_tagged(&tagged).unwrap();
+        assert_eq!(tris, triangulate(&points).unwrap());
+        for t in &tris {
+            assert_eq!(tagged[t.0].0, points[tagged[t.0].1 / 10]);
+        }
+    }
+
+    #[test]
+    fn test_with_supertriangle() {
+        let points = complex_points();
+        let supertriangle = [
+            TriangulationPoint::new(-3000.0, -3000.0),
+            TriangulationPoint::new(3000.0, -3000.0),
+            TriangulationPoint::new(0.0, 3000.0),
+        ];
 
-        assert_eq!(tris.len(), 1);
-        assert_eq!(tris[..], [Triangle(0, 1, 2)][..]);
+        // A cloud of points, and two overlapping batches of it.
+        let mut state = 12345u32;
+        let mut random = || {
+            state = state.wrapping_mul(1_103_515_245).wrapping_add(12345);
+            f64::from(state >> 8) / f64::from(1u32 << 24) * 100.0
+        };
+        let mut cloud: Vec<_> = (0..200)
+            .map(|_| TriangulationPoint::new(random(), random()))
+            .collect();
+        cloud.sort_by(sort_points);
+        let first: Vec<_> = cloud.iter().cloned().filter(|p| p.x < 60.0).collect();
+        let second: Vec<_> = cloud.iter().cloned().filter(|p| p.x > 40.0).collect();
+        let first_tris = triangulate_with_supertriangle(&first, supertriangle).unwrap();
+        let second_tris = triangulate_with_supertriangle(&second, supertriangle).unwrap();
+
+        // The triangles whose circumcircle is within the overlap are in both batches.
+        let mut shared = 0;
+        for t in &first_tris {
+            let corners = [first[t.0], first[t.1], first[t.2]];
+            let (x, _, radius_sq) =
+                validate::circumcircle(&corners[0], &corners[1], &corners[2]).unwrap();
+            let radius = radius_sq.sqrt();
+            if x - radius <= 40.0 || x + radius >= 60.0 {
+                continue;
+            }
+            let index = |p: &TriangulationPoint<f64>| second.iter().position(|q| q == p).unwrap();
+            let expected = Triangle(index(&corners[0]), index(&corners[1]), index(&corners[2]));
+            assert!(second_tris.contains(&expected));
+            shared += 1;
+        }
+        assert!(shared > 10);
+
+        assert_eq!(
+            triangulate_with_supertriangle(&points, supertriangle).unwrap(),
+            triangulate(&points).unwrap()
+        );
+        let small = [
+            TriangulationPoint::new(0.0, 0.0),
+            TriangulationPoint::new(100.0, 0.0),
+            TriangulationPoint::new(0.0, 100.0),
+        ];
+        match triangulate_with_supertriangle(&points, small) {
+            Err(TriangulateError::OutsideSupertriangle) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+
+        // The bounds of the points don't matter when the supertriangle is given.
+        let flat = [
+            TriangulationPoint::new(0.0, 0.0),
+            TriangulationPoint::new(1.0, 0.0),
+            TriangulationPoint::new(2.0, 0.0),
+        ];
+        match triangulate(&flat) {
+            Err(TriangulateError::Degenerate) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+        assert_eq!(
+            triangulate_with_supertriangle(&flat, supertriangle).unwrap(),
+            []
+        );
+    }
+
+    #[test]
+    fn test_with_stats() {
+        let points = complex_points();
+        let (tris, stats) = triangulate_with_stats(&points).unwrap();
+        assert_eq!(tris, triangulate(&points).unwrap());
+        assert_eq!(stats.cavity_sizes.len(), points.len());
+        assert!(stats.cavity_sizes.iter().all(|&size| size >= 1));
+        assert_eq!(
+            stats.max_cavity_size,
+            *stats.cavity_sizes.iter().max().unwrap()
+        );
+
+        // On a grid, most points are aligned with the vertices of the triangles they're tested
+        // against.
+        let grid: Vec<_> = (0..16)
+            .map(|i| TriangulationPoint::new((i / 4) as f64, (i % 4) as f64))
+            .collect();
+        let (_, stats) = triangulate_with_stats(&grid).unwrap();
+        assert!(stats.shortcuts > 0);
     }
 
     #[test]
@@ -414,15 +2220,16 @@ mod tests {
             TriangulationPoint::new(25.0, 15.0),
         ];
 
-        let tris: Vec<Triangle> = triangulate(&points).unwrap();
-
-        assert_eq!(tris.len(), 1);
-        assert_eq!(tris[..], [Triangle(0, 1, 2)][..]);
+        // Two coincident points are collinear with any third one.
+        match triangulate(&points) {
+            Err(TriangulateError::Degenerate) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
     }
 
-    #[test]
-    fn test_complex() {
-        let points = [
+    /// A non-trivial set of 25 points, sorted on x.
+    fn complex_points() -> [TriangulationPoint<f64>; 25] {
+        [
             TriangulationPoint::new(11.0, 264.0),
             TriangulationPoint::new(65.0, 216.0),
             TriangulationPoint::new(104.0, 522.0),
@@ -448,7 +2255,12 @@ mod tests {
             TriangulationPoint::new(839.0, 178.0),
             TriangulationPoint::new(876.0, 110.0),
             TriangulationPoint::new(895.0, 666.0),
-        ];
+        ]
+    }
+
+    #[test]
+    fn test_complex() {
+        let points = complex_points();
 
         let tris: Vec<Triangle> = triangulate(&points).unwrap();
 
@@ -498,6 +2310,471 @@ mod tests {
         assert_eq!(tris[..], expected_tris[..]);
     }
 
+    #[test]
+    fn test_into() {
+        let points = complex_points();
+        let mut tris = vec![Triangle(1, 2, 3); 100];
+        triangulate_into(&points, &mut tris).unwrap();
+        assert_eq!(tris, triangulate(&points).unwrap());
+
+        let capacity = tris.capacity();
+        triangulate_into(&points[..10], &mut tris).unwrap();
+        assert_eq!(tris, triangulate(&points[..10]).unwrap());
+        assert_eq!(tris.capacity(), capacity);
+
+        assert!(triangulate_into(&points[..2], &mut tris).is_err());
+        assert!(tris.is_empty());
+    }
+
+    #[test]
+    fn test_fixed() {
+        let points = complex_points();
+        let expected = triangulate(&points).unwrap();
+        let mut out = [Triangle(1, 2, 3); 50];
+        let count = triangulate_fixed::<_, _, 25>(&points, &mut out).unwrap();
+        assert_eq!(out[..count], *expected);
+
+        let count = triangulate_fixed::<_, _, 25>(&points[..10], &mut out).unwrap();
+        assert_eq!(out[..count], *triangulate(&points[..10]).unwrap());
+
+        match triangulate_fixed::<_, _, 25>(&points, &mut out[..expected.len() - 1]) {
+            Err(TriangulateError::OutputTooSmall) => (),
+            other => panic!("unexpected result: {:?}", other),
+        }
+        match triangulate_fixed::<_, _, 24>(&points, &mut out) {
+            Err(TriangulateError::TooManyPoints) => (),
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[cfg(feature = "allocator-api")]
+    #[test]
+    fn test_in_allocator() {
+        use std::alloc::{AllocError, Allocator, Global, Layout};
+        use std::cell::Cell;
+        use std::ptr::NonNull;
+
+        use super::triangulate_in;
+
+        #[derive(Default)]
+        struct Counting(Cell<usize>);
+
+        unsafe impl Allocator for &Counting {
+            fn allocate(&self, layout: Layout) -> std::result::Result<NonNull<[u8]>, AllocError> {
+                self.0.set(self.0.get() + 1);
+                Global.allocate(layout)
+            }
+
+            unsafe fn deallocate(&self, ptr: NonNull<u8>, layout: Layout) {
+                Global.deallocate(ptr, layout)
+            }
+        }
+
+        let counting = Counting::default();
+        let points = complex_points();
+        let tris = triangulate_in(&points, &counting).unwrap();
+        assert_eq!(*tris, *triangulate(&points).unwrap());
+        assert!(counting.0.get() >= 3);
+    }
+
+    #[test]
+    fn test_with_capacity() {
+        let points = complex_points();
+        let tris: Vec<Triangle> = triangulate(&points).unwrap();
+
+        assert_eq!(triangulate_with_capacity(&points, 0).unwrap(), tris);
+        assert_eq!(triangulate_with_capacity(&points, 1000).unwrap(), tris);
+    }
+
+    #[test]
+    fn test_flat() {
+        let points = complex_points();
+        let tris: Vec<Triangle> = triangulate(&points).unwrap();
+        let indices = triangulate_flat(&points).unwrap();
+
+        assert_eq!(indices.len(), tris.len() * 3);
+        for (t, i) in tris.iter().zip(indices.chunks(3)) {
+            assert_eq!([t.0 as u32, t.1 as u32, t.2 as u32], i);
+        }
+    }
+
+    #[test]
+    fn test_widen_accumulate() {
+        // A point just outside of the circumcircle of a triangle. Exact arithmetic puts it
+        // outside, but the f32 squared distances, rounded, compare the other way around.
+        let point = TriangulationPoint::new(13297.389f32, 10815.063);
+        let t0 = TriangulationPoint::new(12332.084f32, 13781.736);
+        let t1 = TriangulationPoint::new(13219.3f32, 10747.972);
+        let t2 = TriangulationPoint::new(11388.552f32, 10542.476);
+
+        assert!(in_circumcircle(&point, &t0, &t1, &t2, false));
+        assert!(!in_circumcircle(&point, &t0, &t1, &t2, true));
+    }
+
+    #[test]
+    fn test_drop_slivers() {
+        // The second point is barely above the line going from the first to the last one, making
+        // a triangle with two angles of less than 6 degrees.
+        let points = [
+            TriangulationPoint::new(0.0, 0.0),
+            TriangulationPoint::new(5.0, 0.5),
+            TriangulationPoint::new(5.0, 5.0),
+            TriangulationPoint::new(10.0, 0.0),
+        ];
+        let tris: Vec<Triangle> = triangulate(&points).unwrap();
+        assert_eq!(tris.len(), 3);
+
+        let options = TriangulateOptions {
+            drop_slivers_below_angle: Some(10.0f64.to_radians()),
+            ..Default::default()
+        };
+        let filtered = triangulate_with(&points, &options).unwrap();
+        assert_eq!(filtered.len(), 2);
+        assert!(filtered.iter().all(|t| tris.contains(t)));
+        assert!(filtered.iter().all(|t| t.0 == 2 || t.1 == 2 || t.2 == 2));
+
+        let options = TriangulateOptions {
+            drop_slivers_below_angle: Some(1.0f64.to_radians()),
+            ..Default::default()
+        };
+        assert_eq!(triangulate_with(&points, &options).unwrap(), tris);
+
+        let options = TriangulateOptions {
+            drop_slivers_below_angle: Some(2.0),
+            ..Default::default()
+        };
+        assert_eq!(triangulate_with(&points, &options).unwrap(), []);
+    }
+
+    #[test]
+    fn test_break_cocircular_ties() {
+        let grid: Vec<_> = (0..9)
+            .map(|i| TriangulationPoint::new((i / 3) as f64, (i % 3) as f64))
+            .collect();
+        let options = TriangulateOptions {
+            break_cocircular_ties: true,
+            ..Default::default()
+        };
+        let canonical = |tris: Vec<Triangle>| {
+            let mut tris: Vec<_> = tris.iter().map(Triangle::canonical).collect();
+            tris.sort();
+            tris
+        };
+
+        // Every square is split along its diagonal from its lowest-indexed corner, the bottom
+        // left one.
+        let tris = triangulate_with(&grid, &options).unwrap();
+        assert_eq!(
+            canonical(tris.clone()),
+            [
+                Triangle(0, 1, 4),
+                Triangle(0, 4, 3),
+                Triangle(1, 2, 5),
+                Triangle(1, 5, 4),
+                Triangle(3, 4, 7),
+                Triangle(3, 7, 6),
+                Triangle(4, 5, 8),
+                Triangle(4, 8, 7),
+            ]
+        );
+        assert!(is_delaunay(&grid, &tris));
+
+        // Tiny perturbations don't change the triangles. The points stay sorted, as the ones
+        // sharing a column are moved further right as they go up.
+        let perturbed: Vec<_> = grid
+            .iter()
+            .enumerate()
+            .map(|(i, p)| {
+                let offset = ((i * 7) % 5) as f64 * 1e-15;
+                TriangulationPoint::new(p.x + (i % 3) as f64 * 1e-15, p.y - offset)
+            })
+            .collect();
+        assert!(is_sorted_for_triangulation(&perturbed));
+        assert_eq!(
+            canonical(triangulate_with(&perturbed, &options).unwrap()),
+            canonical(tris)
+        );
+    }
+
+    #[test]
+    fn test_shared_options() {
+        let options = TriangulateOptions {
+            drop_slivers_below_angle: Some(1.0f64.to_radians()),
+            ..Default::default()
+        };
+        let inputs: Vec<Vec<_>> = (0..4)
+            .map(|k| {
+                let mut points: Vec<_> = (0..50)
+                    .map(|i| {
+                        let i = (i + k * 50) as f64;
+                        TriangulationPoint::new((i * 0.618).fract(), (i * 0.755).fract())
+                    })
+                    .collect();
+                points.sort_by(sort_points);
+                points
+            })
+            .collect();
+
+        // Every thread borrows the same options.
+        let shared = &options;
+        let outputs: Vec<_> = std::thread::scope(|scope| {
+            let handles: Vec<_> = inputs
+                .iter()
+                .map(|points| scope.spawn(move || triangulate_with(points, shared).unwrap()))
+                .collect();
+            handles.into_iter().map(|h| h.join().unwrap()).collect()
+        });
+        for (points, triangles) in inputs.iter().zip(outputs.iter()) {
+            assert_eq!(*triangles, triangulate_with(points, &options).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_send_sync() {
+        // Fails to compile if a public type stops being shareable between threads, such as by
+        // holding a trait object.
+        fn assert_send_sync<S: Send + Sync>() {}
+        assert_send_sync::<Triangle>();
+        assert_send_sync::<Edge>();
+        assert_send_sync::<TriangulationPoint<f64>>();
+        assert_send_sync::<TriangulationPoint<f32>>();
+        assert_send_sync::<TriangulateError>();
+        assert_send_sync::<TriangulateOptions<f64>>();
+        assert_send_sync::<TriangulateStats>();
+        assert_send_sync::<SmallResult>();
+        assert_send_sync::<
+            ConcatPoints<'static, TriangulationPoint<f64>, TriangulationPoint<f64>, f64>,
+        >();
+        assert_send_sync::<MeshRef<'static, f64, TriangulationPoint<f64>>>();
+        assert_send_sync::<Voronoi<f64>>();
+        assert_send_sync::<Triangulation<f64>>();
+        assert_send_sync::<Diff>();
+        assert_send_sync::<HalfEdgeMesh>();
+    }
+
+    #[test]
+    fn test_with_extra() {
+        let points = complex_points();
+        let (inner, ghosts) = points.split_at(20);
+        let tris = triangulate(&points).unwrap();
+
+        // Appending the last points as extra ones gives the same mesh.
+        assert_eq!(triangulate_with_extra(inner, ghosts, true).unwrap(), tris);
+        let inner_tris = triangulate_with_extra(inner, ghosts, false).unwrap();
+        assert!(!inner_tris.is_empty());
+        assert_eq!(
+            inner_tris,
+            tris.iter()
+                .filter(|t| t.0 < 20 && t.1 < 20 && t.2 < 20)
+                .cloned()
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_with_extra_unsorted() {
+        // Ghost points to the left of the input, which don't sort after it.
+        let points = complex_points();
+        let (ghosts, inner) = points.split_at(5);
+        let from_points = |i: usize| if i < 5 { i + inner.len() } else { i - 5 };
+        let tris: Vec<Triangle> = triangulate(&points)
+            .unwrap()
+            .iter()
+            .map(|t| Triangle(from_points(t.0), from_points(t.1), from_points(t.2)))
+            .collect();
+
+        assert_eq!(triangulate_with_extra(inner, ghosts, true).unwrap(), tris);
+        let inner_tris = triangulate_with_extra(inner, ghosts, false).unwrap();
+        assert!(!inner_tris.is_empty());
+        assert_eq!(
+            inner_tris,
+            tris.iter()
+                .filter(|t| t.0 < inner.len() && t.1 < inner.len() && t.2 < inner.len())
+                .cloned()
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_steps() {
+        let points = complex_points();
+        let tris: Vec<Triangle> = triangulate(&points).unwrap();
+
+        let steps = triangulate_steps(&points, false).unwrap();
+        assert_eq!(steps.len(), points.len());
+        assert_eq!(steps[0], []);
+        assert_eq!(steps[points.len() - 1], tris);
+        for (i, step) in steps.iter().enumerate() {
+            assert!(step.iter().all(|t| t.0 <= i && t.1 <= i && t.2 <= i));
+        }
+
+        let steps = triangulate_steps(&points, true).unwrap();
+        assert_eq!(steps.len(), points.len());
+        assert_eq!(steps[0].len(), 3);
+        assert!(steps[0].iter().all(|t| t.2 == 0));
+        assert!(steps[points.len() - 1].len() > tris.len());
+    }
+
+    #[test]
+    fn test_progress() {
+        let points = complex_points();
+        let mut reported = Vec::new();
+        let tris = triangulate_with_progress(&points, &mut |i| {
+            reported.push(i);
+            ControlFlow::Continue(())
+        })
+        .unwrap();
+        assert_eq!(tris, triangulate(&points).unwrap());
+        assert_eq!(reported, (0..points.len()).collect::<Vec<_>>());
+
+        reported.clear();
+        let result = triangulate_with_progress(&points, &mut |i| {
+            reported.push(i);
+            if i == 10 {
+                ControlFlow::Break(())
+            } else {
+                ControlFlow::Continue(())
+            }
+        });
+        match result {
+            Err(TriangulateError::Aborted) => (),
+            other => panic!("unexpected result: {:?}", other),
+        }
+        assert_eq!(reported, (0..11).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_vertical_column() {
+        let is_flat = |points: &[TriangulationPoint<f32>], t: &Triangle| {
+            let (a, b, c) = (points[t.0], points[t.1], points[t.2]);
+            (b.x - a.x) * (c.y - a.y) - (b.y - a.y) * (c.x - a.x) == 0.0
+        };
+
+        let x = 10.0f32 / 7.0;
+        let points = [
+            TriangulationPoint::new(x, 36.243244),
+            TriangulationPoint::new(x, 203.54054),
+            TriangulationPoint::new(x, 203.86487),
+            TriangulationPoint::new(x, 203.89189),
+            TriangulationPoint::new(x, 256.64865),
+            TriangulationPoint::new(74.23077, 107.83784),
+        ];
+        let tris = triangulate(&points).unwrap();
+        assert_eq!(tris.len(), 4);
+        assert!(tris.iter().all(|t| !is_flat(&points, t)));
+        assert!(is_delaunay(&points, &tris));
+
+        let mut points: Vec<TriangulationPoint<f32>> = (0..50)
+            .map(|i| TriangulationPoint::new(x, (i * i % 97) as f32 / 3.7))
+            .collect();
+        points.push(TriangulationPoint::new(-20.0, 13.0));
+        points.push(TriangulationPoint::new(30.0, 7.0));
+        points.sort_by(sort_points);
+        points.dedup();
+        let tris = triangulate(&points).unwrap();
+        assert_eq!(tris.len(), 2 * (points.len() - 3));
+        assert!(tris.iter().all(|t| !is_flat(&points, t)));
+        assert!(is_delaunay(&points, &tris));
+    }
+
+    #[test]
+    fn test_collinear_prefix() {
+        // Every variant starts from a supertriangle rather than from the first three points, so
+        // a collinear prefix never makes a flat seed triangle.
+        let points = [
+            TriangulationPoint::new(0.0, 0.0),
+            TriangulationPoint::new(0.0, 1.0),
+            TriangulationPoint::new(0.0, 2.0),
+            TriangulationPoint::new(0.0, 3.0),
+            TriangulationPoint::new(2.0, 1.5),
+        ];
+        let check = |tris: Vec<Triangle>| {
+            assert_eq!(tris.len(), 3);
+            assert!(tris.iter().all(|t| t.0 == 4 || t.1 == 4 || t.2 == 4));
+            assert!(is_delaunay(&points, &tris));
+        };
+        check(triangulate(&points).unwrap());
+        check(triangulate_lawson(&points).unwrap());
+        check(triangulate_approx(&points, 1.0).unwrap());
+        check(triangulate_progressive(&points, 1).unwrap().next().unwrap());
+
+        let mut triangulation =
+            Triangulation::new(points[0], TriangulationPoint::new(2.0, 3.0)).unwrap();
+        for p in &points {
+            triangulation.insert(p);
+        }
+        check(triangulation.triangles());
+    }
+
+    #[cfg(feature = "test-util")]
+    #[test]
+    fn test_in_circumcircle_near_cocircular() {
+        use super::near_cocircular_points;
+
+        // The predicates are compared at several relative distances between the fourth point and
+        // the circle. Over 10000 quadruples, the naive `f64` predicate is always right down to
+        // 1e-9, but fails 1.4% of the time at 1e-13, where the fourth point is within rounding
+        // errors of the circle. The `f32` one fails about 2.4% of the time at 1e-5, and 29% below
+        // 1e-9, while widening its last comparison to `f64` brings that down to 26%: the
+        // circumcenter is still computed in `f32`.
+        let disagreements = |perturbation: f64| {
+            let mut counts = (0, 0, 0);
+            for &(points, inside) in &near_cocircular_points(10_000, 7, perturbation) {
+                if in_circumcircle(&points[3], &points[0], &points[1], &points[2], false) != inside
+                {
+                    counts.0 += 1;
+                }
+
+                // An incircle determinant in f64 is close enough to exact for f32 coordinates.
+                let p: Vec<(f64, f64)> = points
+                    .iter()
+                    .map(|p| (f64::from(p.x as f32), f64::from(p.y as f32)))
+                    .collect();
+                let d = |i: usize| (p[i].0 - p[3].0, p[i].1 - p[3].1);
+                let (a, b, c) = (d(0), d(1), d(2));
+                let lift = |v: (f64, f64)| v.0 * v.0 + v.1 * v.1;
+                let det = a.0 * (b.1 * lift(c) - lift(b) * c.1)
+                    - a.1 * (b.0 * lift(c) - lift(b) * c.0)
+                    + lift(a) * (b.0 * c.1 - b.1 * c.0);
+                let orientation = (b.0 - a.0) * (c.1 - a.1) - (b.1 - a.1) * (c.0 - a.0);
+                let expected = det * orientation > 0.0;
+
+                let narrow: Vec<TriangulationPoint<f32>> = p
+                    .iter()
+                    .map(|&(x, y)| TriangulationPoint::new(x as f32, y as f32))
+                    .collect();
+                let predicate =
+                    |widen| in_circumcircle(&narrow[3], &narrow[0], &narrow[1], &narrow[2], widen);
+                if predicate(false) != expected {
+                    counts.1 += 1;
+                }
+                if predicate(true) != expected {
+                    counts.2 += 1;
+                }
+            }
+            counts
+        };
+
+        let (naive, narrow, widened) = disagreements(1e-9);
+        assert_eq!(naive, 0);
+        assert!(widened <= narrow && narrow < 3500);
+
+        let (naive, narrow, widened) = disagreements(1e-13);
+        assert!(naive > 0 && naive < 500);
+        assert!(widened <= narrow);
+    }
+
+    #[test]
+    fn test_reproducible() {
+        let points = complex_points();
+        let tris: Vec<Triangle> = triangulate(&points).unwrap();
+
+        let copied_points = points.to_vec();
+        for _ in 0..10 {
+            assert_eq!(triangulate(&copied_points).unwrap(), tris);
+        }
+    }
+
     #[test]
     fn test_coincident_points() {
         let points = [
@@ -507,8 +2784,36 @@ mod tests {
             TriangulationPoint::new(11.0, 10.0),
         ];
 
-        let tris: Vec<Triangle> = triangulate(&points).unwrap();
-        assert_eq!(tris.len(), 0);
+        // They all lie on a horizontal line.
+        match triangulate(&points) {
+            Err(TriangulateError::Degenerate) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_line_points() {
+        let horizontal: Vec<_> = (0..10)
+            .map(|i| TriangulationPoint::new(i as f64 * 1.5, 10.0))
+            .collect();
+        let vertical: Vec<_> = (0..10)
+            .map(|i| TriangulationPoint::new(10.0, i as f64 * 1.5))
+            .collect();
+        for points in &[horizontal, vertical] {
+            match triangulate(points) {
+                Err(TriangulateError::Degenerate) => {}
+                other => panic!("unexpected result: {:?}", other),
+            }
+        }
+    }
+
+    #[test]
+    fn test_identical_points() {
+        let points = [TriangulationPoint::new(10.0, 10.0); 4];
+        match triangulate(&points) {
+            Err(TriangulateError::Degenerate) => {}
+            other => panic!("unexpected result: {:?}", other),
+        }
     }
 
     #[test]
@@ -518,6 +2823,168 @@ mod tests {
         triangulate(&points).unwrap();
     }
 
+    #[test]
+    fn test_sort_nan_last() {
+        let mut points = [
+            TriangulationPoint::new(f64::NAN, 0.0),
+            TriangulationPoint::new(2.0, f64::NAN),
+            TriangulationPoint::new(2.0, 1.0),
+            TriangulationPoint::new(f64::NAN, f64::NAN),
+            TriangulationPoint::new(1.0, 0.0),
+        ];
+        points.sort_by(sort_points);
+        assert!(is_sorted_for_triangulation(&points));
+        assert_eq!(
+            points[..2],
+            [
+                TriangulationPoint::new(1.0, 0.0),
+                TriangulationPoint::new(2.0, 1.0)
+            ]
+        );
+        assert!(points[2].y.is_nan() && points[3].x.is_nan() && points[4].x.is_nan());
+    }
+
+    #[test]
+    fn test_non_finite() {
+        for &bad in &[f64::NAN, f64::INFINITY] {
+            let mut points = vec![
+                TriangulationPoint::new(0.0, 0.0),
+                TriangulationPoint::new(1.0, 1.0),
+                TriangulationPoint::new(2.0, 0.0),
+                TriangulationPoint::new(bad, 0.5),
+            ];
+            points.sort_by(sort_points);
+            match triangulate(&points) {
+                Err(TriangulateError::NonFiniteCoordinate) => {}
+                other => panic!("unexpected result: {:?}", other),
+            }
+            match triangulate_with(&points, &TriangulateOptions::default()) {
+                Err(TriangulateError::NonFiniteCoordinate) => {}
+                other => panic!("unexpected result: {:?}", other),
+            }
+            match triangulate_approx(&points, 1.0) {
+                Err(TriangulateError::NonFiniteCoordinate) => {}
+                other => panic!("unexpected result: {:?}", other),
+            }
+        }
+    }
+
+    #[test]
+    fn test_checked() {
+        let points = [
+            TriangulationPoint::new(10.0, 10.0),
+            TriangulationPoint::new(15.0, 25.0),
+            TriangulationPoint::new(25.0, 15.0),
+            TriangulationPoint::new(30.0, 25.0),
+            TriangulationPoint::new(40.0, 15.0),
+        ];
+        let (tris, complete) = triangulate_checked(&points).unwrap();
+        assert_eq!(tris, triangulate(&points).unwrap());
+        assert!(complete);
+
+        // The thin triangle (0, 2, 1) along the hull is missing from the output.
+        let points = [
+            TriangulationPoint::new(3.0, 465.0),
+            TriangulationPoint::new(99.0, 812.0),
+            TriangulationPoint::new(113.0, 959.0),
+            TriangulationPoint::new(217.0, 70.0),
+            TriangulationPoint::new(323.0, 377.0),
+            TriangulationPoint::new(575.0, 533.0),
+            TriangulationPoint::new(820.0, 118.0),
+            TriangulationPoint::new(895.0, 650.0),
+        ];
+        let (tris, complete) = triangulate_checked(&points).unwrap();
+        assert_eq!(tris.len(), 8);
+        assert!(!complete);
+    }
+
+    #[test]
+    fn test_indexed() {
+        let points: Vec<TriangulationPoint<f64>> = (0..254)
+            .map(|i| TriangulationPoint::new(i as f64, ((i * i) % 17) as f64))
+            .collect();
+
+        let tris = triangulate(&points[..253]).unwrap();
+        let small = triangulate_indexed::<u8, _, _>(&points[..253]).unwrap();
+        assert_eq!(small.len(), tris.len());
+        for (s, t) in small.iter().zip(tris.iter()) {
+            assert_eq!(
+                [s[0] as usize, s[1] as usize, s[2] as usize],
+                [t.0, t.1, t.2]
+            );
+        }
+
+        match triangulate_indexed::<u8, _, _>(&points) {
+            Err(TriangulateError::TooManyPoints) => (),
+            other => panic!("unexpected result: {:?}", other),
+        }
+        assert!(triangulate_indexed::<u16, _, _>(&points).is_ok());
+    }
+
+    #[test]
+    fn test_projected() {
+        struct Scan(f64, f64, f64);
+        impl Point<f64> for Scan {
+            fn x(&self) -> f64 {
+                self.0
+            }
+            fn y(&self) -> f64 {
+                self.1
+            }
+        }
+
+        let points = [
+            Scan(0.0, 0.0, 1.0),
+            Scan(0.0, 0.0, 2.0),
+            Scan(0.0, 10.0, 1.0),
+            Scan(10.0, 0.0, 5.0),
+            Scan(10.0, 0.0, 4.0),
+            Scan(10.0, 0.0, 5.0),
+            Scan(10.0, 10.0, 1.0),
+        ];
+        let (kept, tris) = triangulate_projected(&points, |p| p.2).unwrap();
+        assert_eq!(kept, [1, 2, 3, 6]);
+
+        let unique: Vec<TriangulationPoint<f64>> = kept
+            .iter()
+            .map(|&i| TriangulationPoint::new(points[i].0, points[i].1))
+            .collect();
+        let expected: Vec<Triangle> = triangulate(&unique)
+            .unwrap()
+            .into_iter()
+            .map(|t| Triangle(kept[t.0], kept[t.1], kept[t.2]))
+            .collect();
+        assert_eq!(tris.len(), 2);
+        assert_eq!(tris, expected);
+    }
+
+    #[test]
+    fn test_display() {
+        assert_eq!(TriangulationPoint::new(1.5, -2.0).to_string(), "(1.5, -2)");
+        assert_eq!(Triangle(0, 12, 3).to_string(), "[0, 12, 3]");
+        assert_eq!(format!("{:?}", Triangle(0, 12, 3)), "Triangle(0, 12, 3)");
+    }
+
+    #[test]
+    fn test_canonical_triangle() {
+        for t in &[Triangle(3, 7, 5), Triangle(7, 5, 3), Triangle(5, 3, 7)] {
+            assert_eq!(t.canonical(), Triangle(3, 7, 5));
+        }
+        for t in &[Triangle(3, 5, 7), Triangle(5, 7, 3), Triangle(7, 3, 5)] {
+            assert_eq!(t.canonical(), Triangle(3, 5, 7));
+        }
+        assert_eq!(Triangle(2, 2, 1).canonical(), Triangle(1, 2, 2));
+        assert!(Triangle(0, 2, 1) < Triangle(1, 0, 0));
+    }
+
+    #[test]
+    fn test_edge_ordering() {
+        assert_eq!(Edge(1, 0).cmp(&Edge(0, 1)), Ordering::Equal);
+        assert!(Edge(3, 0) < Edge(1, 2));
+        assert!(Edge(0, 1) < Edge(2, 0));
+        assert!(Edge(2, 1) > Edge(0, 5));
+    }
+
     #[test]
     fn test_points_ordering() {
         let mut unsorted_points = [
@@ -0,0 +1,125 @@
+// Copyright 2017-2018 Samuel Loretan <tynril@gmail.com> -- See LICENSE file
+
+//! A triangulation borrowing the points it was made from.
+
+use std::marker::PhantomData;
+
+use super::topology::triangle_adjacency;
+use super::{triangulate, Coordinate, Point, Result, Triangle};
+
+/// The triangles of a triangulation, along with a borrow of the points they index into.
+///
+/// This keeps the points and their triangles together, without copying the points, which makes
+/// the queries over the mesh convenient. As the mesh borrows the points, it can't outlive them:
+///
+/// ```rust,compile_fail,E0597
+/// use rtriangulate::{triangulate_ref, TriangulationPoint};
+///
+/// let mesh = {
+///     let points = vec![
+///         TriangulationPoint::new(0.0, 0.0),
+///         TriangulationPoint::new(1.0, 2.0),
+///         TriangulationPoint::new(2.0, 0.0)
+///     ];
+///     triangulate_ref(&points).unwrap()
+/// };
+/// assert_eq!(mesh.triangles.len(), 1);
+/// ```
+#[derive(Clone, Debug)]
+pub struct MeshRef<'a, T, P: 'a> {
+    /// The points of the triangulation.
+    pub points: &'a [P],
+    /// The triangles, as indices into `points`.
+    pub triangles: Vec<Triangle>,
+    coordinate: PhantomData<T>,
+}
+
+impl<'a, T, P> MeshRef<'a, T, P>
+where
+    T: Coordinate,
+    P: Point<T>,
+{
+    /// Makes a mesh from some points and triangles indexing into them.
+    pub fn new(points: &'a [P], triangles: Vec<Triangle>) -> Self {
+        MeshRef {
+            points,
+            triangles,
+            coordinate: PhantomData,
+        }
+    }
+
+    /// Returns the three points of a triangle of the mesh.
+    pub fn triangle_points(&self, triangle: usize) -> [&'a P; 3] {
+        let t = &self.triangles[triangle];
+        [&self.points[t.0], &self.points[t.1], &self.points[t.2]]
+    }
+
+    /// Returns the total area covered by the triangles.
+    pub fn area(&self) -> T {
+        let doubled_area = (0..self.triangles.len()).fold(T::zero(), |area, i| {
+            let [a, b, c] = self.triangle_points(i);
+            let cross = (b.x() - a.x()) * (c.y() - a.y()) - (b.y() - a.y()) * (c.x() - a.x());
+            area + cross.abs()
+        });
+        doubled_area / (T::one() + T::one())
+    }
+
+    /// Computes the neighbouring triangles of every triangle, as `triangle_adjacency` does.
+    pub fn adjacency(&self) -> Vec<[Option<usize>; 3]> {
+        triangle_adjacency(&self.triangles)
+    }
+}
+
+/// Triangulates a set of points, returning the triangles along with a borrow of the points.
+///
+/// This is the same as `triangulate`, with the points and the triangles kept together in a
+/// `MeshRef`, which borrows the points instead of copying them.
+///
+/// Example:
+///
+/// ```rust
+/// use rtriangulate::{triangulate_ref, Triangle, TriangulationPoint};
+///
+/// let points = [
+///     TriangulationPoint::new(0.0, 0.0),
+///     TriangulationPoint::new(1.0, 2.0),
+///     TriangulationPoint::new(2.0, 0.0),
+///     TriangulationPoint::new(3.0, 2.0)
+/// ];
+/// let mesh = triangulate_ref(&points).unwrap();
+/// assert_eq!(mesh.triangles, [Triangle(0, 1, 2), Triangle(2, 1, 3)]);
+/// assert_eq!(mesh.area(), 4.0);
+/// assert_eq!(mesh.triangle_points(1), [&points[2], &points[1], &points[3]]);
+/// ```
+pub fn triangulate_ref<'a, T, P>(points: &'a [P]) -> Result<MeshRef<'a, T, P>>
+where
+    T: Coordinate,
+    P: Point<T>,
+{
+    Ok(MeshRef::new(points, triangulate(points)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{triangulate, TriangulationPoint};
+    use super::{triangulate_ref, MeshRef};
+
+    #[test]
+    fn test_mesh_ref() {
+        let points = vec![
+            TriangulationPoint::new(10.0, 10.0),
+            TriangulationPoint::new(15.0, 25.0),
+            TriangulationPoint::new(25.0, 15.0),
+            TriangulationPoint::new(30.0, 25.0),
+            TriangulationPoint::new(40.0, 15.0),
+        ];
+        let mesh = triangulate_ref(&points).unwrap();
+        assert!(::std::ptr::eq(mesh.points, &points[..]));
+        assert_eq!(mesh.triangles, triangulate(&points).unwrap());
+        assert_eq!(mesh.area(), 287.5);
+        assert_eq!(mesh.adjacency().len(), 4);
+
+        let empty = MeshRef::new(&points, Vec::new());
+        assert_eq!(empty.area(), 0.0);
+    }
+}
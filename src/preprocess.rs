@@ -0,0 +1,82 @@
+// Copyright 2017-2018 Samuel Loretan <tynril@gmail.com> -- See LICENSE file
+
+//! Preparation of the input points of a triangulation.
+
+use num_traits::float::FloatCore;
+
+use super::{Point, TriangulationPoint};
+
+/// Rounds the coordinates of every point to the nearest multiple of `cell`.
+///
+/// Snapping makes the triangulation insensitive to the noise below the size of a cell: inputs
+/// which only differ by tiny floating point errors end up as the exact same points, and so as the
+/// exact same triangles. Points closer to each other than a cell can however be snapped onto the
+/// same location. Such coincident points should then be removed, which is easily done after
+/// sorting them, as `dedup` does. Halfway coordinates are rounded away from zero.
+///
+/// Example:
+///
+/// ```rust
+/// use rtriangulate::{snap_to_grid, sort_points, TriangulationPoint};
+///
+/// let points = [
+///     TriangulationPoint::new(0.1, 0.2),
+///     TriangulationPoint::new(9.9, -0.1),
+///     TriangulationPoint::new(-0.2, 0.1),
+///     TriangulationPoint::new(5.0, 7.6)
+/// ];
+/// let mut snapped = snap_to_grid(&points, 0.5);
+/// snapped.sort_by(sort_points);
+/// snapped.dedup();
+/// assert_eq!(snapped, [
+///     TriangulationPoint::new(0.0, 0.0),
+///     TriangulationPoint::new(5.0, 7.5),
+///     TriangulationPoint::new(10.0, 0.0)
+/// ]);
+/// ```
+pub fn snap_to_grid<T, P>(points: &[P], cell: T) -> Vec<TriangulationPoint<T>>
+where
+    T: FloatCore,
+    P: Point<T>,
+{
+    let snap = |v: T| (v / cell).round() * cell;
+    points
+        .iter()
+        .map(|p| TriangulationPoint::new(snap(p.x()), snap(p.y())))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{triangulate, TriangulationPoint};
+    use super::snap_to_grid;
+
+    #[test]
+    fn test_snap_to_grid() {
+        let points = [
+            TriangulationPoint::new(10.0, 10.0),
+            TriangulationPoint::new(15.0, 25.0),
+            TriangulationPoint::new(25.0, 15.0),
+            TriangulationPoint::new(30.0, 25.0),
+        ];
+        let noisy: Vec<_> = points
+            .iter()
+            .enumerate()
+            .map(|(i, p)| {
+                let noise = (i as f64 - 1.5) * 1e-9;
+                TriangulationPoint::new(p.x + noise, p.y - noise)
+            })
+            .collect();
+
+        let snapped = snap_to_grid(&noisy, 0.25);
+        assert_eq!(snapped, points);
+        assert_eq!(
+            triangulate(&snapped).unwrap(),
+            triangulate(&points).unwrap()
+        );
+        assert_eq!(
+            snap_to_grid(&[TriangulationPoint::new(-0.75f32, 0.74)], 0.5),
+            [TriangulationPoint::new(-1.0, 0.5)]
+        );
+    }
+}
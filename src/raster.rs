@@ -0,0 +1,376 @@
+// Copyright 2017-2018 Samuel Loretan <tynril@gmail.com> -- See LICENSE file
+
+//! Rasterization of values interpolated over a triangulation.
+
+use std::ops::Range;
+
+use num_traits::Float;
+
+use super::{Coordinate, Point, Result, Triangle, TriangulateError, TriangulationPoint};
+
+/// Interpolates values given at the points over a regular grid of pixels.
+///
+/// The `bounds` are the `(min, max)` corners of the area covered by the grid, which is split into
+/// `width` columns and `height` rows of pixels. Every pixel is sampled at its center: with pixels
+/// of size `(dx, dy)`, the pixel of column `i` and row `j` is sampled at `(min.x + (i + 0.5) * dx,
+/// min.y + (j + 0.5) * dy)`, so row 0 is the one along `min.y`. The result is stored row by row,
+/// the value of that pixel being at index `j * width + i`.
+///
+/// The value of a pixel is interpolated linearly (with barycentric coordinates) from the `values`
+/// of the vertices of the triangle containing its center. The `values` are aligned with `points`.
+/// Pixels whose center isn't covered by any triangle, such as outside of the hull, are `None`.
+///
+/// Each triangle is rasterized with `rasterize_triangle`, instead of looking up the triangle
+/// containing each pixel, so this is linear in the number of triangles plus the number of covered
+/// pixels. A center lying exactly on an edge follows its top-left rule: it gets its value from
+/// only one of the two triangles sharing the edge, and on the hull, it is only covered along the
+/// top and left edges, as in graphics APIs.
+///
+/// If the pixel positions can't be represented by `T`, `TriangulateError::UnsupportedCoordinate` is
+/// returned.
+///
+/// Example:
+///
+/// ```rust
+/// use rtriangulate::{rasterize, triangulate, TriangulationPoint};
+///
+/// let points = [
+///     TriangulationPoint::new(0.0, 0.0),
+///     TriangulationPoint::new(0.0, 2.0),
+///     TriangulationPoint::new(2.0, 0.0),
+///     TriangulationPoint::new(2.0, 2.0)
+/// ];
+/// let triangles = triangulate(&points).unwrap();
+/// let bounds = (TriangulationPoint::new(0.0, 0.0), TriangulationPoint::new(2.0, 2.0));
+/// let raster = rasterize(&points, &triangles, &[0.0, 2.0, 4.0, 6.0], bounds, 2, 2).unwrap();
+/// assert_eq!(raster, [Some(1.5), Some(3.5), Some(2.5), Some(4.5)]);
+/// ```
+pub fn rasterize<T, P>(
+    points: &[P],
+    triangles: &[Triangle],
+    values: &[T],
+    bounds: (TriangulationPoint<T>, TriangulationPoint<T>),
+    width: usize,
+    height: usize,
+) -> Result<Vec<Option<T>>>
+where
+    T: Float + Coordinate,
+    P: Point<T>,
+{
+    assert_eq!(
+        points.len(),
+        values.len(),
+        "there must be one value per point"
+    );
+
+    let mut raster = vec![None; width * height];
+    for t in triangles {
+        let (a, b, c) = (&points[t.0], &points[t.1], &points[t.2]);
+        for (i, j, (u, v, w)) in rasterize_triangle(a, b, c, bounds, width, height)? {
+            raster[j as usize * width + i as usize] =
+                Some(values[t.0] * u + values[t.1] * v + values[t.2] * w);
+        }
+    }
+    Ok(raster)
+}
+
+/// Iterates over the pixels covered by a triangle, along with the barycentric coordinates of their
+/// centers.
+///
+/// The grid of pixels is the same as with `rasterize`: the `bounds` are split into `width`
+/// columns and `height` rows, every pixel is sampled at its center, and row 0 is along `min.y`.
+/// This yields the column and row of every pixel whose center is covered by the triangle, row by
+/// row, along with the weights of `a`, `b` and `c` at that center, which add up to one. This makes
+/// it possible to do any per-pixel work, such as interpolating several attributes at once, into
+/// any kind of buffer. Degenerate triangles cover no pixel.
+///
+/// A pixel whose center lies exactly on an edge is only covered if it is a top or a left edge of
+/// the triangle, as with the top-left rule of graphics APIs: a left edge is one with the inside of
+/// the triangle on its right, along the x axis, and a top edge is a horizontal edge with the
+/// inside of the triangle below it, towards lower y. So every pixel is covered by exactly one of
+/// the triangles sharing an edge, which lets them be blended without covering any pixel twice.
+/// This is what `rasterize` uses for every triangle.
+///
+/// If the pixel positions can't be represented by `T`, `TriangulateError::UnsupportedCoordinate` is
+/// returned.
+///
+/// Example:
+///
+/// ```rust
+/// use rtriangulate::{rasterize_triangle, TriangulationPoint};
+///
+/// let (a, b, c) = (
+///     TriangulationPoint::new(0.0, 0.0),
+///     TriangulationPoint::new(0.0, 4.0),
+///     TriangulationPoint::new(4.0, 0.0)
+/// );
+/// let bounds = (TriangulationPoint::new(0.0, 0.0), TriangulationPoint::new(4.0, 4.0));
+/// let pixels: Vec<_> = rasterize_triangle(&a, &b, &c, bounds, 2, 2).unwrap().collect();
+/// assert_eq!(pixels, [(0, 0, (0.5, 0.25, 0.25))]);
+/// ```
+pub fn rasterize_triangle<T, P>(
+    a: &P,
+    b: &P,
+    c: &P,
+    bounds: (TriangulationPoint<T>, TriangulationPoint<T>),
+    width: usize,
+    height: usize,
+) -> Result<impl Iterator<Item = (u32, u32, (T, T, T))>>
+where
+    T: Float + Coordinate,
+    P: Point<T>,
+{
+    let (columns, mut rows) = pixel_ranges(a, b, c, bounds, width, height)?;
+    let corners = [
+        TriangulationPoint::new(a.x(), a.y()),
+        TriangulationPoint::new(b.x(), b.y()),
+        TriangulationPoint::new(c.x(), c.y()),
+    ];
+
+    // Turn the triangle counter-clockwise, so that its inside is on the left of all its edges.
+    let zero = <T as Coordinate>::zero();
+    let signed_area = edge_function(&corners[0], &corners[1], &corners[2]);
+    let order = if signed_area > zero {
+        [0, 1, 2]
+    } else {
+        [0, 2, 1]
+    };
+    if signed_area == zero || width == 0 || height == 0 {
+        rows = 0..0;
+    }
+
+    // The centers of the pixels, along each axis.
+    let (min, _) = bounds;
+    let to_coordinate =
+        |n: usize| T::from_f64(n as f64).ok_or(TriangulateError::UnsupportedCoordinate);
+    let half = T::from_f64(0.5).ok_or(TriangulateError::UnsupportedCoordinate)?;
+    let pixel_width = (bounds.1.x - min.x) / to_coordinate(width.max(1))?;
+    let pixel_height = (bounds.1.y - min.y) / to_coordinate(height.max(1))?;
+    let centers = |range: Range<usize>, origin: T, size: T| {
+        range
+            .map(|k| Ok((k, origin + (to_coordinate(k)? + half) * size)))
+            .collect::<Result<Vec<_>>>()
+    };
+    let columns = centers(columns, min.x, pixel_width)?;
+    let rows = centers(rows, min.y, pixel_height)?;
+
+    Ok(rows
+        .into_iter()
+        .flat_map(move |(j, y)| columns.clone().into_iter().map(move |(i, x)| (i, j, x, y)))
+        .filter_map(move |(i, j, x, y)| {
+            let center = TriangulationPoint::new(x, y);
+
+            // The weight of every corner is the edge function of the edge across it.
+            let mut weights = [zero; 3];
+            for k in 0..3 {
+                let (from, to) = (&corners[order[(k + 1) % 3]], &corners[order[(k + 2) % 3]]);
+                let value = edge_function(from, to, &center);
+                let (dx, dy) = (to.x - from.x, to.y - from.y);
+                let top_left = dy < zero || (dy == zero && dx < zero);
+                if value < zero || (value == zero && !top_left) {
+                    return None;
+                }
+                weights[order[k]] = value;
+            }
+            let sum = weights[0] + weights[1] + weights[2];
+            Some((
+                i as u32,
+                j as u32,
+                (weights[0] / sum, weights[1] / sum, weights[2] / sum),
+            ))
+        }))
+}
+
+/// Returns twice the signed area of the triangle `from`, `to`, `p`: positive when `p` is on the
+/// left of the edge going from `from` to `to`.
+///
+/// It is computed from the lowest of the end points of the edge, so that the edge function of a
+/// shared edge is exactly the opposite in the two triangles sharing it.
+fn edge_function<T: Float>(
+    from: &TriangulationPoint<T>,
+    to: &TriangulationPoint<T>,
+    p: &TriangulationPoint<T>,
+) -> T {
+    let lowest_first = (from.x, from.y) < (to.x, to.y);
+    let (a, b) = if lowest_first { (from, to) } else { (to, from) };
+    let value = (b.x - a.x) * (p.y - a.y) - (b.y - a.y) * (p.x - a.x);
+    if lowest_first {
+        value
+    } else {
+        -value
+    }
+}
+
+/// Returns the range of the columns and rows of the pixels whose center lies within the bounding
+/// box of a triangle.
+fn pixel_ranges<T, P>(
+    a: &P,
+    b: &P,
+    c: &P,
+    bounds: (TriangulationPoint<T>, TriangulationPoint<T>),
+    width: usize,
+    height: usize,
+) -> Result<(Range<usize>, Range<usize>)>
+where
+    T: Float + Coordinate,
+    P: Point<T>,
+{
+    let (min, max) = bounds;
+    let to_coordinate =
+        |n: usize| T::from_f64(n as f64).ok_or(TriangulateError::UnsupportedCoordinate);
+    let half = T::from_f64(0.5).ok_or(TriangulateError::UnsupportedCoordinate)?;
+
+    // The range of pixels whose center lies within [from, to], along one axis.
+    let pixel_range = |from: T, to: T, origin: T, size: T, count: usize| {
+        if count == 0 {
+            return Ok(0..0);
+        }
+        let first = Float::max(
+            ((from - origin) / size - half).ceil(),
+            <T as Coordinate>::zero(),
+        );
+        let last = Float::min(
+            ((to - origin) / size - half).floor(),
+            to_coordinate(count - 1)?,
+        );
+        Ok(match (first.to_usize(), last.to_usize()) {
+            (Some(first), Some(last)) if first <= last => first..last + 1,
+            _ => 0..0,
+        })
+    };
+
+    let columns = pixel_range(
+        Float::min(Float::min(a.x(), b.x()), c.x()),
+        Float::max(Float::max(a.x(), b.x()), c.x()),
+        min.x,
+        (max.x - min.x) / to_coordinate(width.max(1))?,
+        width,
+    )?;
+    let rows = pixel_range(
+        Float::min(Float::min(a.y(), b.y()), c.y()),
+        Float::max(Float::max(a.y(), b.y()), c.y()),
+        min.y,
+        (max.y - min.y) / to_coordinate(height.max(1))?,
+        height,
+    )?;
+    Ok((columns, rows))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{triangulate, TriangulationPoint};
+    use super::{rasterize, rasterize_triangle};
+
+    #[test]
+    fn test_rasterize_linear() {
+        let points = [
+            TriangulationPoint::new(0.0, 0.0),
+            TriangulationPoint::new(0.0, 8.0),
+            TriangulationPoint::new(3.0, 5.0),
+            TriangulationPoint::new(8.0, 0.0),
+            TriangulationPoint::new(8.0, 8.0),
+        ];
+        let values: Vec<f64> = points.iter().map(|p| p.x + 2.0 * p.y).collect();
+        let tris = triangulate(&points).unwrap();
+        let bounds = (
+            TriangulationPoint::new(0.0, 0.0),
+            TriangulationPoint::new(8.0, 8.0),
+        );
+
+        // A linear function is interpolated exactly, and the whole square is covered.
+        let raster = rasterize(&points, &tris, &values, bounds, 4, 8).unwrap();
+        for j in 0..8 {
+            for i in 0..4 {
+                let (x, y) = (2.0 * i as f64 + 1.0, j as f64 + 0.5);
+                let value = raster[j * 4 + i].unwrap();
+                assert!((value - (x + 2.0 * y)).abs() < 1e-9);
+            }
+        }
+    }
+
+    #[test]
+    fn test_rasterize_outside() {
+        let points = [
+            TriangulationPoint::new(1.0, 1.0),
+            TriangulationPoint::new(1.0, 3.0),
+            TriangulationPoint::new(3.0, 1.0),
+        ];
+        let tris = triangulate(&points).unwrap();
+        let bounds = (
+            TriangulationPoint::new(0.0, 0.0),
+            TriangulationPoint::new(4.0, 4.0),
+        );
+
+        // The centers of the pixels 6 and 9 lie on the hypotenuse, which is neither a top nor a
+        // left edge of the triangle.
+        let raster = rasterize(&points, &tris, &[1.0, 1.0, 1.0], bounds, 4, 4).unwrap();
+        let covered: Vec<usize> = (0..16).filter(|&i| raster[i].is_some()).collect();
+        assert_eq!(covered, [5]);
+        assert!(rasterize(&points, &tris, &[1.0, 1.0, 1.0], bounds, 0, 4)
+            .unwrap()
+            .is_empty());
+
+        // They are covered by the triangle on the other side of the hypotenuse, of which it is a
+        // left edge.
+        let flipped = [
+            TriangulationPoint::new(1.0, 3.0),
+            TriangulationPoint::new(3.0, 1.0),
+            TriangulationPoint::new(3.0, 3.0),
+        ];
+        let tris = triangulate(&flipped).unwrap();
+        let raster = rasterize(&flipped, &tris, &[1.0, 1.0, 1.0], bounds, 4, 4).unwrap();
+        let covered: Vec<usize> = (0..16).filter(|&i| raster[i].is_some()).collect();
+        assert_eq!(covered, [6, 9, 10]);
+    }
+
+    #[test]
+    fn test_rasterize_triangle() {
+        let points = [
+            TriangulationPoint::new(0.0, 0.0),
+            TriangulationPoint::new(0.0, 8.0),
+            TriangulationPoint::new(4.0, 4.0),
+            TriangulationPoint::new(8.0, 0.0),
+            TriangulationPoint::new(8.0, 8.0),
+        ];
+        let tris = triangulate(&points).unwrap();
+        let bounds = (
+            TriangulationPoint::new(0.0, 0.0),
+            TriangulationPoint::new(8.0, 8.0),
+        );
+
+        // Many pixel centers are on the diagonals, but every pixel is covered exactly once.
+        let mut covered = vec![0; 64];
+        for t in &tris {
+            let (a, b, c) = (&points[t.0], &points[t.1], &points[t.2]);
+            for (i, j, (u, v, w)) in rasterize_triangle(a, b, c, bounds, 8, 8).unwrap() {
+                covered[j as usize * 8 + i as usize] += 1;
+                assert!(u >= 0.0 && v >= 0.0 && w >= 0.0);
+                let x = a.x * u + b.x * v + c.x * w;
+                let y = a.y * u + b.y * v + c.y * w;
+                assert!((x - (i as f64 + 0.5)).abs() < 1e-9);
+                assert!((y - (j as f64 + 0.5)).abs() < 1e-9);
+            }
+        }
+        let bounds = (
+            TriangulationPoint::new(-0.5, -0.5),
+            TriangulationPoint::new(7.5, 7.5),
+        );
+        let mut on_edges = vec![0; 64];
+        for t in &tris {
+            let (a, b, c) = (&points[t.0], &points[t.1], &points[t.2]);
+            for (i, j, _) in rasterize_triangle(a, b, c, bounds, 8, 8).unwrap() {
+                on_edges[j as usize * 8 + i as usize] += 1;
+            }
+        }
+        assert!(covered.iter().all(|&count| count == 1));
+        assert!(on_edges.iter().all(|&count| count <= 1));
+
+        let flat = TriangulationPoint::new(2.0, 2.0);
+        assert_eq!(
+            rasterize_triangle(&points[0], &flat, &points[4], bounds, 8, 8)
+                .unwrap()
+                .count(),
+            0
+        );
+    }
+}
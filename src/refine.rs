@@ -0,0 +1,464 @@
+// Copyright 2017-2018 Samuel Loretan <tynril@gmail.com> -- See LICENSE file
+
+//! Refinement of a triangulation by the insertion of Steiner points.
+
+use std::cmp::Ordering;
+use std::collections::HashSet;
+
+use num_traits::Float;
+
+use super::geometry::{circumcenter, triangle_qualities};
+use super::topology::boundary_edges;
+use super::{
+    sort_points, Coordinate, Edge, Point, Result, Triangle, TriangulateError, Triangulation,
+    TriangulationPoint,
+};
+
+/// Where `refine` inserts the point splitting a poor-quality triangle.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum SteinerStrategy {
+    /// Inserts the center of the circumcircle of the triangle, as Ruppert's algorithm does.
+    ///
+    /// This removes the triangle, and all the ones whose circumcircle contains that center:
+    /// the new triangles are well-shaped, and the refinement terminates as long as the quality
+    /// threshold is at least `sqrt(2)`. Like in Ruppert's algorithm, a circumcenter lying within
+    /// the diametral circle of an edge of the hull (including outside of the triangulation)
+    /// isn't inserted, and that edge is split in its middle instead.
+    Circumcenter,
+    /// Inserts the centroid of the triangle, which always lies inside of it.
+    ///
+    /// This never adds points outside of the triangulation, but splitting a thin triangle at its
+    /// centroid mostly makes thinner ones: the triangles get smaller, not necessarily better, so
+    /// the number of inserted points has to be bounded for the refinement to terminate.
+    Centroid,
+}
+
+/// Triangulates a set of points, and inserts new points until all the triangles have a good
+/// quality.
+///
+/// The quality of a triangle is the ratio of its circumradius to its shortest edge, as computed
+/// by `triangle_qualities`. While a triangle has a ratio above `max_ratio`, the worst one is split
+/// by inserting a new point (a Steiner point), chosen according to the `strategy`. At most
+/// `max_steiner_points` are inserted, which guarantees that the refinement terminates, even when
+/// the quality can't be reached. This happens when the hull has a sharp angle, as the triangle
+/// in that corner keeps it whatever the inserted points. Degenerate triangles, whose ratio is
+/// infinite, are left as is.
+///
+/// The returned points are the input ones, in the same order, followed by the Steiner points,
+/// and the triangles index into them. Unlike `triangulate`, the input points don't need to be
+/// sorted. Returns `TriangulateError::NotEnoughPoints` if there are fewer than three points, and
+/// `TriangulateError::Degenerate` if they are all coincident.
+///
+/// Example:
+///
+/// ```rust
+/// use rtriangulate::{refine, triangle_qualities, SteinerStrategy, TriangulationPoint};
+///
+/// let points = [
+///     TriangulationPoint::new(0.0, 0.0),
+///     TriangulationPoint::new(0.0, 3.0),
+///     TriangulationPoint::new(20.0, 0.0),
+///     TriangulationPoint::new(20.0, 3.0)
+/// ];
+/// let (points, triangles) = refine(&points, 1.5, 100, SteinerStrategy::Circumcenter).unwrap();
+/// assert!(points.len() > 4);
+/// assert!(triangle_qualities(&points, &triangles).iter().all(|&q| q <= 1.5));
+/// ```
+pub fn refine<T, P>(
+    points: &[P],
+    max_ratio: T,
+    max_steiner_points: usize,
+    strategy: SteinerStrategy,
+) -> Result<(Vec<TriangulationPoint<T>>, Vec<Triangle>)>
+where
+    T: Float + Coordinate,
+    P: Point<T>,
+{
+    if points.len() < 3 {
+        return Err(TriangulateError::NotEnoughPoints);
+    }
+
+    // Every Steiner point lies within the hull of the input points, which bounds them all.
+    let (min, max) = points.iter().fold(
+        (
+            TriangulationPoint::<T>::infinity(),
+            TriangulationPoint::<T>::neg_infinity(),
+        ),
+        |(min, max), p| {
+            (
+                TriangulationPoint::new(Float::min(min.x, p.x()), Float::min(min.y, p.y())),
+                TriangulationPoint::new(Float::max(max.x, p.x()), Float::max(max.y, p.y())),
+            )
+        },
+    );
+    let mut triangulation = Triangulation::new(min, max)?;
+    for p in points {
+        triangulation.insert(p);
+    }
+
+    let unsupported = || TriangulateError::UnsupportedCoordinate;
+    let half = T::from_f64(0.5).ok_or_else(unsupported)?;
+    let three = T::from_f64(3.0).ok_or_else(unsupported)?;
+    let mut steiner_points = 0;
+    loop {
+        let triangles = triangulation.triangles();
+        let points = triangulation.points();
+        if steiner_points == max_steiner_points {
+            return Ok((points.to_vec(), triangles));
+        }
+
+        let worst = triangle_qualities(points, &triangles)
+            .into_iter()
+            .enumerate()
+            .filter(|&(_, q)| q > max_ratio && Float::is_finite(q))
+            .fold(None, |worst: Option<(usize, T)>, (i, q)| match worst {
+                Some((_, worst_q)) if worst_q >= q => worst,
+                _ => Some((i, q)),
+            });
+        let t = match worst {
+            Some((t, _)) => triangles[t],
+            None => return Ok((points.to_vec(), triangles)),
+        };
+
+        let (a, b, c) = (&points[t.0], &points[t.1], &points[t.2]);
+        let centroid =
+            TriangulationPoint::new((a.x + b.x + c.x) / three, (a.y + b.y + c.y) / three);
+        let steiner = match strategy {
+            SteinerStrategy::Circumcenter => {
+                let center = circumcenter(points, &t)
+                    .filter(|center| triangles.iter().any(|t| contains(points, t, center)))
+                    .unwrap_or_else(|| longest_edge_midpoint(points, &t, half));
+
+                // A point within the diametral circle of a boundary edge would make a thin
+                // triangle with it, so that edge is split in its middle instead.
+                let mut hull: Vec<Edge> = boundary_edges(&triangles).into_iter().collect();
+                hull.sort();
+                hull.iter()
+                    .map(|e| {
+                        let middle = midpoint(&points[e.0], &points[e.1], half);
+                        (middle, distance_sq(&middle, &points[e.0]))
+                    })
+                    .find(|&(middle, radius_sq)| distance_sq(&middle, &center) < radius_sq)
+                    .map_or(center, |(middle, _)| middle)
+            }
+            SteinerStrategy::Centroid => centroid,
+        };
+        triangulation.insert(&steiner);
+        steiner_points += 1;
+    }
+}
+
+/// Triangulates a set of points, and inserts new points until all the constraint edges are part of
+/// the triangulation.
+///
+/// The result is a conforming Delaunay triangulation: every `constraints` edge which is missing
+/// from the Delaunay triangulation is split in its middle by a new point (a Steiner point), and
+/// so on for its two halves, until each piece is an edge of the mesh. The constraints are then
+/// preserved as chains of edges, and the whole mesh stays Delaunay. A constrained Delaunay
+/// triangulation instead keeps the constraint edges whole, without adding any point, at the cost
+/// of the Delaunay property around them. If a point lies exactly on a constraint edge, the edge is
+/// split at that point instead of adding a new one.
+///
+/// Edges close to each other, such as two constraints meeting at a sharp angle, can need a lot of
+/// splits, so at most `max_steiner_points` points are inserted: when that limit is reached, some
+/// pieces of the constraints can still be missing from the mesh.
+///
+/// The constraints are pairs of indices into `points`. The returned points are the input ones, in
+/// the same order, followed by the Steiner points, and the triangles index into them. Of identical
+/// input points, only the first one is used by the triangles, and constraints using the others
+/// are joined to it. Unlike
+/// `triangulate`, the input points don't need to be sorted. Returns
+/// `TriangulateError::ConstraintOutOfRange` with the first edge which has a vertex out of range of
+/// the points, `TriangulateError::NotEnoughPoints` if there are fewer than three points, and
+/// `TriangulateError::Degenerate` if they are all coincident.
+///
+/// Example:
+///
+/// ```rust
+/// use rtriangulate::{triangulate_conforming, Edge, TriangulationPoint};
+///
+/// let points = [
+///     TriangulationPoint::new(0.0, 0.0),
+///     TriangulationPoint::new(4.0, 1.0),
+///     TriangulationPoint::new(8.0, 0.0),
+///     TriangulationPoint::new(4.0, -1.0)
+/// ];
+/// let (points, triangles) = triangulate_conforming(&points, &[Edge(0, 2)], 10).unwrap();
+/// assert_eq!(points[4], TriangulationPoint::new(4.0, 0.0));
+/// assert!(triangles.iter().flat_map(|t| t.edges()).any(|e| e == Edge(0, 4)));
+/// assert!(triangles.iter().flat_map(|t| t.edges()).any(|e| e == Edge(4, 2)));
+/// ```
+pub fn triangulate_conforming<T, P>(
+    points: &[P],
+    constraints: &[Edge],
+    max_steiner_points: usize,
+) -> Result<(Vec<TriangulationPoint<T>>, Vec<Triangle>)>
+where
+    T: Coordinate,
+    P: Point<T>,
+{
+    if let Some(edge) = constraints
+        .iter()
+        .find(|e| e.0 >= points.len() || e.1 >= points.len())
+    {
+        return Err(TriangulateError::ConstraintOutOfRange { edge: edge.clone() });
+    }
+    if points.len() < 3 {
+        return Err(TriangulateError::NotEnoughPoints);
+    }
+
+    // Every Steiner point lies on a constraint, so within the bounds of the input points.
+    let (min, max) = points.iter().fold(
+        (
+            TriangulationPoint::<T>::infinity(),
+            TriangulationPoint::<T>::neg_infinity(),
+        ),
+        |(min, max), p| {
+            (
+                TriangulationPoint::new(min.x.min(p.x()), min.y.min(p.y())),
+                TriangulationPoint::new(max.x.max(p.x()), max.y.max(p.y())),
+            )
+        },
+    );
+    let mut triangulation = Triangulation::new(min, max)?;
+
+    // Only insert the first of identical points, and map the indices of the points to the ones in
+    // the triangulation, and back.
+    let mut by_position: Vec<usize> = (0..points.len()).collect();
+    by_position.sort_by(|&a, &b| sort_points(&points[a], &points[b]).then(a.cmp(&b)));
+    let mut first: Vec<usize> = (0..points.len()).collect();
+    for w in by_position.windows(2) {
+        if sort_points(&points[w[0]], &points[w[1]]) == Ordering::Equal {
+            first[w[1]] = first[w[0]];
+        }
+    }
+    let mut inserted = vec![0; points.len()];
+    let mut original = Vec::with_capacity(points.len());
+    for (i, p) in points.iter().enumerate() {
+        if first[i] == i {
+            triangulation.insert(p);
+            inserted[i] = original.len();
+            original.push(i);
+        } else {
+            inserted[i] = inserted[first[i]];
+        }
+    }
+
+    // The Steiner points come after all the input points, duplicates included.
+    let output = |triangulation: &Triangulation<T>, triangles: Vec<Triangle>| {
+        let index = |k: usize| match original.get(k) {
+            Some(&i) => i,
+            None => k - original.len() + points.len(),
+        };
+        let mut all: Vec<TriangulationPoint<T>> = points
+            .iter()
+            .map(|p| TriangulationPoint::new(p.x(), p.y()))
+            .collect();
+        all.extend_from_slice(&triangulation.points()[original.len()..]);
+        let triangles = triangles
+            .iter()
+            .map(|t| Triangle(index(t.0), index(t.1), index(t.2)))
+            .collect();
+        (all, triangles)
+    };
+
+    let half = T::from_f64(0.5).ok_or(TriangulateError::UnsupportedCoordinate)?;
+    let mut segments: Vec<Edge> = constraints
+        .iter()
+        .map(|e| Edge(inserted[e.0], inserted[e.1]))
+        .filter(|e| e.0 != e.1)
+        .collect();
+    let mut steiner_points = 0;
+    loop {
+        let triangles = triangulation.triangles();
+        let edges: HashSet<Edge> = triangles.iter().flat_map(|t| t.edges()).collect();
+        let missing = match segments.iter().position(|e| !edges.contains(e)) {
+            Some(missing) => missing,
+            None => return Ok(output(&triangulation, triangles)),
+        };
+
+        let (a, b) = (segments[missing].0, segments[missing].1);
+        let split = {
+            let points = triangulation.points();
+            let (p, q) = (&points[a], &points[b]);
+            points.iter().position(|r| {
+                let cross = (q.x - p.x) * (r.y - p.y) - (q.y - p.y) * (r.x - p.x);
+                let along = (r.x - p.x) * (q.x - p.x) + (r.y - p.y) * (q.y - p.y);
+                let length_sq = (q.x - p.x) * (q.x - p.x) + (q.y - p.y) * (q.y - p.y);
+                cross == T::zero() && along > T::zero() && along < length_sq
+            })
+        };
+        let split = match split {
+            Some(split) => split,
+            None if steiner_points == max_steiner_points => {
+                return Ok(output(&triangulation, triangles));
+            }
+            None => {
+                let (p, q) = (triangulation.points()[a], triangulation.points()[b]);
+                triangulation.insert(&TriangulationPoint::new(
+                    (p.x + q.x) * half,
+                    (p.y + q.y) * half,
+                ));
+                steiner_points += 1;
+                triangulation.points().len() - 1
+            }
+        };
+        segments[missing] = Edge(a, split);
+        segments.push(Edge(split, b));
+    }
+}
+
+/// Returns the middle of the longest edge of a triangle.
+fn longest_edge_midpoint<T: Float>(
+    points: &[TriangulationPoint<T>],
+    t: &Triangle,
+    half: T,
+) -> TriangulationPoint<T> {
+    let length_sq = |e: &Edge| distance_sq(&points[e.0], &points[e.1]);
+    let edges = t.edges();
+    let longest = edges.iter().fold(&edges[0], |longest, e| {
+        if length_sq(e) > length_sq(longest) {
+            e
+        } else {
+            longest
+        }
+    });
+    midpoint(&points[longest.0], &points[longest.1], half)
+}
+
+/// Returns the point halfway between two points, given one half as a `T`.
+fn midpoint<T: Float>(
+    a: &TriangulationPoint<T>,
+    b: &TriangulationPoint<T>,
+    half: T,
+) -> TriangulationPoint<T> {
+    TriangulationPoint::new((a.x + b.x) * half, (a.y + b.y) * half)
+}
+
+/// Returns the squared distance between two points.
+fn distance_sq<T: Float>(a: &TriangulationPoint<T>, b: &TriangulationPoint<T>) -> T {
+    (b.x - a.x) * (b.x - a.x) + (b.y - a.y) * (b.y - a.y)
+}
+
+/// Returns true if a point is inside of a triangle, or on its boundary.
+fn contains<T: Float>(
+    points: &[TriangulationPoint<T>],
+    t: &Triangle,
+    p: &TriangulationPoint<T>,
+) -> bool {
+    let side = |a: &TriangulationPoint<T>, b: &TriangulationPoint<T>| {
+        (b.x - a.x) * (p.y - a.y) - (b.y - a.y) * (p.x - a.x)
+    };
+    let (a, b, c) = (&points[t.0], &points[t.1], &points[t.2]);
+    let sides = [side(a, b), side(b, c), side(c, a)];
+    sides.iter().all(|&s| s >= T::zero()) || sides.iter().all(|&s| s <= T::zero())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{
+        is_delaunay, triangle_qualities, Edge, TriangulateError, TriangulationPoint,
+    };
+    use super::{refine, triangulate_conforming, SteinerStrategy};
+
+    fn thin_points() -> Vec<TriangulationPoint<f64>> {
+        vec![
+            TriangulationPoint::new(0.0, 0.0),
+            TriangulationPoint::new(20.0, 0.0),
+            TriangulationPoint::new(20.0, 3.0),
+            TriangulationPoint::new(0.0, 3.0),
+            TriangulationPoint::new(9.0, 1.0),
+        ]
+    }
+
+    #[test]
+    fn test_refine_circumcenter() {
+        let points = thin_points();
+        let (refined, tris) = refine(&points, 1.5, 1000, SteinerStrategy::Circumcenter).unwrap();
+        assert_eq!(refined[..points.len()], points[..]);
+        assert!(refined.len() > points.len());
+        assert!(triangle_qualities(&refined, &tris)
+            .iter()
+            .all(|&q| q <= 1.5));
+        assert!(is_delaunay(&refined, &tris));
+
+        // The Steiner points all stay within the rectangle.
+        assert!(refined
+            .iter()
+            .all(|p| p.x >= 0.0 && p.x <= 20.0 && p.y >= 0.0 && p.y <= 3.0));
+    }
+
+    #[test]
+    fn test_refine_centroid() {
+        let points = thin_points();
+        let (refined, tris) = refine(&points, 1.0, 20, SteinerStrategy::Centroid).unwrap();
+        assert_eq!(refined.len(), points.len() + 20);
+        assert!(is_delaunay(&refined, &tris));
+
+        let (refined, _) = refine(&points, 1e9, 20, SteinerStrategy::Centroid).unwrap();
+        assert_eq!(refined, points);
+    }
+
+    #[test]
+    fn test_conforming() {
+        let points = thin_points();
+        let constraints = [Edge(0, 2), Edge(1, 3), Edge(4, 4)];
+        let (conforming, tris) = triangulate_conforming(&points, &constraints, 100).unwrap();
+        assert_eq!(conforming[..points.len()], points[..]);
+        assert!(is_delaunay(&conforming, &tris));
+
+        // Both diagonals of the rectangle are chains of edges, through the points on them.
+        let edges: Vec<Edge> = tris.iter().flat_map(|t| t.edges()).collect();
+        for e in &constraints[..2] {
+            let (p, q) = (conforming[e.0], conforming[e.1]);
+            let along = |i: usize| {
+                let r = &conforming[i];
+                (r.x - p.x) * (q.x - p.x) + (r.y - p.y) * (q.y - p.y)
+            };
+            let mut chain: Vec<usize> = (0..conforming.len())
+                .filter(|&i| {
+                    let r = &conforming[i];
+                    (q.x - p.x) * (r.y - p.y) - (q.y - p.y) * (r.x - p.x) == 0.0
+                })
+                .collect();
+            chain.sort_by(|&a, &b| along(a).partial_cmp(&along(b)).unwrap());
+            assert!(chain.len() > 2);
+            assert!(chain.windows(2).all(|w| edges.contains(&Edge(w[0], w[1]))));
+        }
+
+        let (limited, _) = triangulate_conforming(&points, &constraints, 1).unwrap();
+        assert_eq!(limited.len(), points.len() + 1);
+    }
+
+    #[test]
+    fn test_conforming_duplicates() {
+        // The first point is repeated at the end, and only its last copy is in a constraint.
+        let mut points = thin_points();
+        points.push(points[0]);
+        let (conforming, tris) = triangulate_conforming(&points, &[Edge(5, 2)], 100).unwrap();
+        assert_eq!(conforming[..points.len()], points[..]);
+        assert!(is_delaunay(&conforming, &tris));
+        assert!(tris.iter().all(|t| t.0 != 5 && t.1 != 5 && t.2 != 5));
+
+        // The diagonal is a chain of edges from the first copy.
+        let edges: Vec<Edge> = tris.iter().flat_map(|t| t.edges()).collect();
+        let mut chain: Vec<usize> = (0..conforming.len())
+            .filter(|&i| i != 5 && conforming[i].y * 20.0 == conforming[i].x * 3.0)
+            .collect();
+        chain.sort_by(|&a, &b| conforming[a].x.partial_cmp(&conforming[b].x).unwrap());
+        assert_eq!((chain[0], chain[chain.len() - 1]), (0, 2));
+        assert!(chain.len() > 2);
+        assert!(chain.windows(2).all(|w| edges.contains(&Edge(w[0], w[1]))));
+    }
+
+    #[test]
+    fn test_conforming_out_of_range() {
+        let points = thin_points();
+        for edge in &[Edge(5, 0), Edge(1, 100)] {
+            match triangulate_conforming(&points, &[Edge(0, 2), edge.clone()], 100) {
+                Err(TriangulateError::ConstraintOutOfRange { edge: e }) => assert_eq!(e, *edge),
+                other => panic!("unexpected result: {:?}", other),
+            }
+        }
+    }
+}
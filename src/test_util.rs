@@ -0,0 +1,286 @@
+// Copyright 2017-2018 Samuel Loretan <tynril@gmail.com> -- See LICENSE file
+
+//! Utilities to test and benchmark code using triangulations.
+
+use num_traits::float::FloatCore;
+
+use super::hull::monotone_chain;
+use super::validate::first_delaunay_violation;
+use super::xorshift::XorShift;
+use super::{sort_points, Point, Triangle, TriangulationPoint};
+
+/// Generates `count` pseudo-random points within `bounds`, sorted as `triangulate` requires.
+///
+/// The `bounds` are the minimum and maximum corners of the rectangle the points are uniformly
+/// spread in. The points are generated with a small xorshift generator, without any dependency:
+/// the same `seed` always gives exactly the same points, on any platform. This makes for
+/// reproducible inputs for tests, fuzzing and benchmarks, but isn't suitable for anything
+/// requiring statistical or cryptographic quality.
+///
+/// Example:
+///
+/// ```rust
+/// use rtriangulate::{random_points, triangulate, TriangulationPoint};
+///
+/// let bounds = (TriangulationPoint::new(0.0, 0.0), TriangulationPoint::new(100.0, 100.0));
+/// let points = random_points(1000, 42, bounds);
+/// assert_eq!(points, random_points(1000, 42, bounds));
+/// assert!(triangulate(&points).is_ok());
+/// ```
+pub fn random_points(
+    count: usize,
+    seed: u64,
+    bounds: (TriangulationPoint<f64>, TriangulationPoint<f64>),
+) -> Vec<TriangulationPoint<f64>> {
+    let (min, max) = bounds;
+    let mut rng = XorShift::new(seed);
+    let mut points: Vec<_> = (0..count)
+        .map(|_| {
+            let x = min.x + rng.next_f64() * (max.x - min.x);
+            let y = min.y + rng.next_f64() * (max.y - min.y);
+            TriangulationPoint::new(x, y)
+        })
+        .collect();
+    points.sort_by(sort_points);
+    points
+}
+
+/// Generates `count` pseudo-random point quadruples which are close to being cocircular.
+///
+/// The first three points of every quadruple are on a circle of random center and radius, and
+/// the fourth one is moved off that circle, along its radius, by a random relative amount of at
+/// most `perturbation`. Each quadruple comes with whether the fourth point is strictly inside of
+/// the circle, before the coordinates get rounded to `f64`: this is only reliable when the
+/// `perturbation` is well above the `f64` precision, e.g. `1e-12`.
+///
+/// These are adversarial inputs for incircle predicates, which have to decide on which side of
+/// the circumcircle of the first three points the fourth one lies, with rounding errors of the
+/// same order as the distance to the circle. Like `random_points`, the same `seed` always gives
+/// the same quadruples.
+///
+/// Example:
+///
+/// ```rust
+/// use rtriangulate::{is_delaunay, near_cocircular_points, Triangle};
+///
+/// for &(points, inside) in &near_cocircular_points(100, 42, 1e-3) {
+///     assert_eq!(is_delaunay(&points, &[Triangle(0, 1, 2)]), !inside);
+/// }
+/// ```
+pub fn near_cocircular_points(
+    count: usize,
+    seed: u64,
+    perturbation: f64,
+) -> Vec<([TriangulationPoint<f64>; 4], bool)> {
+    let mut rng = XorShift::new(seed);
+    (0..count)
+        .map(|_| {
+            let center_x = rng.next_f64() * 2000.0 - 1000.0;
+            let center_y = rng.next_f64() * 2000.0 - 1000.0;
+            let radius = 1.0 + rng.next_f64() * 999.0;
+            let delta = (rng.next_f64() * 2.0 - 1.0) * perturbation;
+
+            let mut on_circle = |scale: f64| {
+                let angle = rng.next_f64() * 2.0 * ::std::f64::consts::PI;
+                TriangulationPoint::new(
+                    center_x + radius * scale * angle.cos(),
+                    center_y + radius * scale * angle.sin(),
+                )
+            };
+            let points = [
+                on_circle(1.0),
+                on_circle(1.0),
+                on_circle(1.0),
+                on_circle(1.0 + delta),
+            ];
+            (points, delta < 0.0)
+        })
+        .collect()
+}
+
+/// Panics if the triangles aren't a valid Delaunay triangulation of the points.
+///
+/// This is meant to be used in tests, to guard against invalid meshes. The points must be sorted
+/// as `triangulate` requires, and the following properties are checked, in that order:
+///
+/// * every triangle only references existing points,
+/// * no point lies strictly inside the circumcircle of a triangle (see `is_delaunay`),
+/// * the triangles cover the convex hull of the points: the sum of their areas equals the area of
+///   the hull, up to a small rounding tolerance.
+///
+/// The panic message identifies the first offending triangle and point, when there is one.
+///
+/// Note that `triangulate` can miss very thin triangles along the convex hull of the points, when
+/// their circumcircle is so large that it contains a vertex of the supertriangle. This reports
+/// such triangulations as not covering the area of the hull.
+///
+/// Example:
+///
+/// ```rust
+/// use rtriangulate::{assert_valid_triangulation, triangulate, TriangulationPoint};
+///
+/// let points = [
+///     TriangulationPoint::new(10.0, 10.0),
+///     TriangulationPoint::new(15.0, 25.0),
+///     TriangulationPoint::new(25.0, 15.0),
+///     TriangulationPoint::new(30.0, 25.0)
+/// ];
+/// assert_valid_triangulation(&points, &triangulate(&points).unwrap());
+/// ```
+pub fn assert_valid_triangulation<T, P>(points: &[P], triangles: &[Triangle])
+where
+    T: FloatCore + ::std::fmt::Debug,
+    P: Point<T>,
+{
+    for (i, t) in triangles.iter().enumerate() {
+        for &vertex in &[t.0, t.1, t.2] {
+            assert!(
+                vertex < points.len(),
+                "triangle {} ({:?}) references point {}, but there are only {} points",
+                i,
+                t,
+                vertex,
+                points.len()
+            );
+        }
+    }
+
+    if let Some((triangle, point)) = first_delaunay_violation(points, triangles) {
+        panic!(
+            "point {} lies inside the circumcircle of triangle {} ({:?})",
+            point, triangle, triangles[triangle]
+        );
+    }
+
+    let two = T::one() + T::one();
+    let doubled_area = |a: &P, b: &P, c: &P| {
+        ((b.x() - a.x()) * (c.y() - a.y()) - (b.y() - a.y()) * (c.x() - a.x())).abs()
+    };
+    let triangles_area = triangles.iter().fold(T::zero(), |area, t| {
+        area + doubled_area(&points[t.0], &points[t.1], &points[t.2])
+    }) / two;
+
+    let hull = monotone_chain(points);
+    let hull_area = if hull.len() < 3 {
+        T::zero()
+    } else {
+        hull.iter()
+            .skip(2)
+            .zip(hull.iter().skip(1))
+            .fold(T::zero(), |area, (&c, &b)| {
+                area + doubled_area(&points[hull[0]], &points[b], &points[c])
+            })
+            / two
+    };
+
+    let tolerance = T::epsilon() * T::from(64 * points.len().max(1)).unwrap() * hull_area;
+    assert!(
+        (triangles_area - hull_area).abs() <= tolerance,
+        "the triangles cover an area of {:?}, but the convex hull of the points has an area of {:?}",
+        triangles_area,
+        hull_area
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+
+    use super::super::{is_delaunay, sort_points, triangulate, Triangle, TriangulationPoint};
+    use super::{assert_valid_triangulation, near_cocircular_points, random_points};
+
+    #[test]
+    fn test_random_points() {
+        let bounds = (
+            TriangulationPoint::new(-10.0, 5.0),
+            TriangulationPoint::new(10.0, 6.0),
+        );
+        let points = random_points(500, 1, bounds);
+
+        assert_eq!(points.len(), 500);
+        assert_eq!(points, random_points(500, 1, bounds));
+        assert_ne!(points, random_points(500, 2, bounds));
+        assert!(points
+            .iter()
+            .all(|p| p.x >= -10.0 && p.x < 10.0 && p.y >= 5.0 && p.y < 6.0));
+        assert!(points
+            .windows(2)
+            .all(|w| sort_points(&w[0], &w[1]) != ::std::cmp::Ordering::Greater));
+    }
+
+    #[test]
+    fn test_random_points_zero_seed() {
+        let bounds = (
+            TriangulationPoint::new(0.0, 0.0),
+            TriangulationPoint::new(1.0, 1.0),
+        );
+        let points = random_points(200, 0, bounds);
+        assert!(points.windows(2).all(|w| w[0] != w[1]));
+        assert!(is_delaunay(&points, &triangulate(&points).unwrap()));
+    }
+
+    #[test]
+    fn test_near_cocircular_points() {
+        let quadruples = near_cocircular_points(1000, 3, 1e-6);
+        assert_eq!(quadruples, near_cocircular_points(1000, 3, 1e-6));
+
+        let inside = quadruples.iter().filter(|q| q.1).count();
+        assert!(inside > 400 && inside < 600);
+        assert!(near_cocircular_points(1000, 3, 0.0).iter().all(|q| !q.1));
+    }
+
+    #[test]
+    fn test_assert_valid_triangulation() {
+        // Points along concentric circles, with a convex and well-spread hull.
+        let mut points = vec![TriangulationPoint::new(0.0, 0.0)];
+        for ring in 1..6 {
+            for i in 0..(6 * ring) {
+                let angle = (i as f64 + 0.5 * ring as f64) / (6 * ring) as f64 * 2.0 * PI;
+                let radius = ring as f64 * 10.0;
+                points.push(TriangulationPoint::new(
+                    radius * angle.cos(),
+                    radius * angle.sin(),
+                ));
+            }
+        }
+        points.sort_by(sort_points);
+        assert_valid_triangulation(&points, &triangulate(&points).unwrap());
+    }
+
+    #[test]
+    #[should_panic(expected = "point 2 lies inside the circumcircle of triangle 0")]
+    fn test_assert_valid_triangulation_not_delaunay() {
+        let points = [
+            TriangulationPoint::new(10.0, 10.0),
+            TriangulationPoint::new(15.0, 25.0),
+            TriangulationPoint::new(25.0, 15.0),
+            TriangulationPoint::new(30.0, 25.0),
+        ];
+        assert_valid_triangulation(&points, &[Triangle(0, 1, 3), Triangle(0, 3, 2)]);
+    }
+
+    #[test]
+    #[should_panic(expected = "but the convex hull of the points has an area of 175.0")]
+    fn test_assert_valid_triangulation_hole() {
+        let points = [
+            TriangulationPoint::new(10.0, 10.0),
+            TriangulationPoint::new(15.0, 25.0),
+            TriangulationPoint::new(25.0, 15.0),
+            TriangulationPoint::new(30.0, 25.0),
+        ];
+        let mut tris = triangulate(&points).unwrap();
+        tris.pop();
+        assert_valid_triangulation(&points, &tris);
+    }
+
+    #[test]
+    #[should_panic(expected = "triangle 0 (Triangle(0, 1, 3)) references point 3")]
+    fn test_assert_valid_triangulation_out_of_range() {
+        let points = [
+            TriangulationPoint::new(10.0, 10.0),
+            TriangulationPoint::new(15.0, 25.0),
+            TriangulationPoint::new(25.0, 15.0),
+        ];
+        assert_valid_triangulation(&points, &[Triangle(0, 1, 3)]);
+    }
+}
@@ -0,0 +1,354 @@
+// Copyright 2017-2018 Samuel Loretan <tynril@gmail.com> -- See LICENSE file
+
+//! Building a large triangulation out of independently triangulated tiles.
+
+use std::cmp::Ordering;
+use std::collections::HashSet;
+
+use super::validate::{circle_tolerance, circumcircle};
+use super::{triangulate, Coordinate, Point, Result, Triangle, TriangulationPoint};
+
+/// Merges the triangulations of two adjacent tiles into a single Delaunay triangulation.
+///
+/// Both tiles are given as triangles indexing into the same slice of `points`, which must be
+/// sorted the same way `triangulate` requires. The tiles must overlap over a vertical strip of
+/// points, whose bounds are given by `seam` as `(seam_min, seam_max)`:
+///
+/// * `left` is the triangulation of every point with `x <= seam_max`,
+/// * `right` is the triangulation of every point with `x >= seam_min`.
+///
+/// The triangles of a tile whose circumcircle doesn't reach the strip are kept as-is, and the
+/// remaining region is re-triangulated from the points of the other triangles. The strip must be
+/// wide enough for the final triangulation not to have any triangle connecting a point left of
+/// the strip to a point right of it, otherwise the merged mesh has holes along the seam. A strip a
+/// few times wider than the typical distance between points is usually enough.
+///
+/// Like for `triangulate`, the points should be in general position: if four or more points of
+/// the seam region lie on the same circle, the tiles can disagree on which diagonal to use.
+///
+/// Example:
+///
+/// ```rust
+/// use rtriangulate::{merge_triangulations, triangulate, TriangulationPoint};
+///
+/// let points = [
+///     TriangulationPoint::new(0.0, 0.0),
+///     TriangulationPoint::new(1.0, 5.0),
+///     TriangulationPoint::new(3.0, 1.0),
+///     TriangulationPoint::new(4.0, 4.0),
+///     TriangulationPoint::new(6.0, 0.5),
+///     TriangulationPoint::new(7.0, 4.5)
+/// ];
+///
+/// // Both tiles overlap for x between 3 and 4.
+/// let left = triangulate(&points[..4]).unwrap();
+/// let right: Vec<_> = triangulate(&points[2..])
+///     .unwrap()
+///     .iter()
+///     .map(|t| rtriangulate::Triangle(t.0 + 2, t.1 + 2, t.2 + 2))
+///     .collect();
+///
+/// let merged = merge_triangulations(&points, &left, &right, (3.0, 4.0)).unwrap();
+/// assert_eq!(merged.len(), triangulate(&points).unwrap().len());
+/// ```
+pub fn merge_triangulations<T, P>(
+    points: &[P],
+    left: &[Triangle],
+    right: &[Triangle],
+    seam: (T, T),
+) -> Result<Vec<Triangle>>
+where
+    T: Coordinate,
+    P: Point<T>,
+{
+    let (seam_min, seam_max) = seam;
+
+    // Keep the triangles whose circumcircle stays on their side of the strip: as it doesn't
+    // contain any point of its tile, it can't contain any point of the other one either.
+    let mut merged = Vec::with_capacity(left.len() + right.len());
+    let mut seam_points = Vec::new();
+    for (triangles, left_side) in [(left, true), (right, false)].iter() {
+        for t in triangles.iter() {
+            let keep = match circumcircle(&points[t.0], &points[t.1], &points[t.2]) {
+                Some((center_x, _, radius_sq)) => {
+                    let gap = if *left_side {
+                        seam_min - center_x
+                    } else {
+                        center_x - seam_max
+                    };
+                    gap > T::zero() && gap * gap > radius_sq
+                }
+                None => false,
+            };
+            if keep {
+                merged.push(*t);
+            } else {
+                seam_points.extend_from_slice(&[t.0, t.1, t.2]);
+            }
+        }
+    }
+    seam_points.sort();
+    seam_points.dedup();
+    if seam_points.len() < 3 {
+        return Ok(merged);
+    }
+
+    // Re-triangulate the points of the seam. As the points are sorted, so is the subset.
+    let subset: Vec<TriangulationPoint<T>> = seam_points
+        .iter()
+        .map(|&i| TriangulationPoint::new(points[i].x(), points[i].y()))
+        .collect();
+    let mut existing: HashSet<[usize; 3]> = merged.iter().map(sorted_indices).collect();
+    for t in triangulate(&subset)? {
+        let t = Triangle(seam_points[t.0], seam_points[t.1], seam_points[t.2]);
+        if is_empty_circumcircle(points, &t) && existing.insert(sorted_indices(&t)) {
+            merged.push(t);
+        }
+    }
+
+    Ok(merged)
+}
+
+/// Splits the bounding box of the points into tiles holding about `points_per_tile` points each.
+///
+/// The box is split in two at the median of the points, on the x axis, then each half is split
+/// on the y axis, then on the x axis again, and so on, until every tile holds at most
+/// `points_per_tile` points: each tile ends up with between half of that and that many points.
+/// The tiles are the `(min, max)` corners of rectangles which cover the box without overlapping,
+/// in the order of the splits, the lower half first. The splits only depend on the positions of
+/// the points and on their order, so the same input always gives the same tiles. A point on the
+/// side shared by two tiles counts for one of them only.
+///
+/// The tiles can be triangulated with `triangulate_subset`, from the points within them and a
+/// margin around them, then joined with `merge_triangulations`.
+///
+/// Example:
+///
+/// ```rust
+/// use rtriangulate::{suggest_tiling, TriangulationPoint};
+///
+/// let points: Vec<_> = (0..16)
+///     .map(|i| TriangulationPoint::new((i % 4) as f64, (i / 4) as f64))
+///     .collect();
+/// let tiles = suggest_tiling(&points, 4);
+/// assert_eq!(tiles.len(), 4);
+/// assert_eq!(tiles[0], (TriangulationPoint::new(0.0, 0.0), TriangulationPoint::new(2.0, 2.0)));
+/// ```
+pub fn suggest_tiling<T, P>(
+    points: &[P],
+    points_per_tile: usize,
+) -> Vec<(TriangulationPoint<T>, TriangulationPoint<T>)>
+where
+    T: Coordinate,
+    P: Point<T>,
+{
+    if points.is_empty() {
+        return Vec::new();
+    }
+    let first = TriangulationPoint::new(points[0].x(), points[0].y());
+    let bounds = points.iter().fold((first, first), |(min, max), p| {
+        (
+            TriangulationPoint::new(min.x.min(p.x()), min.y.min(p.y())),
+            TriangulationPoint::new(max.x.max(p.x()), max.y.max(p.y())),
+        )
+    });
+
+    let mut indices: Vec<usize> = (0..points.len()).collect();
+    let mut tiles = Vec::new();
+    split_tile(
+        points,
+        &mut indices,
+        bounds,
+        points_per_tile.max(1),
+        true,
+        &mut tiles,
+    );
+    tiles
+}
+
+/// Splits a tile at the median of its points, recursively, alternating the axes.
+fn split_tile<T, P>(
+    points: &[P],
+    indices: &mut [usize],
+    bounds: (TriangulationPoint<T>, TriangulationPoint<T>),
+    points_per_tile: usize,
+    on_x: bool,
+    tiles: &mut Vec<(TriangulationPoint<T>, TriangulationPoint<T>)>,
+) where
+    T: Coordinate,
+    P: Point<T>,
+{
+    if indices.len() <= points_per_tile {
+        tiles.push(bounds);
+        return;
+    }
+
+    let coordinate = |i: usize| if on_x { points[i].x() } else { points[i].y() };
+    indices.sort_by(|&a, &b| {
+        coordinate(a)
+            .partial_cmp(&coordinate(b))
+            .unwrap_or(Ordering::Equal)
+            .then(a.cmp(&b))
+    });
+    let middle = indices.len() / 2;
+    let median = coordinate(indices[middle]);
+
+    let (min, max) = bounds;
+    let (lower, upper) = if on_x {
+        (
+            (min, TriangulationPoint::new(median, max.y)),
+            (TriangulationPoint::new(median, min.y), max),
+        )
+    } else {
+        (
+            (min, TriangulationPoint::new(max.x, median)),
+            (TriangulationPoint::new(min.x, median), max),
+        )
+    };
+    let (first, second) = indices.split_at_mut(middle);
+    split_tile(points, first, lower, points_per_tile, !on_x, tiles);
+    split_tile(points, second, upper, points_per_tile, !on_x, tiles);
+}
+
+/// The indices of a triangle in increasing order, identifying it regardless of its winding.
+fn sorted_indices(t: &Triangle) -> [usize; 3] {
+    let mut indices = [t.0, t.1, t.2];
+    indices.sort();
+    indices
+}
+
+/// Returns true if no point of the (x-sorted) slice lies strictly inside the circumcircle of the
+/// triangle.
+fn is_empty_circumcircle<T, P>(points: &[P], t: &Triangle) -> bool
+where
+    T: Coordinate,
+    P: Point<T>,
+{
+    let (center_x, center_y, radius_sq) =
+        match circumcircle(&points[t.0], &points[t.1], &points[t.2]) {
+            Some(circle) => circle,
+            None => return false,
+        };
+
+    // Only the points within the horizontal extent of the circle need to be checked.
+    let tolerance = circle_tolerance();
+    let start = points.partition_point(|p| {
+        let dx = center_x - p.x();
+        dx > T::zero() && dx * dx > radius_sq
+    });
+    for (i, p) in points.iter().enumerate().skip(start) {
+        let dx = p.x() - center_x;
+        if dx > T::zero() && dx * dx > radius_sq {
+            break;
+        }
+        if i == t.0 || i == t.1 || i == t.2 {
+            continue;
+        }
+        let dy = p.y() - center_y;
+        let distance_sq = dx * dx + dy * dy;
+        if radius_sq - distance_sq > radius_sq * tolerance {
+            return false;
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::xorshift::XorShift;
+    use super::super::{is_delaunay, sort_points, triangulate, Triangle, TriangulationPoint};
+    use super::{merge_triangulations, sorted_indices, suggest_tiling};
+
+    /// Some pseudo-random points in `[0, 1000[²`, sorted on x.
+    fn scattered_points(count: usize) -> Vec<TriangulationPoint<f64>> {
+        let mut rng = XorShift::new(0);
+        let mut points: Vec<_> = (0..count)
+            .map(|_| TriangulationPoint::new(rng.next_f64() * 1000.0, rng.next_f64() * 1000.0))
+            .collect();
+        points.sort_by(sort_points);
+        points
+    }
+
+    /// Triangulates the points within an x range, with triangles indexing into all the points.
+    fn triangulate_tile(points: &[TriangulationPoint<f64>], min: f64, max: f64) -> Vec<Triangle> {
+        let start = points.iter().position(|p| p.x >= min).unwrap();
+        let end = points.iter().rposition(|p| p.x <= max).unwrap() + 1;
+        triangulate(&points[start..end])
+            .unwrap()
+            .iter()
+            .map(|t| Triangle(t.0 + start, t.1 + start, t.2 + start))
+            .collect()
+    }
+
+    fn sorted_mesh(triangles: &[Triangle]) -> Vec<[usize; 3]> {
+        let mut mesh: Vec<_> = triangles.iter().map(sorted_indices).collect();
+        mesh.sort();
+        mesh
+    }
+
+    #[test]
+    fn test_merge_triangulations() {
+        let points = scattered_points(300);
+        let left = triangulate_tile(&points, 0.0, 600.0);
+        let right = triangulate_tile(&points, 400.0, 1000.0);
+
+        let merged = merge_triangulations(&points, &left, &right, (400.0, 600.0)).unwrap();
+        assert!(is_delaunay(&points, &merged));
+        assert_eq!(
+            sorted_mesh(&merged),
+            sorted_mesh(&triangulate(&points).unwrap())
+        );
+    }
+
+    #[test]
+    fn test_merge_disjoint_tiles() {
+        let points = scattered_points(100);
+        let merged = merge_triangulations(&points, &[], &[], (0.0, 1000.0)).unwrap();
+        assert_eq!(merged, []);
+
+        // With an empty right tile, the left one is merged with itself.
+        let left = triangulate_tile(&points, 0.0, 1000.0);
+        let merged = merge_triangulations(&points, &left, &[], (1000.0, 1000.0)).unwrap();
+        assert_eq!(sorted_mesh(&merged), sorted_mesh(&left));
+    }
+
+    #[test]
+    fn test_suggest_tiling() {
+        let points = scattered_points(300);
+        let tiles = suggest_tiling(&points, 40);
+        assert_eq!(tiles.len(), 8);
+        assert_eq!(suggest_tiling(&points, 40), tiles);
+
+        // The tiles cover the bounding box without overlapping.
+        let area = |min: &TriangulationPoint<f64>, max: &TriangulationPoint<f64>| {
+            (max.x - min.x).max(0.0) * (max.y - min.y).max(0.0)
+        };
+        let (mut min, mut max) = (points[0], points[0]);
+        for p in &points {
+            min = TriangulationPoint::new(min.x.min(p.x), min.y.min(p.y));
+            max = TriangulationPoint::new(max.x.max(p.x), max.y.max(p.y));
+        }
+        let total: f64 = tiles.iter().map(|(a, b)| area(a, b)).sum();
+        assert!((total - area(&min, &max)).abs() < 1e-6);
+        for (i, a) in tiles.iter().enumerate() {
+            for b in &tiles[i + 1..] {
+                let low = TriangulationPoint::new(a.0.x.max(b.0.x), a.0.y.max(b.0.y));
+                let high = TriangulationPoint::new(a.1.x.min(b.1.x), a.1.y.min(b.1.y));
+                assert_eq!(area(&low, &high), 0.0);
+            }
+        }
+
+        // Every tile holds about the same number of points.
+        for (min, max) in &tiles {
+            let count = points
+                .iter()
+                .filter(|p| p.x > min.x && p.x < max.x && p.y > min.y && p.y < max.y)
+                .count();
+            assert!((30..=40).contains(&count));
+        }
+
+        // A single tile covers few enough points.
+        assert_eq!(suggest_tiling(&points, 300), [(min, max)]);
+        assert_eq!(suggest_tiling::<f64, TriangulationPoint<f64>>(&[], 10), []);
+    }
+}
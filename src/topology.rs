@@ -0,0 +1,1049 @@
+// Copyright 2017-2018 Samuel Loretan <tynril@gmail.com> -- See LICENSE file
+
+//! Queries over the connectivity of a triangulation.
+
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use super::{Coordinate, Edge, Point, Triangle};
+
+/// Returns true if the given edge is a boundary edge of the triangulation.
+///
+/// A boundary edge belongs to exactly one triangle. For the output of `triangulate`, those are the
+/// edges of the convex hull. Edges are undirected, so `Edge(0, 1)` and `Edge(1, 0)` are the same.
+///
+/// Example:
+///
+/// ```rust
+/// use rtriangulate::{is_boundary_edge, Edge, Triangle};
+///
+/// let triangles = [Triangle(0, 1, 2), Triangle(2, 1, 3)];
+/// assert!(is_boundary_edge(&triangles, &Edge(0, 1)));
+/// assert!(!is_boundary_edge(&triangles, &Edge(2, 1)));
+/// ```
+pub fn is_boundary_edge(triangles: &[Triangle], edge: &Edge) -> bool {
+    let mut count = 0;
+    for triangle in triangles {
+        count += triangle.edges().iter().filter(|e| *e == edge).count();
+    }
+    count == 1
+}
+
+/// Returns the set of all the boundary edges of the triangulation.
+///
+/// This is the batch version of `is_boundary_edge`, which only goes through the triangles once.
+/// The edges are stored in the direction they have in the triangle they belong to.
+///
+/// Example:
+///
+/// ```rust
+/// use rtriangulate::{boundary_edges, Edge, Triangle};
+///
+/// let triangles = [Triangle(0, 1, 2), Triangle(2, 1, 3)];
+/// let edges = boundary_edges(&triangles);
+/// assert_eq!(edges.len(), 4);
+/// assert!(edges.contains(&Edge(1, 0)));
+/// assert!(!edges.contains(&Edge(1, 2)));
+/// ```
+pub fn boundary_edges(triangles: &[Triangle]) -> HashSet<Edge> {
+    edge_counts(triangles)
+        .into_iter()
+        .filter(|&(_, count)| count == 1)
+        .map(|(edge, _)| edge)
+        .collect()
+}
+
+/// Returns true if every edge of the mesh is shared by at most two triangles.
+///
+/// An edge-manifold mesh has interior edges shared by exactly two triangles, and boundary edges
+/// belonging to a single one. The output of `triangulate` always is, but post-processing it, such
+/// as merging or clipping meshes, can create junctions where more triangles meet. Use
+/// `non_manifold_edges` to find them.
+///
+/// Example:
+///
+/// ```rust
+/// use rtriangulate::{is_edge_manifold, Triangle};
+///
+/// assert!(is_edge_manifold(&[Triangle(0, 1, 2), Triangle(2, 1, 3)]));
+/// assert!(!is_edge_manifold(&[Triangle(0, 1, 2), Triangle(2, 1, 3), Triangle(1, 2, 4)]));
+/// ```
+pub fn is_edge_manifold(triangles: &[Triangle]) -> bool {
+    edge_counts(triangles).values().all(|&count| count <= 2)
+}
+
+/// Returns the edges shared by more than two triangles, along with their number of triangles.
+///
+/// The edges are sorted by their smallest, then largest, vertex index. The mesh is edge-manifold
+/// if this is empty.
+///
+/// Example:
+///
+/// ```rust
+/// use rtriangulate::{non_manifold_edges, Edge, Triangle};
+///
+/// let triangles = [Triangle(0, 1, 2), Triangle(2, 1, 3), Triangle(1, 2, 4)];
+/// assert_eq!(non_manifold_edges(&triangles), [(Edge(1, 2), 3)]);
+/// ```
+pub fn non_manifold_edges(triangles: &[Triangle]) -> Vec<(Edge, usize)> {
+    let mut edges: Vec<(Edge, usize)> = edge_counts(triangles)
+        .into_iter()
+        .filter(|&(_, count)| count > 2)
+        .collect();
+    edges.sort();
+    edges
+}
+
+/// Returns the pairs of triangles made of the same three vertices, in the same winding.
+///
+/// The triangles are compared through `Triangle::canonical`, so that rotations of the same
+/// triangle are equal, but a triangle and its reverse aren't. Every duplicate is paired with the
+/// first occurrence of its triangle, as `(first, duplicate)`, in the order of the duplicates. A
+/// triangle found three times thus makes two pairs.
+///
+/// Example:
+///
+/// ```rust
+/// use rtriangulate::{duplicate_triangles, Triangle};
+///
+/// let triangles = [Triangle(0, 1, 2), Triangle(2, 1, 3), Triangle(1, 2, 0), Triangle(0, 2, 1)];
+/// assert_eq!(duplicate_triangles(&triangles), [(0, 2)]);
+/// ```
+pub fn duplicate_triangles(triangles: &[Triangle]) -> Vec<(usize, usize)> {
+    let mut first = HashMap::<Triangle, usize>::with_capacity(triangles.len());
+    triangles
+        .iter()
+        .enumerate()
+        .filter_map(|(t, triangle)| match first.entry(triangle.canonical()) {
+            Entry::Occupied(entry) => Some((*entry.get(), t)),
+            Entry::Vacant(entry) => {
+                entry.insert(t);
+                None
+            }
+        })
+        .collect()
+}
+
+/// Removes the duplicates found by `duplicate_triangles`, keeping the first occurrence of every
+/// triangle, and the order of the triangles.
+///
+/// Example:
+///
+/// ```rust
+/// use rtriangulate::{dedup_triangles, Triangle};
+///
+/// let mut triangles = vec![Triangle(0, 1, 2), Triangle(2, 1, 3), Triangle(1, 2, 0)];
+/// dedup_triangles(&mut triangles);
+/// assert_eq!(triangles, [Triangle(0, 1, 2), Triangle(2, 1, 3)]);
+/// ```
+pub fn dedup_triangles(triangles: &mut Vec<Triangle>) {
+    let mut seen = HashSet::<Triangle>::with_capacity(triangles.len());
+    triangles.retain(|triangle| seen.insert(triangle.canonical()));
+}
+
+/// Iterates over the edges of the triangulation, yielding each undirected edge exactly once.
+///
+/// The edges are yielded lazily, in the order they are first found in the triangles, and in the
+/// direction they have in that first triangle. To skip the edges which were already yielded, a
+/// `HashSet` of them is kept internally, and grows as the iteration goes.
+///
+/// Example:
+///
+/// ```rust
+/// use rtriangulate::{edge_iter, Edge, Triangle};
+///
+/// let triangles = [Triangle(0, 1, 2), Triangle(2, 1, 3)];
+/// let edges: Vec<Edge> = edge_iter(&triangles).collect();
+/// assert_eq!(edges, [Edge(0, 1), Edge(1, 2), Edge(2, 0), Edge(1, 3), Edge(3, 2)]);
+/// ```
+pub fn edge_iter<'a>(triangles: &'a [Triangle]) -> impl Iterator<Item = Edge> + 'a {
+    let mut seen = HashSet::<Edge>::new();
+    triangles
+        .iter()
+        .flat_map(|t| IntoIterator::into_iter(t.edges()))
+        .filter(move |edge| seen.insert(edge.clone()))
+}
+
+/// Calls `f` with the three points of every triangle, in the order of the triangles.
+///
+/// This streams the geometry of the triangulation to the closure, such as to write it out to any
+/// format, without gathering the points of the triangles into a new collection first.
+///
+/// Example:
+///
+/// ```rust
+/// use rtriangulate::{for_each_triangle, Triangle, TriangulationPoint};
+///
+/// let points = [
+///     TriangulationPoint::new(0.0, 0.0),
+///     TriangulationPoint::new(0.0, 2.0),
+///     TriangulationPoint::new(2.0, 0.0),
+///     TriangulationPoint::new(2.0, 2.0)
+/// ];
+/// let triangles = [Triangle(0, 1, 2), Triangle(2, 1, 3)];
+/// let mut svg = String::new();
+/// for_each_triangle(&points, &triangles, |a, b, c| {
+///     svg += &format!("<polygon points=\"{},{} {},{} {},{}\"/>", a.x, a.y, b.x, b.y, c.x, c.y);
+/// });
+/// assert_eq!(svg, "<polygon points=\"0,0 0,2 2,0\"/><polygon points=\"2,0 0,2 2,2\"/>");
+/// ```
+pub fn for_each_triangle<T, P, F>(points: &[P], triangles: &[Triangle], mut f: F)
+where
+    T: Coordinate,
+    P: Point<T>,
+    F: FnMut(&P, &P, &P),
+{
+    for t in triangles {
+        f(&points[t.0], &points[t.1], &points[t.2]);
+    }
+}
+
+/// Calls `f` with the two points of every edge of the triangulation, visiting each undirected
+/// edge exactly once.
+///
+/// The edges are visited as `edge_iter` yields them, in the order they are first found in the
+/// triangles, and in the direction they have in that first triangle. The set of the edges already
+/// visited is the only thing allocated.
+///
+/// Example:
+///
+/// ```rust
+/// use rtriangulate::{for_each_edge, Triangle, TriangulationPoint};
+///
+/// let points: [TriangulationPoint<f64>; 4] = [
+///     TriangulationPoint::new(0.0, 0.0),
+///     TriangulationPoint::new(0.0, 2.0),
+///     TriangulationPoint::new(2.0, 0.0),
+///     TriangulationPoint::new(2.0, 2.0)
+/// ];
+/// let triangles = [Triangle(0, 1, 2), Triangle(2, 1, 3)];
+/// let mut length = 0.0;
+/// for_each_edge(&points, &triangles, |a, b| length += (b.x - a.x).hypot(b.y - a.y));
+/// assert!((length - (8.0 + 8.0f64.sqrt())).abs() < 1e-9);
+/// ```
+pub fn for_each_edge<T, P, F>(points: &[P], triangles: &[Triangle], mut f: F)
+where
+    T: Coordinate,
+    P: Point<T>,
+    F: FnMut(&P, &P),
+{
+    for edge in edge_iter(triangles) {
+        f(&points[edge.0], &points[edge.1]);
+    }
+}
+
+/// Computes the neighbouring triangles of every triangle of the triangulation.
+///
+/// For every triangle, this returns the indices of the triangles across each of its edges, in the
+/// order of `Triangle::edges`. A boundary edge has no neighbour across it, and gets `None`.
+///
+/// Example:
+///
+/// ```rust
+/// use rtriangulate::{triangle_adjacency, Triangle};
+///
+/// let triangles = [Triangle(0, 1, 2), Triangle(2, 1, 3)];
+/// let adjacency = triangle_adjacency(&triangles);
+/// assert_eq!(adjacency, [[None, Some(1), None], [Some(0), None, None]]);
+/// ```
+pub fn triangle_adjacency(triangles: &[Triangle]) -> Vec<[Option<usize>; 3]> {
+    let mut adjacency = vec![[None; 3]; triangles.len()];
+    let mut open_edges = HashMap::<Edge, (usize, usize)>::with_capacity(triangles.len() * 2);
+    for (t, triangle) in triangles.iter().enumerate() {
+        for (k, edge) in triangle.edges().iter().enumerate() {
+            match open_edges.remove(edge) {
+                Some((other_t, other_k)) => {
+                    adjacency[t][k] = Some(other_t);
+                    adjacency[other_t][other_k] = Some(t);
+                }
+                None => {
+                    open_edges.insert(edge.clone(), (t, k));
+                }
+            }
+        }
+    }
+    adjacency
+}
+
+/// Returns the indices of the triangles with at least one boundary edge, in increasing order.
+///
+/// Those are the triangles with no neighbour across one of their edges, as given by
+/// `triangle_adjacency`. For the output of `triangulate`, they make the ring of triangles along the
+/// convex hull.
+///
+/// Example:
+///
+/// ```rust
+/// use rtriangulate::{hull_triangles, Triangle};
+///
+/// // A fan of four triangles around the vertex 4, and one more triangle beyond the first one.
+/// let triangles = [
+///     Triangle(0, 1, 4), Triangle(1, 2, 4), Triangle(2, 3, 4), Triangle(3, 0, 4),
+///     Triangle(1, 0, 5)
+/// ];
+/// assert_eq!(hull_triangles(&triangles), [1, 2, 3, 4]);
+/// ```
+pub fn hull_triangles(triangles: &[Triangle]) -> Vec<usize> {
+    triangle_adjacency(triangles)
+        .iter()
+        .enumerate()
+        .filter(|&(_, neighbours)| neighbours.contains(&None))
+        .map(|(t, _)| t)
+        .collect()
+}
+
+/// Removes the triangles for which `keep` returns false, keeping their adjacency up to date.
+///
+/// The `adjacency` must be the one computed by `triangle_adjacency` for the same triangles. The
+/// remaining triangles keep their relative order, and their neighbours are re-indexed accordingly:
+/// an edge shared with a removed triangle becomes a boundary edge, with no neighbour across it.
+/// This gives the same adjacency as computing it again for the remaining triangles, without going
+/// through a map of their edges.
+///
+/// Example:
+///
+/// ```rust
+/// use rtriangulate::{retain_triangles, triangle_adjacency, Triangle};
+///
+/// let mut triangles = vec![Triangle(0, 1, 2), Triangle(2, 1, 3), Triangle(2, 3, 4)];
+/// let mut adjacency = triangle_adjacency(&triangles);
+/// retain_triangles(&mut triangles, &mut adjacency, |t| t.0 != 0);
+/// assert_eq!(triangles, [Triangle(2, 1, 3), Triangle(2, 3, 4)]);
+/// assert_eq!(adjacency, [[None, None, Some(1)], [Some(0), None, None]]);
+/// ```
+pub fn retain_triangles<F>(
+    triangles: &mut Vec<Triangle>,
+    adjacency: &mut Vec<[Option<usize>; 3]>,
+    keep: F,
+) where
+    F: Fn(&Triangle) -> bool,
+{
+    assert_eq!(
+        triangles.len(),
+        adjacency.len(),
+        "the adjacency must have one entry per triangle"
+    );
+
+    // Give the kept triangles their new indices, then move them and their neighbours down.
+    let mut remap = Vec::with_capacity(triangles.len());
+    let mut kept = 0;
+    for t in triangles.iter() {
+        if keep(t) {
+            remap.push(Some(kept));
+            kept += 1;
+        } else {
+            remap.push(None);
+        }
+    }
+    for (old, &new) in remap.iter().enumerate() {
+        if let Some(new) = new {
+            triangles[new] = triangles[old];
+            let mut neighbours = adjacency[old];
+            for neighbour in &mut neighbours {
+                *neighbour = neighbour.and_then(|n| remap[n]);
+            }
+            adjacency[new] = neighbours;
+        }
+    }
+    triangles.truncate(kept);
+    adjacency.truncate(kept);
+}
+
+/// Computes the dual graph of the triangulation, as an adjacency list over triangles.
+///
+/// Entry `i` lists, in ascending order, the indices of the triangles sharing an edge with triangle
+/// `i`. Unlike `triangle_adjacency`, this doesn't tell which edge is shared, but it is simpler to
+/// feed to graph algorithms, such as a breadth-first search over the mesh. Triangles of the hull
+/// have fewer than three neighbours.
+///
+/// Example:
+///
+/// ```rust
+/// use rtriangulate::{dual_graph, Triangle};
+///
+/// let triangles = [Triangle(0, 1, 2), Triangle(2, 1, 3), Triangle(2, 3, 4)];
+/// assert_eq!(dual_graph(&triangles), [vec![1], vec![0, 2], vec![1]]);
+/// ```
+pub fn dual_graph(triangles: &[Triangle]) -> Vec<Vec<usize>> {
+    let mut edge_triangles = HashMap::<Edge, Vec<usize>>::with_capacity(triangles.len() * 2);
+    for (t, triangle) in triangles.iter().enumerate() {
+        for edge in triangle.edges().iter() {
+            edge_triangles.entry(edge.clone()).or_default().push(t);
+        }
+    }
+
+    let mut graph = vec![Vec::with_capacity(3); triangles.len()];
+    for sharing in edge_triangles.values() {
+        for &a in sharing {
+            graph[a].extend(sharing.iter().filter(|&&b| b != a));
+        }
+    }
+    for neighbours in &mut graph {
+        neighbours.sort_unstable();
+        neighbours.dedup();
+    }
+    graph
+}
+
+/// Finds a shortest path of triangles, going from one to the next across their shared edges.
+///
+/// The `adjacency` must be the one computed by `triangle_adjacency` for the same triangles. This
+/// is a breadth-first search over the dual graph, so the path crosses as few edges as possible,
+/// whatever the size of the triangles. It goes from `start` to `goal`, both included, and is
+/// `None` if the two triangles aren't connected.
+///
+/// Example:
+///
+/// ```rust
+/// use rtriangulate::{triangle_adjacency, triangle_path, Triangle};
+///
+/// let triangles = [Triangle(0, 1, 2), Triangle(2, 1, 3), Triangle(2, 3, 4), Triangle(5, 6, 7)];
+/// let adjacency = triangle_adjacency(&triangles);
+/// assert_eq!(triangle_path(&triangles, &adjacency, 0, 2), Some(vec![0, 1, 2]));
+/// assert_eq!(triangle_path(&triangles, &adjacency, 0, 3), None);
+/// ```
+pub fn triangle_path(
+    triangles: &[Triangle],
+    adjacency: &[[Option<usize>; 3]],
+    start: usize,
+    goal: usize,
+) -> Option<Vec<usize>> {
+    assert_eq!(triangles.len(), adjacency.len());
+
+    // Remember where every triangle was reached from, to walk the path back from the goal.
+    let mut previous = vec![None; triangles.len()];
+    previous[start] = Some(start);
+    let mut queue = VecDeque::new();
+    queue.push_back(start);
+    while let Some(t) = queue.pop_front() {
+        if t == goal {
+            let mut path = vec![goal];
+            let mut current = goal;
+            while current != start {
+                current = previous[current].unwrap();
+                path.push(current);
+            }
+            path.reverse();
+            return Some(path);
+        }
+        for &neighbour in adjacency[t].iter().flatten() {
+            if previous[neighbour].is_none() {
+                previous[neighbour] = Some(t);
+                queue.push_back(neighbour);
+            }
+        }
+    }
+    None
+}
+
+/// Groups the triangles into connected components, returning the triangle indices of each one.
+///
+/// Two triangles are connected if they share an edge: triangles only touching at a vertex belong
+/// to different components. The triangle indices of every component are in ascending order, and
+/// the components are sorted by their smallest triangle index, so the first component is always
+/// the one holding triangle 0.
+///
+/// Example:
+///
+/// ```rust
+/// use rtriangulate::{connected_components, Triangle};
+///
+/// let triangles = [Triangle(0, 1, 2), Triangle(3, 4, 5), Triangle(2, 1, 6)];
+/// assert_eq!(connected_components(&triangles), [vec![0, 2], vec![1]]);
+/// ```
+pub fn connected_components(triangles: &[Triangle]) -> Vec<Vec<usize>> {
+    // Union-find over the triangles, always keeping the smallest index as the root.
+    let mut parents: Vec<usize> = (0..triangles.len()).collect();
+    fn find(parents: &mut [usize], mut t: usize) -> usize {
+        while parents[t] != t {
+            parents[t] = parents[parents[t]];
+            t = parents[t];
+        }
+        t
+    }
+
+    let mut open_edges = HashMap::<Edge, usize>::with_capacity(triangles.len() * 2);
+    for (t, triangle) in triangles.iter().enumerate() {
+        for edge in triangle.edges().iter() {
+            let other = *open_edges.entry(edge.clone()).or_insert(t);
+            let (a, b) = (find(&mut parents, t), find(&mut parents, other));
+            parents[a.max(b)] = a.min(b);
+        }
+    }
+
+    // Roots are the smallest index of their component, so they are met in the expected order.
+    let mut components: Vec<Vec<usize>> = Vec::new();
+    let mut component_of = vec![0; triangles.len()];
+    for t in 0..triangles.len() {
+        let root = find(&mut parents, t);
+        if root == t {
+            component_of[t] = components.len();
+            components.push(vec![t]);
+        } else {
+            component_of[t] = component_of[root];
+            components[component_of[t]].push(t);
+        }
+    }
+    components
+}
+
+/// Returns the indices of the triangles using a vertex, in ascending order.
+///
+/// This is a plain scan of the triangles, which doesn't allocate anything but the result. When
+/// the triangles of many vertices are needed, `vertex_triangles` builds them all at once. To get
+/// them in order around the vertex, see `hull_vertex_fan`.
+///
+/// Example:
+///
+/// ```rust
+/// use rtriangulate::{triangles_containing, Triangle};
+///
+/// let triangles = [Triangle(0, 1, 2), Triangle(2, 1, 3), Triangle(2, 3, 4)];
+/// assert_eq!(triangles_containing(&triangles, 2), [0, 1, 2]);
+/// assert_eq!(triangles_containing(&triangles, 3), [1, 2]);
+/// assert!(triangles_containing(&triangles, 5).is_empty());
+/// ```
+pub fn triangles_containing(triangles: &[Triangle], vertex: usize) -> Vec<usize> {
+    triangles
+        .iter()
+        .enumerate()
+        .filter(|&(_, t)| t.0 == vertex || t.1 == vertex || t.2 == vertex)
+        .map(|(i, _)| i)
+        .collect()
+}
+
+/// Returns, for every point, the indices of the triangles using it, in ascending order.
+///
+/// This is the batch version of `triangles_containing`, for the `points_count` first points. Points
+/// which aren't used by any triangle get an empty list. Every vertex of the triangles must be
+/// lower than `points_count`.
+///
+/// Example:
+///
+/// ```rust
+/// use rtriangulate::{vertex_triangles, Triangle};
+///
+/// let triangles = [Triangle(0, 1, 2), Triangle(2, 1, 3)];
+/// assert_eq!(
+///     vertex_triangles(&triangles, 5),
+///     [vec![0], vec![0, 1], vec![0, 1], vec![1], vec![]]
+/// );
+/// ```
+pub fn vertex_triangles(triangles: &[Triangle], points_count: usize) -> Vec<Vec<usize>> {
+    // Count first, so that every list is allocated once, with the right size.
+    let mut counts = vec![0; points_count];
+    for t in triangles {
+        counts[t.0] += 1;
+        counts[t.1] += 1;
+        counts[t.2] += 1;
+    }
+
+    let mut map: Vec<Vec<usize>> = counts.into_iter().map(Vec::with_capacity).collect();
+    for (i, t) in triangles.iter().enumerate() {
+        map[t.0].push(i);
+        map[t.1].push(i);
+        map[t.2].push(i);
+    }
+    map
+}
+
+/// Returns the triangles incident to a vertex, in counter-clockwise order around it.
+///
+/// The `adjacency` must be the one computed by `triangle_adjacency` for the same triangles.
+///
+/// For an interior vertex, the triangles form a closed cycle: the last returned triangle shares an
+/// edge with the first one, and the cycle starts from an arbitrary triangle. For a vertex of the
+/// hull, however, the fan is open: there is a gap, outside of the triangulation, between the two
+/// boundary edges of the vertex. The first returned triangle then holds the boundary edge at the
+/// start of the fan, the last one holds the boundary edge at its end, and the gap lies between the
+/// last and the first triangles. With a single incident triangle, both boundary edges belong to it.
+///
+/// If the vertex has several gaps, which can happen once triangles have been removed (e.g. by
+/// `filter_long_edges`), only one of its fans is returned. If no triangle uses the vertex, the
+/// result is empty.
+///
+/// Example:
+///
+/// ```rust
+/// use rtriangulate::{hull_vertex_fan, triangle_adjacency, Triangle, TriangulationPoint};
+///
+/// let points = [
+///     TriangulationPoint::new(0.0, 0.0),
+///     TriangulationPoint::new(10.0, 0.0),
+///     TriangulationPoint::new(10.0, 10.0),
+///     TriangulationPoint::new(0.0, 10.0),
+/// ];
+/// let triangles = [Triangle(0, 2, 3), Triangle(0, 1, 2)];
+/// let adjacency = triangle_adjacency(&triangles);
+/// assert_eq!(hull_vertex_fan(&points, &triangles, &adjacency, 0), [1, 0]);
+/// assert_eq!(hull_vertex_fan(&points, &triangles, &adjacency, 1), [1]);
+/// ```
+pub fn hull_vertex_fan<T, P>(
+    points: &[P],
+    triangles: &[Triangle],
+    adjacency: &[[Option<usize>; 3]],
+    vertex: usize,
+) -> Vec<usize>
+where
+    T: Coordinate,
+    P: Point<T>,
+{
+    assert_eq!(triangles.len(), adjacency.len());
+
+    // The two edges of a triangle that contain the vertex, as (other vertex, neighbour) pairs.
+    let vertex_edges = |t: usize| {
+        let edges = triangles[t].edges();
+        let mut found = edges
+            .iter()
+            .zip(adjacency[t].iter())
+            .filter(|&(e, _)| e.0 == vertex || e.1 == vertex)
+            .map(|(e, &n)| (if e.0 == vertex { e.1 } else { e.0 }, n));
+        (found.next().unwrap(), found.next().unwrap())
+    };
+
+    let is_incident = |t: &Triangle| t.0 == vertex || t.1 == vertex || t.2 == vertex;
+    let incident = match triangles.iter().position(is_incident) {
+        Some(t) => t,
+        None => return Vec::new(),
+    };
+
+    // Looks for a boundary edge to start an open fan from, otherwise the fan is closed.
+    let start = (incident..triangles.len())
+        .filter(|&t| is_incident(&triangles[t]))
+        .find(|&t| {
+            let (first, second) = vertex_edges(t);
+            first.1.is_none() || second.1.is_none()
+        })
+        .unwrap_or(incident);
+    let (first, second) = vertex_edges(start);
+    let (open, entry, exit) = match (first.1, second.1) {
+        (None, _) => (true, first, second),
+        (_, None) => (true, second, first),
+        _ => (false, first, second),
+    };
+
+    let mut fan = vec![start];
+    let (mut previous, mut next) = (start, exit.1);
+    while let Some(current) = next {
+        if current == start {
+            break;
+        }
+        fan.push(current);
+        let (first, second) = vertex_edges(current);
+        next = if first.1 == Some(previous) {
+            second.1
+        } else {
+            first.1
+        };
+        previous = current;
+    }
+
+    // Leaving the first triangle through the edge to `exit` turns counter-clockwise if that edge
+    // comes after the one to `entry`.
+    let (v, a, b) = (&points[vertex], &points[entry.0], &points[exit.0]);
+    let turn = (a.x() - v.x()) * (b.y() - v.y()) - (a.y() - v.y()) * (b.x() - v.x());
+    if turn < T::zero() {
+        if open {
+            fan.reverse();
+        } else {
+            fan[1..].reverse();
+        }
+    }
+    fan
+}
+
+/// Returns the `k` points closest to a starting point, following the edges of the triangulation.
+///
+/// This expands from the `start` point along the Delaunay edges, always visiting next the reached
+/// point which is the closest to `start`, by Euclidean distance. The visited points are returned in
+/// that order, without `start` itself. As the Delaunay neighbours of a point are spatially close,
+/// this is a cheap approximation of the k-nearest neighbours, but it is graph-local: a point close
+/// to `start` can be missed if it's only reachable through farther ones, such as across a hole
+/// made by filtering triangles out. If fewer than `k` points are connected to `start`, they are
+/// all returned.
+///
+/// Example:
+///
+/// ```rust
+/// use rtriangulate::{k_nearest_graph, Triangle, TriangulationPoint};
+///
+/// let points = [
+///     TriangulationPoint::new(0.0, 0.0),
+///     TriangulationPoint::new(1.0, 3.0),
+///     TriangulationPoint::new(2.0, 0.0),
+///     TriangulationPoint::new(10.0, 1.0)
+/// ];
+/// let triangles = [Triangle(0, 1, 2), Triangle(2, 1, 3)];
+/// assert_eq!(k_nearest_graph(&points, &triangles, 0, 2), [2, 1]);
+/// assert_eq!(k_nearest_graph(&points, &triangles, 0, 10), [2, 1, 3]);
+/// ```
+pub fn k_nearest_graph<T, P>(
+    points: &[P],
+    triangles: &[Triangle],
+    start: usize,
+    k: usize,
+) -> Vec<usize>
+where
+    T: Coordinate,
+    P: Point<T>,
+{
+    let mut neighbours = vec![Vec::new(); points.len()];
+    for edge in edge_iter(triangles) {
+        neighbours[edge.0].push(edge.1);
+        neighbours[edge.1].push(edge.0);
+    }
+
+    let origin = &points[start];
+    let distance_sq = |i: usize| {
+        let (dx, dy) = (points[i].x() - origin.x(), points[i].y() - origin.y());
+        dx * dx + dy * dy
+    };
+
+    let mut reached = vec![false; points.len()];
+    reached[start] = true;
+    let mut frontier = vec![start];
+    let mut nearest = Vec::with_capacity(k);
+    while nearest.len() < k {
+        for &i in &neighbours[frontier.pop().unwrap()] {
+            if !reached[i] {
+                reached[i] = true;
+                frontier.push(i);
+            }
+        }
+        if frontier.is_empty() {
+            break;
+        }
+
+        // Keep the closest point last, so that it's the next one to be visited.
+        let mut closest = 0;
+        for j in 1..frontier.len() {
+            if distance_sq(frontier[j]) < distance_sq(frontier[closest]) {
+                closest = j;
+            }
+        }
+        let last = frontier.len() - 1;
+        frontier.swap(closest, last);
+        nearest.push(frontier[last]);
+    }
+    nearest
+}
+
+/// Counts, for every undirected edge of the triangulation, the number of triangles it belongs to.
+pub(crate) fn edge_counts(triangles: &[Triangle]) -> HashMap<Edge, usize> {
+    let mut counts = HashMap::<Edge, usize>::with_capacity(triangles.len() * 2);
+    for triangle in triangles {
+        for edge in triangle.edges().iter() {
+            *counts.entry(edge.clone()).or_insert(0) += 1;
+        }
+    }
+    counts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{triangulate, Triangle, TriangulationPoint};
+    use super::{
+        boundary_edges, connected_components, dedup_triangles, dual_graph, duplicate_triangles,
+        edge_iter, for_each_edge, for_each_triangle, hull_triangles, hull_vertex_fan,
+        is_boundary_edge, is_edge_manifold, k_nearest_graph, non_manifold_edges, retain_triangles,
+        triangle_adjacency, triangle_path, triangles_containing, vertex_triangles, Edge,
+    };
+
+    fn five_points() -> [TriangulationPoint<f64>; 5] {
+        [
+            TriangulationPoint::new(10.0, 10.0),
+            TriangulationPoint::new(15.0, 25.0),
+            TriangulationPoint::new(25.0, 15.0),
+            TriangulationPoint::new(30.0, 25.0),
+            TriangulationPoint::new(40.0, 15.0),
+        ]
+    }
+
+    #[test]
+    fn test_boundary_edges() {
+        let tris = triangulate(&five_points()).unwrap();
+
+        let edges = boundary_edges(&tris);
+        assert_eq!(edges.len(), 4);
+        for edge in &[Edge(0, 1), Edge(1, 3), Edge(3, 4), Edge(4, 0)] {
+            assert!(edges.contains(edge));
+            assert!(is_boundary_edge(&tris, edge));
+        }
+        for edge in &[Edge(1, 2), Edge(2, 0), Edge(2, 3), Edge(2, 4)] {
+            assert!(!edges.contains(edge));
+            assert!(!is_boundary_edge(&tris, edge));
+        }
+    }
+
+    #[test]
+    fn test_boundary_edge_direction() {
+        let tris = triangulate(&[
+            TriangulationPoint::new(10.0, 10.0),
+            TriangulationPoint::new(15.0, 25.0),
+            TriangulationPoint::new(25.0, 15.0),
+        ])
+        .unwrap();
+
+        assert!(is_boundary_edge(&tris, &Edge(0, 1)));
+        assert!(is_boundary_edge(&tris, &Edge(1, 0)));
+        assert!(!is_boundary_edge(&tris, &Edge(0, 5)));
+    }
+
+    #[test]
+    fn test_triangle_adjacency() {
+        let tris = triangulate(&five_points()).unwrap();
+        let adjacency = triangle_adjacency(&tris);
+        assert_eq!(adjacency.len(), tris.len());
+        for (t, neighbours) in adjacency.iter().enumerate() {
+            for (edge, neighbour) in tris[t].edges().iter().zip(neighbours.iter()) {
+                match *neighbour {
+                    Some(n) => assert!(n != t && tris[n].edges().contains(edge)),
+                    None => assert!(is_boundary_edge(&tris, edge)),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_retain_triangles() {
+        let points: Vec<_> = (0..25)
+            .map(|i| {
+                TriangulationPoint::new(
+                    (i / 5) as f64 * 10.0,
+                    (i % 5) as f64 * 10.0 + (i / 5) as f64,
+                )
+            })
+            .collect();
+        let mut tris = triangulate(&points).unwrap();
+        let mut adjacency = triangle_adjacency(&tris);
+
+        // Punch a hole around the middle point, and keep the adjacency of what is left.
+        retain_triangles(&mut tris, &mut adjacency, |t| {
+            t.0 != 12 && t.1 != 12 && t.2 != 12
+        });
+        assert!(!tris.is_empty());
+        assert!(tris.iter().all(|t| t.0 != 12 && t.1 != 12 && t.2 != 12));
+        assert_eq!(adjacency, triangle_adjacency(&tris));
+
+        retain_triangles(&mut tris, &mut adjacency, |_| false);
+        assert!(tris.is_empty() && adjacency.is_empty());
+    }
+
+    #[test]
+    fn test_hull_triangles() {
+        let grid: Vec<_> = (0..16)
+            .map(|i| TriangulationPoint::new((i / 4) as f64, (i % 4) as f64))
+            .collect();
+        let tris = triangulate(&grid).unwrap();
+        let boundary = boundary_edges(&tris);
+        let hull = hull_triangles(&tris);
+        let expected: Vec<usize> = (0..tris.len())
+            .filter(|&t| tris[t].edges().iter().any(|e| boundary.contains(e)))
+            .collect();
+        assert_eq!(hull, expected);
+
+        // The triangles of the middle square, at least, don't touch the hull.
+        assert!(hull.len() <= tris.len() - 2);
+        assert!(hull_triangles(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_hull_vertex_fan() {
+        let points = five_points();
+        let tris = triangulate(&points).unwrap();
+        let adjacency = triangle_adjacency(&tris);
+        let vertices = |fan: Vec<usize>| -> Vec<[usize; 3]> {
+            fan.into_iter()
+                .map(|t| {
+                    let mut v = [tris[t].0, tris[t].1, tris[t].2];
+                    v.sort();
+                    v
+                })
+                .collect()
+        };
+
+        // A hull vertex has an open fan, from one boundary edge to the other.
+        let fan = vertices(hull_vertex_fan(&points, &tris, &adjacency, 0));
+        assert_eq!(fan, [[0, 2, 4], [0, 1, 2]]);
+
+        // An interior vertex has a closed fan, starting anywhere.
+        let mut fan = vertices(hull_vertex_fan(&points, &tris, &adjacency, 2));
+        let first = fan.iter().position(|v| *v == [2, 3, 4]).unwrap();
+        fan.rotate_left(first);
+        assert_eq!(fan, [[2, 3, 4], [1, 2, 3], [0, 1, 2], [0, 2, 4]]);
+
+        assert!(hull_vertex_fan(&points, &tris, &adjacency, 5).is_empty());
+    }
+
+    #[test]
+    fn test_dual_graph() {
+        let tris = triangulate(&five_points()).unwrap();
+        let adjacency = triangle_adjacency(&tris);
+        let graph = dual_graph(&tris);
+        assert_eq!(graph.len(), tris.len());
+        for (neighbours, slots) in graph.iter().zip(adjacency.iter()) {
+            let mut expected: Vec<usize> = slots.iter().filter_map(|&n| n).collect();
+            expected.sort();
+            assert_eq!(*neighbours, expected);
+        }
+        assert!(dual_graph(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_triangle_path() {
+        let tris = triangulate(&five_points()).unwrap();
+        let adjacency = triangle_adjacency(&tris);
+        for start in 0..tris.len() {
+            for goal in 0..tris.len() {
+                let path = triangle_path(&tris, &adjacency, start, goal).unwrap();
+                assert_eq!((path[0], path[path.len() - 1]), (start, goal));
+                for step in path.windows(2) {
+                    assert!(adjacency[step[0]].contains(&Some(step[1])));
+                }
+            }
+        }
+        assert_eq!(triangle_path(&tris, &adjacency, 1, 1), Some(vec![1]));
+
+        // The first and last triangles only share a vertex, and must go round it.
+        let tris = [
+            Triangle(0, 1, 2),
+            Triangle(2, 1, 3),
+            Triangle(2, 3, 4),
+            Triangle(2, 4, 5),
+        ];
+        let adjacency = triangle_adjacency(&tris);
+        assert_eq!(
+            triangle_path(&tris, &adjacency, 0, 3),
+            Some(vec![0, 1, 2, 3])
+        );
+    }
+
+    #[test]
+    fn test_connected_components() {
+        let points = five_points();
+        let tris = triangulate(&points).unwrap();
+        assert_eq!(
+            connected_components(&tris),
+            [(0..tris.len()).collect::<Vec<_>>()]
+        );
+
+        // Splitting the mesh in two islands touching at a single vertex.
+        let tris = [
+            Triangle(3, 4, 5),
+            Triangle(0, 1, 2),
+            Triangle(5, 4, 6),
+            Triangle(2, 1, 3),
+            Triangle(6, 4, 7),
+        ];
+        assert_eq!(connected_components(&tris), [vec![0, 2, 4], vec![1, 3]]);
+        assert!(connected_components(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_vertex_triangles() {
+        let points = five_points();
+        let tris = triangulate(&points).unwrap();
+        let map = vertex_triangles(&tris, points.len() + 1);
+        assert_eq!(map.len(), points.len() + 1);
+        for (vertex, containing) in map.iter().enumerate() {
+            assert_eq!(*containing, triangles_containing(&tris, vertex));
+        }
+        assert_eq!(map[2].len(), 4);
+        assert!(map[5].is_empty());
+    }
+
+    #[test]
+    fn test_edge_iter() {
+        let tris = triangulate(&five_points()).unwrap();
+        let edges: Vec<Edge> = edge_iter(&tris).collect();
+        assert_eq!(edges.len(), 8);
+        for (i, edge) in edges.iter().enumerate() {
+            assert!(!edges[..i].contains(edge));
+            assert!(tris.iter().any(|t| t.edges()[..].contains(edge)));
+        }
+    }
+
+    #[test]
+    fn test_for_each_triangle() {
+        let points = five_points();
+        let tris = triangulate(&points).unwrap();
+        let mut visited = Vec::new();
+        for_each_triangle(&points, &tris, |a, b, c| visited.push([*a, *b, *c]));
+        let expected: Vec<_> = tris
+            .iter()
+            .map(|t| [points[t.0], points[t.1], points[t.2]])
+            .collect();
+        assert_eq!(visited, expected);
+    }
+
+    #[test]
+    fn test_for_each_edge() {
+        let points = five_points();
+        let tris = triangulate(&points).unwrap();
+        let mut visited = Vec::new();
+        for_each_edge(&points, &tris, |a, b| visited.push((*a, *b)));
+        let expected: Vec<_> = edge_iter(&tris)
+            .map(|e| (points[e.0], points[e.1]))
+            .collect();
+        assert_eq!(visited.len(), 8);
+        assert_eq!(visited, expected);
+    }
+
+    #[test]
+    fn test_k_nearest_graph() {
+        let points = five_points();
+        let tris = triangulate(&points).unwrap();
+        assert_eq!(k_nearest_graph(&points, &tris, 2, 0), []);
+        assert_eq!(k_nearest_graph(&points, &tris, 2, 2), [3, 1]);
+        assert_eq!(k_nearest_graph(&points, &tris, 2, 4), [3, 1, 4, 0]);
+        assert_eq!(k_nearest_graph(&points, &tris, 4, 10), [3, 2, 1, 0]);
+
+        // The two halves aren't connected.
+        let tris = [Triangle(0, 1, 2), Triangle(3, 4, 5)];
+        let points = [
+            TriangulationPoint::new(0.0, 0.0),
+            TriangulationPoint::new(0.0, 1.0),
+            TriangulationPoint::new(2.0, 0.0),
+            TriangulationPoint::new(0.0, 0.5),
+            TriangulationPoint::new(5.0, 5.0),
+            TriangulationPoint::new(5.0, 6.0),
+        ];
+        assert_eq!(k_nearest_graph(&points, &tris, 0, 5), [1, 2]);
+    }
+
+    #[test]
+    fn test_manifold() {
+        let tris = triangulate(&five_points()).unwrap();
+        assert!(is_edge_manifold(&tris));
+        assert!(non_manifold_edges(&tris).is_empty());
+
+        let mut tris = tris.clone();
+        tris.push(Triangle(3, 4, 5));
+        tris.push(Triangle(4, 3, 6));
+        tris.push(Triangle(0, 1, 7));
+        assert!(!is_edge_manifold(&tris));
+        assert_eq!(non_manifold_edges(&tris), [(Edge(3, 4), 3)]);
+    }
+
+    #[test]
+    fn test_duplicate_triangles() {
+        let tris = triangulate(&five_points()).unwrap();
+        assert!(duplicate_triangles(&tris).is_empty());
+
+        let mut duplicated = tris.clone();
+        duplicated.push(tris[1].canonical());
+        duplicated.push(Triangle(tris[0].1, tris[0].2, tris[0].0));
+        duplicated.push(Triangle(tris[2].0, tris[2].2, tris[2].1));
+        duplicated.push(tris[1]);
+        let count = tris.len();
+        assert_eq!(
+            duplicate_triangles(&duplicated),
+            [(1, count), (0, count + 1), (1, count + 3)]
+        );
+
+        dedup_triangles(&mut duplicated);
+        let mut expected = tris.clone();
+        expected.push(Triangle(tris[2].0, tris[2].2, tris[2].1));
+        assert_eq!(duplicated, expected);
+    }
+}
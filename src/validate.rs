@@ -0,0 +1,226 @@
+// Copyright 2017-2018 Samuel Loretan <tynril@gmail.com> -- See LICENSE file
+
+//! Verification of the properties of a triangulation.
+
+use std::collections::HashMap;
+
+use super::geometry::circumcenter_offset;
+use super::{Coordinate, Point, Triangle};
+
+/// Returns true if the triangles form a Delaunay triangulation of the points.
+///
+/// This checks the empty circumcircle property: no point can lie strictly inside the circumcircle
+/// of any triangle. Points on (or extremely close to) a circumcircle are accepted, as cocircular
+/// points can be triangulated in several valid ways. Degenerate triangles, which have no
+/// circumcircle, are ignored.
+///
+/// Every triangle is checked against every point, so this is a slow verification, meant for tests
+/// and debugging.
+///
+/// Example:
+///
+/// ```rust
+/// use rtriangulate::{is_delaunay, triangulate, TriangulationPoint, Triangle};
+///
+/// let points = [
+///     TriangulationPoint::new(0.0, 0.0),
+///     TriangulationPoint::new(1.0, 2.0),
+///     TriangulationPoint::new(2.0, 0.0),
+///     TriangulationPoint::new(3.0, 2.0)
+/// ];
+/// assert!(is_delaunay(&points, &triangulate(&points).unwrap()));
+/// assert!(!is_delaunay(&points, &[Triangle(0, 1, 3), Triangle(0, 3, 2)]));
+/// ```
+pub fn is_delaunay<T, P>(points: &[P], triangles: &[Triangle]) -> bool
+where
+    T: Coordinate,
+    P: Point<T>,
+{
+    first_delaunay_violation(points, triangles).is_none()
+}
+
+/// Finds the first triangle which has a point strictly inside of its circumcircle, returning the
+/// index of the triangle and of the point.
+pub(crate) fn first_delaunay_violation<T, P>(
+    points: &[P],
+    triangles: &[Triangle],
+) -> Option<(usize, usize)>
+where
+    T: Coordinate,
+    P: Point<T>,
+{
+    let tolerance = circle_tolerance();
+    for (i, t) in triangles.iter().enumerate() {
+        let (center_x, center_y, radius_sq) =
+            match circumcircle(&points[t.0], &points[t.1], &points[t.2]) {
+                Some(circle) => circle,
+                None => continue,
+            };
+
+        for (j, p) in points.iter().enumerate() {
+            if j == t.0 || j == t.1 || j == t.2 {
+                continue;
+            }
+            let (dx, dy) = (p.x() - center_x, p.y() - center_y);
+            let distance_sq = dx * dx + dy * dy;
+            if radius_sq - distance_sq > radius_sq * tolerance {
+                return Some((i, j));
+            }
+        }
+    }
+
+    None
+}
+
+/// The relative tolerance under which a point is considered to be on a circumcircle.
+///
+/// The factor of 1024 is built by doubling one, so that it can't fail for any coordinate type.
+#[inline(always)]
+pub(crate) fn circle_tolerance<T: Coordinate>() -> T {
+    (0..10).fold(T::epsilon(), |tolerance, _| tolerance + tolerance)
+}
+
+/// Computes the center and the squared radius of the circumcircle of a triangle, or `None` if the
+/// triangle is degenerate.
+pub(crate) fn circumcircle<T, P>(a: &P, b: &P, c: &P) -> Option<(T, T, T)>
+where
+    T: Coordinate,
+    P: Point<T>,
+{
+    let (x, y) = circumcenter_offset((a.x(), a.y()), (b.x(), b.y()), (c.x(), c.y()), T::zero())?;
+    if !x.is_finite() || !y.is_finite() {
+        return None;
+    }
+
+    Some((a.x() + x, a.y() + y, x * x + y * y))
+}
+
+/// Returns true if the triangles seem to cover the whole convex hull of the points.
+///
+/// The boundary of the triangles must be convex, with no reflex nor pinched vertex, and every point
+/// must be used by a triangle, except for the duplicates of a used point. The points must be
+/// sorted as `triangulate` expects, which puts the duplicates next to each other.
+pub(crate) fn covers_hull<T, P>(points: &[P], triangles: &[Triangle]) -> bool
+where
+    T: Coordinate,
+    P: Point<T>,
+{
+    if triangles.is_empty() {
+        return true;
+    }
+
+    let mut used = vec![false; points.len()];
+    for t in triangles {
+        used[t.0] = true;
+        used[t.1] = true;
+        used[t.2] = true;
+    }
+    let duplicates_used = |i: usize, j: usize| {
+        used[j] && points[i].x() == points[j].x() && points[i].y() == points[j].y()
+    };
+    let all_used = (0..points.len()).all(|i| {
+        used[i]
+            || (i > 0 && duplicates_used(i, i - 1))
+            || (i + 1 < points.len() && duplicates_used(i, i + 1))
+    });
+    if !all_used {
+        return false;
+    }
+
+    // Orient the boundary edges with their triangle on the left, and chain them.
+    let cross = |a: usize, b: usize, c: usize| {
+        let (a, b, c) = (&points[a], &points[b], &points[c]);
+        (b.x() - a.x()) * (c.y() - a.y()) - (b.y() - a.y()) * (c.x() - a.x())
+    };
+    let counts = super::topology::edge_counts(triangles);
+    let mut next = HashMap::<usize, usize>::new();
+    for t in triangles {
+        for (edge, &opposite) in t.edges().iter().zip(&[t.2, t.0, t.1]) {
+            if counts[edge] == 1 {
+                let (a, b) = if cross(edge.0, edge.1, opposite) < T::zero() {
+                    (edge.1, edge.0)
+                } else {
+                    (edge.0, edge.1)
+                };
+                if next.insert(a, b).is_some() {
+                    return false;
+                }
+            }
+        }
+    }
+
+    next.iter().all(|(&a, &b)| match next.get(&b) {
+        Some(&c) => cross(a, b, c) >= T::zero(),
+        None => false,
+    })
+}
+
+/// Panics with a descriptive message if the triangles aren't a valid Delaunay triangulation of
+/// the points, or if an edge is shared by more than two triangles.
+#[cfg(feature = "debug_validate")]
+pub(crate) fn assert_valid<T, P>(points: &[P], triangles: &[Triangle])
+where
+    T: Coordinate,
+    P: Point<T>,
+{
+    if let Some((triangle, point)) = first_delaunay_violation(points, triangles) {
+        panic!(
+            "invalid triangulation: point {} lies inside the circumcircle of triangle {} ({:?})",
+            point, triangle, triangles[triangle]
+        );
+    }
+
+    if let Some((edge, count)) = super::topology::non_manifold_edges(triangles).first() {
+        panic!(
+            "invalid triangulation: edge {:?} is shared by {} triangles",
+            edge, count
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{triangulate, Triangle, TriangulationPoint};
+    use super::{first_delaunay_violation, is_delaunay};
+
+    #[test]
+    fn test_is_delaunay() {
+        let points = [
+            TriangulationPoint::new(10.0, 10.0),
+            TriangulationPoint::new(15.0, 25.0),
+            TriangulationPoint::new(25.0, 15.0),
+            TriangulationPoint::new(30.0, 25.0),
+            TriangulationPoint::new(40.0, 15.0),
+        ];
+        assert!(is_delaunay(&points, &triangulate(&points).unwrap()));
+
+        // Flip the edge between the first two triangles.
+        let flipped = [
+            Triangle(0, 1, 3),
+            Triangle(0, 3, 2),
+            Triangle(0, 2, 4),
+            Triangle(2, 3, 4),
+        ];
+        assert!(!is_delaunay(&points, &flipped));
+        assert_eq!(first_delaunay_violation(&points, &flipped), Some((0, 2)));
+    }
+
+    #[test]
+    fn test_is_delaunay_cocircular() {
+        // The four corners of a square, which accept both diagonals.
+        let points = [
+            TriangulationPoint::new(0.0, 0.0),
+            TriangulationPoint::new(0.0, 1.0),
+            TriangulationPoint::new(1.0, 0.0),
+            TriangulationPoint::new(1.0, 1.0),
+        ];
+        assert!(is_delaunay(
+            &points,
+            &[Triangle(0, 1, 2), Triangle(2, 1, 3)]
+        ));
+        assert!(is_delaunay(
+            &points,
+            &[Triangle(0, 1, 3), Triangle(0, 3, 2)]
+        ));
+    }
+}
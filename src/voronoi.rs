@@ -0,0 +1,568 @@
+// Copyright 2017-2018 Samuel Loretan <tynril@gmail.com> -- See LICENSE file
+
+//! The Voronoi diagram, dual of the Delaunay triangulation.
+
+use num_traits::Float;
+
+use super::geometry::{circumcenter, clip_polygon};
+use super::topology::{triangle_adjacency, vertex_triangles};
+use super::{
+    sort_points, triangulate, Coordinate, Point, Result, Triangle, TriangulateError,
+    TriangulationPoint,
+};
+
+/// The Voronoi diagram of a set of points, made from their Delaunay triangulation.
+///
+/// Every triangle of the triangulation gives a vertex of the diagram, the center of its
+/// circumcircle, and every point gives a cell, made of the vertices of the triangles around it.
+/// The vertices are indexed like the triangles, and the cells like the points.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Voronoi<T> {
+    /// The vertices of the diagram, one per triangle, in the same order. Degenerate triangles,
+    /// whose circumcircle is undefined, have `None`.
+    pub vertices: Vec<Option<TriangulationPoint<T>>>,
+    /// The cell of every point, as indices into `vertices`, counter-clockwise around the point.
+    ///
+    /// A point of the hull has an unbounded cell: its first and last vertices are those of the
+    /// triangles holding the two boundary edges of the point, and the cell extends to infinity
+    /// from them, across those edges. A point used by no triangle has an empty cell.
+    pub cells: Vec<Vec<usize>>,
+    /// Whether the cell of every point is bounded, meaning that the point is surrounded by
+    /// triangles.
+    pub bounded: Vec<bool>,
+}
+
+/// Computes the Voronoi diagram of a set of points, from their Delaunay triangulation.
+///
+/// The `triangles` must index into `points`, as returned by `triangulate`. They only need to be
+/// consistently oriented, and edge-manifold: if triangles have been removed so that a point has
+/// several fans of triangles around it, its cell is only made of one of them.
+///
+/// Example:
+///
+/// ```rust
+/// use rtriangulate::{triangulate, voronoi, TriangulationPoint};
+///
+/// let points = [
+///     TriangulationPoint::new(0.0, 0.0),
+///     TriangulationPoint::new(0.0, 2.0),
+///     TriangulationPoint::new(1.0, 1.2),
+///     TriangulationPoint::new(2.0, 0.0),
+///     TriangulationPoint::new(2.0, 2.0)
+/// ];
+/// let triangles = triangulate(&points).unwrap();
+/// let diagram = voronoi(&points, &triangles);
+/// assert_eq!(diagram.vertices.len(), triangles.len());
+/// assert_eq!(diagram.bounded, [false, false, true, false, false]);
+/// assert_eq!(diagram.cells[2].len(), 4);
+/// ```
+pub fn voronoi<T, P>(points: &[P], triangles: &[Triangle]) -> Voronoi<T>
+where
+    T: Float,
+    P: Point<T>,
+{
+    let adjacency = triangle_adjacency(triangles);
+    let mut cells = Vec::with_capacity(points.len());
+    let mut bounded = Vec::with_capacity(points.len());
+    for (vertex, incident) in vertex_triangles(triangles, points.len())
+        .into_iter()
+        .enumerate()
+    {
+        let (cell, closed) = cell(points, triangles, &adjacency, vertex, incident);
+        cells.push(cell);
+        bounded.push(closed);
+    }
+
+    Voronoi {
+        vertices: triangles.iter().map(|t| circumcenter(points, t)).collect(),
+        cells,
+        bounded,
+    }
+}
+
+/// Returns the vertices of the Voronoi diagram, as a set of points.
+///
+/// These are the centers of the circumcircles of the triangles, in the same order as `triangles`,
+/// as in the `vertices` of `voronoi`. Degenerate triangles, whose circumcircle is undefined, are
+/// skipped: the points are then fewer than the triangles, and the `i`th point isn't the center of
+/// the `i`th triangle anymore.
+///
+/// Example:
+///
+/// ```rust
+/// use rtriangulate::{voronoi_vertices, Triangle, TriangulationPoint};
+///
+/// let points = [
+///     TriangulationPoint::new(0.0, 0.0),
+///     TriangulationPoint::new(0.0, 2.0),
+///     TriangulationPoint::new(2.0, 0.0),
+///     TriangulationPoint::new(4.0, 0.0)
+/// ];
+/// let triangles = [Triangle(0, 1, 2), Triangle(0, 2, 3)];
+/// assert_eq!(voronoi_vertices(&points, &triangles), [TriangulationPoint::new(1.0, 1.0)]);
+/// ```
+pub fn voronoi_vertices<T, P>(points: &[P], triangles: &[Triangle]) -> Vec<TriangulationPoint<T>>
+where
+    T: Float,
+    P: Point<T>,
+{
+    triangles
+        .iter()
+        .filter_map(|t| circumcenter(points, t))
+        .collect()
+}
+
+/// Computes the area of the Voronoi cell of every point, clipped to a rectangle.
+///
+/// The `triangles` must be a Delaunay triangulation of `points`, as returned by `triangulate`.
+/// The `bounds` are the `(min, max)` corners of the rectangle. The cells of the hull points are
+/// unbounded, so every cell is clipped to the rectangle to get a finite area: when all the points
+/// are within it, the areas add up to the area of the rectangle. A point used by no triangle has an
+/// area of zero.
+///
+/// The inverse of the area of its cell is a measure of the density of the points around a point.
+/// If the constants of the clipping can't be represented by `T`,
+/// `TriangulateError::UnsupportedCoordinate` is returned.
+///
+/// Example:
+///
+/// ```rust
+/// use rtriangulate::{triangulate, voronoi_cell_areas, TriangulationPoint};
+///
+/// let points = [
+///     TriangulationPoint::new(0.0, 0.0),
+///     TriangulationPoint::new(0.0, 2.0),
+///     TriangulationPoint::new(1.0, 1.0),
+///     TriangulationPoint::new(2.0, 0.0),
+///     TriangulationPoint::new(2.0, 2.0)
+/// ];
+/// let triangles = triangulate(&points).unwrap();
+/// let bounds = (TriangulationPoint::new(0.0, 0.0), TriangulationPoint::new(2.0, 2.0));
+/// let areas = voronoi_cell_areas(&points, &triangles, bounds).unwrap();
+/// assert!((areas[2] - 2.0f64).abs() < 1e-9);
+/// assert!((areas[0] - 0.5f64).abs() < 1e-9);
+/// ```
+pub fn voronoi_cell_areas<T, P>(
+    points: &[P],
+    triangles: &[Triangle],
+    bounds: (TriangulationPoint<T>, TriangulationPoint<T>),
+) -> Result<Vec<T>>
+where
+    T: Float + Coordinate,
+    P: Point<T>,
+{
+    let half = T::from_f64(0.5).ok_or(TriangulateError::UnsupportedCoordinate)?;
+    Ok(clipped_cells(points, triangles, bounds)?
+        .iter()
+        .map(|polygon| {
+            let doubled = (0..polygon.len()).fold(<T as Coordinate>::zero(), |area, k| {
+                let (a, b) = (&polygon[k], &polygon[(k + 1) % polygon.len()]);
+                area + a.x * b.y - b.x * a.y
+            });
+            Float::abs(doubled * half)
+        })
+        .collect())
+}
+
+/// Moves every point to the centroid of its Voronoi cell, weighted by a density, repeatedly.
+///
+/// This is Lloyd's relaxation, with the centroids weighted by `density`, which can't be negative
+/// within the rectangle given by the `(min, max)` corners of `bounds`: the points spread out
+/// evenly where the density is constant, and gather where it is high. This makes adaptive blue
+/// noise, such as to distribute samples by importance. With a constant density, this is the plain
+/// Lloyd's relaxation.
+///
+/// Every iteration triangulates the points, and clips their Voronoi cells to the rectangle. The
+/// weighted centroid of a cell is then approximated by cutting the cell into triangles, and every
+/// triangle into `samples * samples` smaller ones, evaluating the density at their centroids: more
+/// samples make for a more accurate centroid, at the cost of calling `density` more often (zero
+/// samples are taken as one). A point
+/// whose cell has no weight, such as the duplicate of another point, stays in place. The points
+/// don't need to be sorted, and are returned in their input order. This fails if a triangulation
+/// fails, as `triangulate` does, or with `TriangulateError::UnsupportedCoordinate` if the sample
+/// positions can't be represented by `T`.
+///
+/// Example:
+///
+/// ```rust
+/// use rtriangulate::{lloyd_relax_weighted, TriangulationPoint};
+///
+/// let points: Vec<_> = (0..16)
+///     .map(|i| TriangulationPoint::new((i / 4) as f64 + 0.5, (i % 4) as f64 + 0.5))
+///     .collect();
+/// let bounds = (TriangulationPoint::new(0.0, 0.0), TriangulationPoint::new(4.0, 4.0));
+///
+/// // The points move to the left, where the density is higher.
+/// let relaxed = lloyd_relax_weighted(&points, 5, bounds, 4, |p| 5.0 - p.x).unwrap();
+/// let mean_x = |points: &[TriangulationPoint<f64>]| {
+///     points.iter().map(|p| p.x).sum::<f64>() / points.len() as f64
+/// };
+/// assert!(mean_x(&relaxed) < mean_x(&points) - 0.1);
+/// ```
+pub fn lloyd_relax_weighted<T, P, F>(
+    points: &[P],
+    iterations: usize,
+    bounds: (TriangulationPoint<T>, TriangulationPoint<T>),
+    samples: usize,
+    density: F,
+) -> Result<Vec<TriangulationPoint<T>>>
+where
+    T: Float + Coordinate,
+    P: Point<T>,
+    F: Fn(&TriangulationPoint<T>) -> T,
+{
+    let mut relaxed: Vec<TriangulationPoint<T>> = points
+        .iter()
+        .map(|p| TriangulationPoint::new(p.x(), p.y()))
+        .collect();
+    for _ in 0..iterations {
+        let mut order: Vec<usize> = (0..relaxed.len()).collect();
+        order.sort_by(|&a, &b| sort_points(&relaxed[a], &relaxed[b]));
+        let sorted: Vec<TriangulationPoint<T>> = order.iter().map(|&i| relaxed[i]).collect();
+        let triangles = triangulate(&sorted)?;
+
+        for (cell, &i) in clipped_cells(&sorted, &triangles, bounds)?
+            .iter()
+            .zip(&order)
+        {
+            if let Some(centroid) = weighted_centroid(cell, samples.max(1), &density)? {
+                relaxed[i] = centroid;
+            }
+        }
+    }
+    Ok(relaxed)
+}
+
+/// Approximates the centroid of a convex polygon, weighted by a density, or returns `None` if the
+/// polygon has no weight, or an error if the sample positions can't be represented by `T`.
+///
+/// The polygon is cut into a fan of triangles, and every triangle into a grid of
+/// `samples * samples` smaller ones, sampled at their centroids.
+fn weighted_centroid<T, F>(
+    polygon: &[TriangulationPoint<T>],
+    samples: usize,
+    density: &F,
+) -> Result<Option<TriangulationPoint<T>>>
+where
+    T: Float + Coordinate,
+    F: Fn(&TriangulationPoint<T>) -> T,
+{
+    let to_coordinate =
+        |n: usize| T::from_f64(n as f64).ok_or(TriangulateError::UnsupportedCoordinate);
+    let (zero, one) = (<T as Coordinate>::zero(), <T as Coordinate>::one());
+    let two = one + one;
+    let n = to_coordinate(samples)?;
+    let step = one / (to_coordinate(3)? * n);
+    let (mut weight, mut x, mut y) = (zero, zero, zero);
+    for k in 1..polygon.len().saturating_sub(1) {
+        let (a, b, c) = (polygon[0], polygon[k], polygon[k + 1]);
+        let (ux, uy) = (b.x - a.x, b.y - a.y);
+        let (vx, vy) = (c.x - a.x, c.y - a.y);
+        let area = Float::abs(ux * vy - uy * vx) / (two * n * n);
+        let mut sample = |u: T, v: T| {
+            let p = TriangulationPoint::new(a.x + u * ux + v * vx, a.y + u * uy + v * vy);
+            let w = density(&p) * area;
+            weight = weight + w;
+            x = x + w * p.x;
+            y = y + w * p.y;
+        };
+
+        // The sub-triangles pointing up, then the ones pointing down between them.
+        for i in 0..samples {
+            for j in 0..(samples - i) {
+                let (u, v) = (to_coordinate(3 * i)?, to_coordinate(3 * j)?);
+                sample((u + one) * step, (v + one) * step);
+                if i + j + 1 < samples {
+                    sample((u + two) * step, (v + two) * step);
+                }
+            }
+        }
+    }
+
+    let (x, y) = (x / weight, y / weight);
+    if weight > zero && Float::is_finite(x) && Float::is_finite(y) {
+        Ok(Some(TriangulationPoint::new(x, y)))
+    } else {
+        Ok(None)
+    }
+}
+
+/// Computes the Voronoi cell of every point, as a polygon clipped to a rectangle, or an empty one
+/// for a point used by no triangle, or an error if the far reach of the cells can't be represented
+/// by `T`.
+fn clipped_cells<T, P>(
+    points: &[P],
+    triangles: &[Triangle],
+    bounds: (TriangulationPoint<T>, TriangulationPoint<T>),
+) -> Result<Vec<Vec<TriangulationPoint<T>>>>
+where
+    T: Float + Coordinate,
+    P: Point<T>,
+{
+    let diagram = voronoi(points, triangles);
+    let (min, max) = bounds;
+    let rectangle = [
+        min,
+        TriangulationPoint::new(max.x, min.y),
+        max,
+        TriangulationPoint::new(min.x, max.y),
+    ];
+    let eight = T::from_f64(8.0).ok_or(TriangulateError::UnsupportedCoordinate)?;
+
+    Ok((0..points.len())
+        .map(|i| {
+            let cell = &diagram.cells[i];
+            let mut polygon: Vec<(T, T)> = cell
+                .iter()
+                .filter_map(|&t| diagram.vertices[t])
+                .map(|v| (v.x, v.y))
+                .collect();
+            if polygon.is_empty() {
+                return Vec::new();
+            }
+
+            if !diagram.bounded[i] {
+                // Close the cell far enough outside of the bounds, along the rays leaving its
+                // first and last vertices across the boundary edges of the point.
+                let p = (points[i].x(), points[i].y());
+                let (first, last) = (polygon[0], polygon[polygon.len() - 1]);
+                let outward = |t: usize, clockwise_most: bool| {
+                    let t = &triangles[t];
+                    let others: Vec<usize> = [t.0, t.1, t.2]
+                        .iter()
+                        .cloned()
+                        .filter(|&v| v != i)
+                        .collect();
+                    let (a, b) = (&points[others[0]], &points[others[1]]);
+                    let cross = (a.x() - p.0) * (b.y() - p.1) - (a.y() - p.1) * (b.x() - p.0);
+                    let q = if (cross > <T as Coordinate>::zero()) == clockwise_most {
+                        a
+                    } else {
+                        b
+                    };
+                    let (dx, dy) = (q.x() - p.0, q.y() - p.1);
+                    let (x, y) = if clockwise_most { (dy, -dx) } else { (-dy, dx) };
+                    let length = (x * x + y * y).sqrt();
+                    (x / length, y / length)
+                };
+                let leaving = outward(cell[0], true);
+                let entering = outward(cell[cell.len() - 1], false);
+                let middle = {
+                    let (x, y) = (leaving.0 + entering.0, leaving.1 + entering.1);
+                    let length = (x * x + y * y).sqrt();
+                    (x / length, y / length)
+                };
+
+                let distance = |v: (T, T)| Float::abs(v.0 - min.x) + Float::abs(v.1 - min.y);
+                let reach = (max.x - min.x)
+                    + (max.y - min.y)
+                    + Float::max(Float::max(distance(p), distance(first)), distance(last));
+                let far = reach * eight;
+                polygon.insert(0, (first.0 + leaving.0 * far, first.1 + leaving.1 * far));
+                polygon.push((last.0 + entering.0 * far, last.1 + entering.1 * far));
+                polygon.push((p.0 + middle.0 * far, p.1 + middle.1 * far));
+            }
+
+            let polygon: Vec<_> = polygon
+                .into_iter()
+                .map(|(x, y)| TriangulationPoint::new(x, y))
+                .collect();
+            clip_polygon(polygon, &rectangle)
+        })
+        .collect())
+}
+
+/// Orders the triangles incident to a vertex counter-clockwise around it, and returns whether
+/// they make a closed fan.
+fn cell<T, P>(
+    points: &[P],
+    triangles: &[Triangle],
+    adjacency: &[[Option<usize>; 3]],
+    vertex: usize,
+    incident: Vec<usize>,
+) -> (Vec<usize>, bool)
+where
+    T: Float,
+    P: Point<T>,
+{
+    // The neighbours across the edges of a triangle leaving the vertex, in counter-clockwise
+    // order: the one before the triangle, then the one after it.
+    let neighbours = |t: usize| {
+        let triangle = &triangles[t];
+        let k = if triangle.0 == vertex {
+            0
+        } else if triangle.1 == vertex {
+            1
+        } else {
+            2
+        };
+        // The edge `k` starts from the vertex, and the edge `k + 2` ends at it.
+        let (leaving, entering) = (adjacency[t][k], adjacency[t][(k + 2) % 3]);
+        let (v, a, b) = (
+            &points[vertex],
+            &points[triangle.edges()[k].1],
+            &points[triangle.edges()[(k + 2) % 3].0],
+        );
+        let cross = (a.x() - v.x()) * (b.y() - v.y()) - (a.y() - v.y()) * (b.x() - v.x());
+        if cross > T::zero() {
+            (leaving, entering)
+        } else {
+            (entering, leaving)
+        }
+    };
+
+    let start = match incident.iter().find(|&&t| neighbours(t).0.is_none()) {
+        Some(&t) => t,
+        None => match incident.first() {
+            Some(&t) => t,
+            None => return (Vec::new(), false),
+        },
+    };
+    let mut fan = vec![start];
+    let mut next = neighbours(start).1;
+    while let Some(t) = next {
+        if t == start {
+            return (fan, true);
+        }
+        fan.push(t);
+        next = neighbours(t).1;
+    }
+    (fan, false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{circumcircles, sort_points, triangulate, TriangulationPoint};
+    use super::{lloyd_relax_weighted, voronoi, voronoi_cell_areas, voronoi_vertices};
+
+    #[test]
+    fn test_voronoi() {
+        let points: Vec<_> = (0..25)
+            .map(|i| {
+                let (x, y) = ((i / 5) as f64, (i % 5) as f64);
+                TriangulationPoint::new(x * 10.0 + y * 1.5, y * 10.0 - x * 0.5)
+            })
+            .collect();
+        let tris = triangulate(&points).unwrap();
+        let diagram = voronoi(&points, &tris);
+        assert_eq!(
+            voronoi_vertices(&points, &tris),
+            diagram
+                .vertices
+                .iter()
+                .filter_map(|&v| v)
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(
+            diagram.vertices,
+            circumcircles(&points, &tris)
+                .into_iter()
+                .map(|c| c.map(|c| c.0))
+                .collect::<Vec<_>>()
+        );
+
+        for (i, cell) in diagram.cells.iter().enumerate() {
+            // Consecutive vertices of a cell come from triangles sharing an edge with the point.
+            for pair in cell.windows(2) {
+                let (a, b) = (&tris[pair[0]], &tris[pair[1]]);
+                let shared = [a.0, a.1, a.2]
+                    .iter()
+                    .filter(|v| [b.0, b.1, b.2].contains(v))
+                    .count();
+                assert_eq!(shared, 2);
+            }
+
+            // The cells are counter-clockwise: their vertices turn around the point.
+            let p = &points[i];
+            let vertices: Vec<_> = cell.iter().map(|&t| diagram.vertices[t].unwrap()).collect();
+            for pair in vertices.windows(2) {
+                let (a, b) = (&pair[0], &pair[1]);
+                let turn = (a.x - p.x) * (b.y - p.y) - (a.y - p.y) * (b.x - p.x);
+                assert!(turn >= -1e-9);
+            }
+
+            // Only the points of the inner 3x3 grid are surrounded.
+            let (x, y) = (i / 5, i % 5);
+            let inner = x > 0 && x < 4 && y > 0 && y < 4;
+            assert_eq!(diagram.bounded[i], inner);
+        }
+    }
+
+    #[test]
+    fn test_voronoi_cell_areas() {
+        // The cells of a grid are squares, clipped to half-squares or quarter-squares on its sides.
+        let grid: Vec<_> = (0..25)
+            .map(|i| TriangulationPoint::new((i / 5) as f64, (i % 5) as f64))
+            .collect();
+        let tris = triangulate(&grid).unwrap();
+        let bounds = (
+            TriangulationPoint::new(0.0, 0.0),
+            TriangulationPoint::new(4.0, 4.0),
+        );
+        for (i, area) in voronoi_cell_areas(&grid, &tris, bounds)
+            .unwrap()
+            .into_iter()
+            .enumerate()
+        {
+            let (x, y) = (i / 5, i % 5);
+            let side = |c: usize| if c == 0 || c == 4 { 0.5 } else { 1.0 };
+            assert!((area - side(x) * side(y)).abs() < 1e-9);
+        }
+
+        // The cells of scattered points cover the whole rectangle.
+        let mut points: Vec<_> = (0..40)
+            .map(|i| {
+                let i = i as f64;
+                TriangulationPoint::new((i * 0.618).fract() * 10.0, (i * 0.755).fract() * 10.0)
+            })
+            .collect();
+        points.sort_by(sort_points);
+        let tris = triangulate(&points).unwrap();
+        let bounds = (
+            TriangulationPoint::new(-1.0, -1.0),
+            TriangulationPoint::new(11.0, 11.0),
+        );
+        let areas = voronoi_cell_areas(&points, &tris, bounds).unwrap();
+        assert!(areas.iter().all(|&a| a > 0.0));
+        assert!((areas.iter().sum::<f64>() - 144.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_lloyd_relax_weighted() {
+        // The points of a grid are already at the centroids of their cells.
+        let grid: Vec<_> = (0..25)
+            .map(|i| TriangulationPoint::new((i / 5) as f64, (i % 5) as f64))
+            .collect();
+        let bounds = (
+            TriangulationPoint::new(-0.5, -0.5),
+            TriangulationPoint::new(4.5, 4.5),
+        );
+        let relaxed = lloyd_relax_weighted(&grid, 3, bounds, 3, |_| 1.0).unwrap();
+        for (p, q) in grid.iter().zip(&relaxed) {
+            assert!((p.x - q.x).abs() < 1e-9 && (p.y - q.y).abs() < 1e-9);
+        }
+
+        // Scattered points gather where the density is high, and stay in the bounds.
+        let points: Vec<_> = (0..40)
+            .map(|i| {
+                let i = i as f64;
+                TriangulationPoint::new((i * 0.618).fract() * 10.0, (i * 0.755).fract() * 10.0)
+            })
+            .collect();
+        let bounds = (
+            TriangulationPoint::new(0.0, 0.0),
+            TriangulationPoint::new(10.0, 10.0),
+        );
+        let dense = |p: &TriangulationPoint<f64>| if p.x < 5.0 { 10.0 } else { 1.0 };
+        let relaxed = lloyd_relax_weighted(&points, 10, bounds, 4, dense).unwrap();
+        let left = |points: &[TriangulationPoint<f64>]| points.iter().filter(|p| p.x < 5.0).count();
+        assert!(left(&relaxed) > left(&points) + 2);
+        assert!(relaxed
+            .iter()
+            .all(|p| p.x >= 0.0 && p.x <= 10.0 && p.y >= 0.0 && p.y <= 10.0));
+        assert_eq!(
+            lloyd_relax_weighted(&points, 0, bounds, 4, dense).unwrap(),
+            points
+        );
+    }
+}
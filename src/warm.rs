@@ -0,0 +1,260 @@
+// Copyright 2017-2018 Samuel Loretan <tynril@gmail.com> -- See LICENSE file
+
+//! Triangulation of moving points, starting from their previous triangulation.
+
+use std::collections::HashMap;
+
+use super::validate::{circle_tolerance, circumcircle, covers_hull};
+use super::{triangulate_subset, Coordinate, Edge, Point, Result, Triangle};
+
+/// Generate the Delaunay triangulation of a set of points, repairing their previous one.
+///
+/// The `previous` triangles must have been computed for the same points, in the same order, before
+/// they moved. When the points only moved a little, most of those triangles are still Delaunay:
+/// the others are fixed by flipping the edges which aren't locally Delaunay anymore, until the
+/// whole mesh is, which only touches the regions that changed. If no point moved enough to change
+/// the triangulation, the previous triangles are returned as is.
+///
+/// The repair can't add nor remove triangles, so it isn't possible when the points moved too much.
+/// The points are then triangulated from scratch, which happens when:
+///
+/// * a triangle of `previous` references a point out of range, or got flipped over (it isn't in
+///   the same clockwise or counter-clockwise order as the others anymore),
+/// * the boundary of the previous triangles isn't convex anymore, or some points aren't used by
+///   any of them, as with an incomplete output of `triangulate` (see `triangulate_checked`),
+/// * the repair needs more edge flips than there are points.
+///
+/// As moving points can change their order, they don't need to be sorted: when triangulating
+/// from scratch, they are sorted into a copy first, as `triangulate_subset` does.
+///
+/// Example:
+///
+/// ```rust
+/// use rtriangulate::{triangulate, triangulate_warm, TriangulationPoint, Triangle};
+///
+/// let mut points = [
+///     TriangulationPoint::new(10.0, 10.0),
+///     TriangulationPoint::new(15.0, 25.0),
+///     TriangulationPoint::new(25.0, 15.0),
+///     TriangulationPoint::new(30.0, 25.0)
+/// ];
+/// let triangles = triangulate(&points).unwrap();
+/// assert_eq!(triangles, [Triangle(0, 1, 2), Triangle(2, 1, 3)]);
+///
+/// // Moving a point makes the other diagonal the Delaunay one.
+/// points[2] = TriangulationPoint::new(28.0, 2.0);
+/// assert_eq!(
+///     triangulate_warm(&points, &triangles).unwrap(),
+///     [Triangle(1, 3, 0), Triangle(3, 2, 0)]
+/// );
+/// ```
+pub fn triangulate_warm<T, P>(points: &[P], previous: &[Triangle]) -> Result<Vec<Triangle>>
+where
+    T: Coordinate,
+    P: Point<T>,
+{
+    match repair(points, previous) {
+        Some(triangles) => Ok(triangles),
+        None => {
+            let all: Vec<usize> = (0..points.len()).collect();
+            triangulate_subset(points, &all)
+        }
+    }
+}
+
+/// Repairs a triangulation by flipping its edges, or returns `None` if that isn't possible.
+fn repair<T, P>(points: &[P], previous: &[Triangle]) -> Option<Vec<Triangle>>
+where
+    T: Coordinate,
+    P: Point<T>,
+{
+    let in_range = |t: &Triangle| t.0 < points.len() && t.1 < points.len() && t.2 < points.len();
+    if previous.is_empty() || !previous.iter().all(in_range) || !covers_hull(points, previous) {
+        return None;
+    }
+
+    let orientation = |a: usize, b: usize, c: usize| {
+        let (a, b, c) = (&points[a], &points[b], &points[c]);
+        let cross = (b.x() - a.x()) * (c.y() - a.y()) - (b.y() - a.y()) * (c.x() - a.x());
+        if cross > T::zero() {
+            1
+        } else if cross < T::zero() {
+            -1
+        } else {
+            0
+        }
+    };
+    let winding = orientation(previous[0].0, previous[0].1, previous[0].2);
+    if winding == 0
+        || !previous
+            .iter()
+            .all(|t| orientation(t.0, t.1, t.2) == winding)
+    {
+        return None;
+    }
+
+    // The triangles on both sides of every edge.
+    let mut triangles = previous.to_vec();
+    let mut sides = HashMap::<Edge, Vec<usize>>::with_capacity(triangles.len() * 2);
+    for (i, t) in triangles.iter().enumerate() {
+        for edge in t.edges().iter() {
+            sides.entry(edge.clone()).or_default().push(i);
+        }
+    }
+
+    let tolerance = circle_tolerance::<T>();
+    let is_locally_delaunay = |triangles: &[Triangle], t: usize, opposite: usize| {
+        let t = &triangles[t];
+        match circumcircle(&points[t.0], &points[t.1], &points[t.2]) {
+            Some((center_x, center_y, radius_sq)) => {
+                let p = &points[opposite];
+                let (dx, dy) = (p.x() - center_x, p.y() - center_y);
+                radius_sq - (dx * dx + dy * dy) <= radius_sq * tolerance
+            }
+            None => true,
+        }
+    };
+
+    let mut pending: Vec<Edge> = sides.keys().cloned().collect();
+    pending.sort();
+    let mut flips = 0;
+    while let Some(edge) = pending.pop() {
+        let (t1, t2) = match sides.get(&edge).map(|s| &s[..]) {
+            Some(&[t1, t2]) => (t1, t2),
+            _ => continue,
+        };
+
+        // Orient the edge as it is in the first triangle: a → b, then c, and b → a, then d.
+        let (a, b, c) = rotate_to(&triangles[t1], &edge);
+        let d = third_vertex(&triangles[t2], a, b);
+        if is_locally_delaunay(&triangles, t1, d) {
+            continue;
+        }
+        if orientation(a, d, c) != winding || orientation(d, b, c) != winding {
+            continue;
+        }
+
+        flips += 1;
+        if flips > points.len() {
+            return None;
+        }
+        triangles[t1] = Triangle(a, d, c);
+        triangles[t2] = Triangle(d, b, c);
+        sides.remove(&edge);
+        sides.insert(Edge(c, d), vec![t1, t2]);
+        for &(ref outer, from, to) in &[
+            (Edge(a, d), t2, t1),
+            (Edge(b, c), t1, t2),
+            (Edge(c, a), t1, t1),
+            (Edge(d, b), t2, t2),
+        ] {
+            if let Some(side) = sides.get_mut(outer) {
+                for s in side.iter_mut().filter(|s| **s == from) {
+                    *s = to;
+                }
+            }
+            pending.push(outer.clone());
+        }
+    }
+
+    Some(triangles)
+}
+
+/// Returns the vertices of a triangle, starting with the two of an edge in the triangle's order.
+pub(crate) fn rotate_to(t: &Triangle, edge: &Edge) -> (usize, usize, usize) {
+    if t.2 != edge.0 && t.2 != edge.1 {
+        (t.0, t.1, t.2)
+    } else if t.0 != edge.0 && t.0 != edge.1 {
+        (t.1, t.2, t.0)
+    } else {
+        (t.2, t.0, t.1)
+    }
+}
+
+/// Returns the vertex of a triangle which isn't `a` nor `b`.
+pub(crate) fn third_vertex(t: &Triangle, a: usize, b: usize) -> usize {
+    [t.0, t.1, t.2]
+        .iter()
+        .cloned()
+        .find(|&v| v != a && v != b)
+        .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{
+        is_delaunay, sort_points, triangulate, triangulate_checked, Triangle, TriangulateExt,
+        TriangulationPoint,
+    };
+    use super::triangulate_warm;
+
+    fn scattered_points() -> Vec<TriangulationPoint<f64>> {
+        let mut points: Vec<_> = (0..40)
+            .map(|i| TriangulationPoint::new((i * 37 % 101) as f64, (i * 59 % 97) as f64))
+            .collect();
+        for &(x, y) in &[
+            (-20.0, -20.0),
+            (-20.0, 120.0),
+            (120.0, -20.0),
+            (120.0, 120.0),
+        ] {
+            points.push(TriangulationPoint::new(x, y));
+        }
+        points.sort_by(sort_points);
+        points
+    }
+
+    fn canonical(triangles: Vec<Triangle>) -> Vec<Triangle> {
+        let mut triangles: Vec<_> = triangles.iter().map(Triangle::canonical).collect();
+        triangles.sort();
+        triangles
+    }
+
+    #[test]
+    fn test_warm_unchanged() {
+        let points = scattered_points();
+        let triangles = triangulate(&points).unwrap();
+        assert_eq!(triangulate_warm(&points, &triangles).unwrap(), triangles);
+    }
+
+    #[test]
+    fn test_warm_small_moves() {
+        let mut points = scattered_points();
+        let (triangles, complete) = triangulate_checked(&points).unwrap();
+        assert!(complete);
+        for (i, p) in points.iter_mut().enumerate() {
+            let offset = ((i * 7) % 5) as f64 * 0.4 - 0.8;
+            p.x += offset;
+            p.y -= offset * 0.5;
+        }
+
+        let warm = triangulate_warm(&points, &triangles).unwrap();
+        assert_eq!(warm.len(), triangles.len());
+        assert!(is_delaunay(&points, &warm));
+        assert_eq!(
+            canonical(warm),
+            canonical(points.triangulate_unsorted().unwrap())
+        );
+    }
+
+    #[test]
+    fn test_warm_fallback() {
+        let mut points = scattered_points();
+        let triangles = triangulate(&points).unwrap();
+
+        // Moving a point out of the hull flips its triangles over.
+        points[20] = TriangulationPoint::new(300.0, 50.0);
+        assert_eq!(
+            triangulate_warm(&points, &triangles).unwrap(),
+            points.triangulate_unsorted().unwrap()
+        );
+        assert_eq!(
+            triangulate_warm(&points, &[Triangle(0, 1, 99)]).unwrap(),
+            points.triangulate_unsorted().unwrap()
+        );
+        assert_eq!(
+            triangulate_warm(&points, &[]).unwrap(),
+            points.triangulate_unsorted().unwrap()
+        );
+    }
+}
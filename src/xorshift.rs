@@ -0,0 +1,55 @@
+// Copyright 2017-2018 Samuel Loretan <tynril@gmail.com> -- See LICENSE file
+
+//! A small pseudo-random number generator, for reproducible shuffles and test inputs.
+
+/// A xorshift64* pseudo-random number generator.
+pub(crate) struct XorShift(u64);
+
+impl XorShift {
+    /// Makes a new generator from a seed. Any seed is valid, including zero.
+    pub(crate) fn new(seed: u64) -> Self {
+        // The state must never be zero, so mix the seed with an arbitrary odd constant.
+        XorShift((seed ^ 0x9e37_79b9_7f4a_7c15) | 1)
+    }
+
+    /// Returns the next pseudo-random 64 bits number.
+    pub(crate) fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_f491_4f6c_dd1d)
+    }
+
+    /// Returns the next pseudo-random number in `[0, 1[`.
+    #[cfg(any(test, feature = "test-util"))]
+    pub(crate) fn next_f64(&mut self) -> f64 {
+        (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+    }
+
+    /// Shuffles the elements of a slice in place (Fisher-Yates).
+    pub(crate) fn shuffle<E>(&mut self, elements: &mut [E]) {
+        for i in (1..elements.len()).rev() {
+            let j = self.next_u64() % (i as u64 + 1);
+            elements.swap(i, j as usize);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::XorShift;
+
+    #[test]
+    fn test_shuffle() {
+        let mut elements: Vec<usize> = (0..100).collect();
+        XorShift::new(42).shuffle(&mut elements);
+        assert_ne!(elements, (0..100).collect::<Vec<_>>());
+
+        let mut again: Vec<usize> = (0..100).collect();
+        XorShift::new(42).shuffle(&mut again);
+        assert_eq!(elements, again);
+
+        elements.sort();
+        assert_eq!(elements, (0..100).collect::<Vec<_>>());
+    }
+}
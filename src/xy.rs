@@ -0,0 +1,112 @@
+// Copyright 2017-2018 Samuel Loretan <tynril@gmail.com> -- See LICENSE file
+
+//! Reading and writing points in a plain text format, with one `x y` point per line.
+
+use std::io::{BufRead, BufReader, Error, ErrorKind, Read, Result, Write};
+
+use super::{Point, TriangulationPoint};
+
+/// Reads points written one per line, as two whitespace-separated coordinates.
+///
+/// Everything following a `#` on a line is a comment, and is ignored along with empty lines. The
+/// input is parsed as it is read, without being loaded in memory first. A line which doesn't hold
+/// exactly two numbers makes for an `InvalidData` error, whose message gives the line number.
+///
+/// Example:
+///
+/// ```rust
+/// use rtriangulate::{read_xy, TriangulationPoint};
+///
+/// let mut input = "# Some points\n10 50\n25.5 40 # The middle one\n\n30 40\n".as_bytes();
+/// assert_eq!(
+///     read_xy(&mut input).unwrap(),
+///     [
+///         TriangulationPoint::new(10.0, 50.0),
+///         TriangulationPoint::new(25.5, 40.0),
+///         TriangulationPoint::new(30.0, 40.0)
+///     ]
+/// );
+/// ```
+pub fn read_xy<R>(r: &mut R) -> Result<Vec<TriangulationPoint<f64>>>
+where
+    R: Read,
+{
+    let mut points = Vec::new();
+    for (number, line) in BufReader::new(r).lines().enumerate() {
+        let line = line?;
+        let content = match line.find('#') {
+            Some(comment) => &line[..comment],
+            None => &line[..],
+        };
+
+        let mut values = content.split_whitespace().map(str::parse::<f64>);
+        match (values.next(), values.next(), values.next()) {
+            (None, _, _) => (),
+            (Some(Ok(x)), Some(Ok(y)), None) => points.push(TriangulationPoint::new(x, y)),
+            _ => {
+                return Err(Error::new(
+                    ErrorKind::InvalidData,
+                    format!("line {}: expected two numbers, got {:?}", number + 1, line),
+                ))
+            }
+        }
+    }
+    Ok(points)
+}
+
+/// Writes points one per line, as two space-separated coordinates.
+///
+/// This is the format `read_xy` reads. The coordinates are written with as many digits as needed
+/// to be read back exactly.
+///
+/// Example:
+///
+/// ```rust
+/// use rtriangulate::{write_xy, TriangulationPoint};
+///
+/// let points = [TriangulationPoint::new(10.0, 50.0), TriangulationPoint::new(25.5, -4.0)];
+/// let mut output = Vec::new();
+/// write_xy(&points, &mut output).unwrap();
+/// assert_eq!(String::from_utf8(output).unwrap(), "10 50\n25.5 -4\n");
+/// ```
+pub fn write_xy<P, W>(points: &[P], w: &mut W) -> Result<()>
+where
+    P: Point<f64>,
+    W: Write,
+{
+    for p in points {
+        writeln!(w, "{} {}", p.x(), p.y())?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::TriangulationPoint;
+    use super::{read_xy, write_xy};
+
+    #[test]
+    fn test_round_trip() {
+        let points = [
+            TriangulationPoint::new(0.1, 1.0 / 3.0),
+            TriangulationPoint::new(-1e-300, 12345678.9),
+            TriangulationPoint::new(f64::MAX, f64::MIN_POSITIVE),
+        ];
+        let mut buffer = Vec::new();
+        write_xy(&points, &mut buffer).unwrap();
+        assert_eq!(read_xy(&mut &buffer[..]).unwrap(), points);
+    }
+
+    #[test]
+    fn test_invalid_lines() {
+        for &(input, line) in &[
+            ("1 2\n3\n", 2),
+            ("1 2\n# Fine\n3 4 5\n", 3),
+            ("1 2\n\n3 four\n", 3),
+        ] {
+            let error = read_xy(&mut input.as_bytes()).unwrap_err();
+            assert!(error.to_string().starts_with(&format!("line {}: ", line)));
+        }
+        assert!(read_xy(&mut "".as_bytes()).unwrap().is_empty());
+    }
+}